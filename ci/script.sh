@@ -40,6 +40,8 @@ run_test_suite() {
 
     cargo build --target $TARGET --verbose
     cargo test --target $TARGET -j 1
+    cargo test --target $TARGET -j 1 --features bitset-liberties
+    cargo test --target $TARGET -j 1 --features tree-walk-patterns
 }
 
 main() {
@@ -0,0 +1,35 @@
+#!/bin/sh
+#
+# A minimal GTP engine for the tests of the match referee. It accepts
+# every command and answers genmove depending on the mode given as the
+# first argument:
+#
+#   pass     always passes (the default)
+#   resign   always resigns
+#   illegal  answers with a vertex that isn't on the board
+#   hang     never answers
+#
+mode=${1:-pass}
+while read -r line; do
+    set -- $line
+    id=""
+    case "$1" in
+        [0-9]*) id=$1; shift ;;
+    esac
+    case "$1" in
+        quit)
+            printf '=%s\n\n' "$id"
+            exit 0 ;;
+        name)
+            printf '=%s stub\n\n' "$id" ;;
+        genmove)
+            case "$mode" in
+                hang) exec sleep 60 ;;
+                resign) printf '=%s resign\n\n' "$id" ;;
+                illegal) printf '=%s Z99\n\n' "$id" ;;
+                *) printf '=%s pass\n\n' "$id" ;;
+            esac ;;
+        *)
+            printf '=%s\n\n' "$id" ;;
+    esac
+done
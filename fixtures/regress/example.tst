@@ -0,0 +1,12 @@
+# An example suite for the tests of the regression runner.
+
+loadsgf ../sgf/twomoves.sgf
+
+10 reg_genmove b
+#? [C3|D4]
+
+20 reg_genmove w
+#? [!pass]
+
+30 name
+#? [Iomrascalai]*
@@ -0,0 +1,183 @@
+/************************************************************************
+ *                                                                      *
+ * Copyright 2016 Urban Hafner                                          *
+ *                                                                      *
+ * This file is part of Iomrascálaí.                                    *
+ *                                                                      *
+ * Iomrascálaí is free software: you can redistribute it and/or modify  *
+ * it under the terms of the GNU General Public License as published by *
+ * the Free Software Foundation, either version 3 of the License, or    *
+ * (at your option) any later version.                                  *
+ *                                                                      *
+ * Iomrascálaí is distributed in the hope that it will be useful,       *
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of       *
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the        *
+ * GNU General Public License for more details.                         *
+ *                                                                      *
+ * You should have received a copy of the GNU General Public License    *
+ * along with Iomrascálaí.  If not, see <http://www.gnu.org/licenses/>. *
+ *                                                                      *
+ ************************************************************************/
+
+use board::Size;
+use config::Config;
+use engine::Engine;
+use game::Game;
+use playout::Playout;
+use version;
+
+use rand::weak_rng;
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+use std::time::Instant;
+
+mod test;
+
+// How long the raw playouts are run for each measurement.
+const PLAYOUT_SECONDS: u64 = 2;
+
+/// The values of one measurement over all repetitions.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Measurement {
+    name: &'static str,
+    values: Vec<f64>,
+}
+
+impl Measurement {
+
+    pub fn new(name: &'static str) -> Measurement {
+        Measurement {
+            name: name,
+            values: vec!(),
+        }
+    }
+
+    pub fn add(&mut self, value: f64) {
+        self.values.push(value);
+    }
+
+    pub fn mean(&self) -> f64 {
+        self.values.iter().sum::<f64>() / self.values.len() as f64
+    }
+
+    /// The sample standard deviation (0 for a single value).
+    pub fn stddev(&self) -> f64 {
+        if self.values.len() < 2 {
+            return 0.0;
+        }
+        let mean = self.mean();
+        let squares: f64 = self.values.iter().map(|v| (v - mean) * (v - mean)).sum();
+        (squares / (self.values.len() - 1) as f64).sqrt()
+    }
+
+    pub fn min(&self) -> f64 {
+        self.values.iter().cloned().fold(::std::f64::INFINITY, f64::min)
+    }
+
+    pub fn max(&self) -> f64 {
+        self.values.iter().cloned().fold(::std::f64::NEG_INFINITY, f64::max)
+    }
+
+    fn to_json(&self) -> String {
+        let values: Vec<String> = self.values.iter().map(|&v| json_number(v)).collect();
+        format!("\"{}\": {{\"mean\": {}, \"stddev\": {}, \"min\": {}, \"max\": {}, \"values\": [{}]}}",
+                self.name,
+                json_number(self.mean()),
+                json_number(self.stddev()),
+                json_number(self.min()),
+                json_number(self.max()),
+                values.join(", "))
+    }
+}
+
+/// Measures the speed of the playouts on their own (with one and with
+/// all threads) and of full searches, and the size of the resulting
+/// search trees.
+pub struct Benchmark {
+    config: Arc<Config>,
+    engine: Engine,
+    playouts: usize,
+    size: Size,
+}
+
+impl Benchmark {
+
+    /// `playouts` is the number of playouts of each search.
+    pub fn new(config: Arc<Config>, engine: Engine, size: Size, playouts: usize) -> Benchmark {
+        Benchmark {
+            config: config,
+            engine: engine,
+            playouts: playouts,
+            size: size,
+        }
+    }
+
+    /// Runs all measurements `repetitions` times and returns the
+    /// results as JSON.
+    pub fn run(&mut self, repetitions: usize) -> String {
+        let mut measurements = vec!(
+            Measurement::new("playouts_per_second_single_thread"),
+            Measurement::new("playouts_per_second_all_threads"),
+            Measurement::new("search_playouts_per_second"),
+            Measurement::new("tree_nodes"),
+            Measurement::new("tree_depth"),
+        );
+        let game = Game::with_super_ko(self.size, 6.5, self.config.ruleset, self.config.super_ko);
+        let duration = Duration::from_secs(PLAYOUT_SECONDS);
+        for repetition in 0..repetitions {
+            info!(self.config, "Benchmark repetition {} of {}", repetition + 1, repetitions);
+            measurements[0].add(Self::playout_speed(self.engine.playout(), &game, 1, duration));
+            measurements[1].add(Self::playout_speed(self.engine.playout(), &game, self.config.threads, duration));
+            let started = Instant::now();
+            self.engine.search_best_move(&game, self.playouts);
+            let stats = self.engine.tree_stats();
+            measurements[2].add(stats.playouts as f64 / seconds(started.elapsed()));
+            measurements[3].add(stats.nodes as f64);
+            measurements[4].add(stats.depth as f64);
+        }
+        let results: Vec<String> = measurements.iter().map(|m| format!("    {}", m.to_json())).collect();
+        format!("{{\n  \"version\": \"{}\",\n  \"size\": \"{}\",\n  \"ruleset\": \"{}\",\n  \"threads\": {},\n  \"search_playouts\": {},\n  \"repetitions\": {},\n  \"results\": {{\n{}\n  }}\n}}",
+                version::version(),
+                self.size,
+                self.config.ruleset,
+                self.config.threads,
+                self.playouts,
+                repetitions,
+                results.join(",\n"))
+    }
+
+    // Playouts per second from the empty board without a tree.
+    fn playout_speed(playout: Arc<Playout>, game: &Game, threads: usize, duration: Duration) -> f64 {
+        let started = Instant::now();
+        let handles: Vec<_> = (0..threads).map(|_| {
+            let playout = playout.clone();
+            let board = game.board();
+            thread::spawn(move || {
+                let mut rng = weak_rng();
+                let mut count: usize = 0;
+                while started.elapsed() < duration {
+                    playout.run(&mut board.clone(), None, &mut rng);
+                    count += 1;
+                }
+                count
+            })
+        }).collect();
+        let count: usize = handles.into_iter().map(|h| h.join().unwrap()).sum();
+        count as f64 / seconds(started.elapsed())
+    }
+
+}
+
+fn seconds(duration: Duration) -> f64 {
+    duration.as_secs() as f64 + duration.subsec_nanos() as f64 / 1e9
+}
+
+// JSON has no representation of infinity and NaN.
+fn json_number(n: f64) -> String {
+    if n.is_finite() {
+        format!("{}", n)
+    } else {
+        "null".to_string()
+    }
+}
@@ -0,0 +1,61 @@
+/************************************************************************
+ *                                                                      *
+ * Copyright 2016 Urban Hafner                                          *
+ *                                                                      *
+ * This file is part of Iomrascálaí.                                    *
+ *                                                                      *
+ * Iomrascálaí is free software: you can redistribute it and/or modify  *
+ * it under the terms of the GNU General Public License as published by *
+ * the Free Software Foundation, either version 3 of the License, or    *
+ * (at your option) any later version.                                  *
+ *                                                                      *
+ * Iomrascálaí is distributed in the hope that it will be useful,       *
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of       *
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the        *
+ * GNU General Public License for more details.                         *
+ *                                                                      *
+ * You should have received a copy of the GNU General Public License    *
+ * along with Iomrascálaí.  If not, see <http://www.gnu.org/licenses/>. *
+ *                                                                      *
+ ************************************************************************/
+
+#![cfg(test)]
+
+use super::Measurement;
+use super::json_number;
+
+fn measurement(values: &[f64]) -> Measurement {
+    let mut measurement = Measurement::new("pps");
+    for &value in values {
+        measurement.add(value);
+    }
+    measurement
+}
+
+#[test]
+fn statistics_of_the_values() {
+    let m = measurement(&[2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0]);
+    assert_eq!(5.0, m.mean());
+    assert_eq!(2.0, m.min());
+    assert_eq!(9.0, m.max());
+    assert!((m.stddev() - 2.138).abs() < 0.001);
+}
+
+#[test]
+fn a_single_value_has_no_deviation() {
+    assert_eq!(0.0, measurement(&[3.0]).stddev());
+}
+
+#[test]
+fn measurements_as_json() {
+    let m = measurement(&[1.0, 3.0]);
+    assert_eq!(
+        "\"pps\": {\"mean\": 2, \"stddev\": 1.4142135623730951, \"min\": 1, \"max\": 3, \"values\": [1, 3]}",
+        m.to_json());
+}
+
+#[test]
+fn infinity_is_null_in_json() {
+    assert_eq!("null", json_number(::std::f64::INFINITY));
+    assert_eq!("0.5", json_number(0.5));
+}
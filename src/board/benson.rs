@@ -0,0 +1,167 @@
+/************************************************************************
+ *                                                                      *
+ * Copyright 2016 Urban Hafner                                          *
+ *                                                                      *
+ * This file is part of Iomrascálaí.                                    *
+ *                                                                      *
+ * Iomrascálaí is free software: you can redistribute it and/or modify  *
+ * it under the terms of the GNU General Public License as published by *
+ * the Free Software Foundation, either version 3 of the License, or    *
+ * (at your option) any later version.                                  *
+ *                                                                      *
+ * Iomrascálaí is distributed in the hope that it will be useful,       *
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of       *
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the        *
+ * GNU General Public License for more details.                         *
+ *                                                                      *
+ * You should have received a copy of the GNU General Public License    *
+ * along with Iomrascálaí.  If not, see <http://www.gnu.org/licenses/>. *
+ *                                                                      *
+ ************************************************************************/
+
+use board::Black;
+use board::Board;
+use board::Color;
+use board::Coord;
+use board::Empty;
+use board::Size;
+use board::White;
+
+// A maximal connected set of points that aren't stones of the
+// enclosing color, together with the chains that enclose it.
+struct Region {
+    chains: Vec<usize>,
+    coords: Vec<Coord>,
+    has_stones: bool,
+    vital_to: Vec<usize>,
+}
+
+impl Board {
+
+    /// Returns the ids of the chains of `color` that are
+    /// unconditionally alive according to Benson's algorithm, i.e.
+    /// that can't be captured even if `color` always passes.
+    pub fn unconditionally_alive_chains(&self, color: Color) -> Vec<usize> {
+        self.benson(color).0
+    }
+
+    /// Returns the pass-alive area of both players, indexed like
+    /// `Coord::to_index()`. A point belongs to a player if it's part
+    /// of one of their unconditionally alive chains or of a region
+    /// that is enclosed by them and vital to at least one of them.
+    /// Stones of the opponent in such a region are dead. All other
+    /// points are `Empty`.
+    pub fn pass_alive_area(&self) -> Vec<Color> {
+        let mut area = vec![Empty; self.size.points()];
+        for &color in [Black, White].iter() {
+            let (alive, regions) = self.benson(color);
+            for &id in alive.iter() {
+                for coord in self.chains[id].coords().iter() {
+                    area[coord.to_index(self.size)] = color;
+                }
+            }
+            for region in regions.iter().filter(|r| Self::is_territory(r, &alive)) {
+                for coord in region.coords.iter() {
+                    area[coord.to_index(self.size)] = color;
+                }
+            }
+        }
+        area
+    }
+
+    /// Returns for every point (indexed like `Coord::to_index()`)
+    /// if it's an empty point of pass-alive territory without any
+    /// stones in it. Playing there can't change the outcome of the
+    /// game for either player.
+    pub fn settled_points(&self) -> Vec<bool> {
+        let mut settled = vec![false; self.size.points()];
+        for &color in [Black, White].iter() {
+            let (alive, regions) = self.benson(color);
+            for region in regions.iter().filter(|r| !r.has_stones && Self::is_territory(r, &alive)) {
+                for coord in region.coords.iter() {
+                    settled[coord.to_index(self.size)] = true;
+                }
+            }
+        }
+        settled
+    }
+
+    /// If `c` is one of the `settled` points returned by
+    /// `settled_points()`. They may belong to a board of another size
+    /// (or not be calculated yet), then no point is settled.
+    pub fn is_settled(settled: &Vec<bool>, size: Size, c: &Coord) -> bool {
+        settled.len() == size.points() && settled[c.to_index(size)]
+    }
+
+    fn is_territory(region: &Region, alive: &Vec<usize>) -> bool {
+        !region.chains.is_empty() && region.vital_to.iter().any(|id| alive.contains(id))
+    }
+
+    // Repeatedly removes the chains that have less than two vital
+    // regions and the regions that are enclosed by a removed chain.
+    // Returns the remaining chains and regions.
+    fn benson(&self, color: Color) -> (Vec<usize>, Vec<Region>) {
+        let mut regions = self.regions(color);
+        let mut alive: Vec<usize> = self.chains.iter()
+            .enumerate()
+            .filter(|&(_, chain)| chain.color() == color)
+            .map(|(id, _)| id)
+            .collect();
+        loop {
+            let before = alive.len();
+            alive.retain(|id| regions.iter().filter(|r| r.vital_to.contains(id)).count() >= 2);
+            regions.retain(|r| r.chains.iter().all(|id| alive.contains(id)));
+            if alive.len() == before {
+                break;
+            }
+        }
+        (alive, regions)
+    }
+
+    fn regions(&self, color: Color) -> Vec<Region> {
+        let mut seen = vec![false; self.size.points()];
+        let mut regions = vec!();
+        for coord in Coord::for_board_size(self.size) {
+            if seen[coord.to_index(self.size)] || self.color(&coord) == color {
+                continue;
+            }
+            seen[coord.to_index(self.size)] = true;
+            let mut region = Region {
+                chains: vec!(),
+                coords: vec!(),
+                has_stones: false,
+                vital_to: vec!(),
+            };
+            let mut stack = vec!(coord);
+            while let Some(c) = stack.pop() {
+                region.coords.push(c);
+                region.has_stones = region.has_stones || self.color(&c) != Empty;
+                for n in self.neighbours(c).iter() {
+                    if self.color(n) == color {
+                        let id = self.chain_id(n);
+                        if !region.chains.contains(&id) {
+                            region.chains.push(id);
+                        }
+                    } else if !seen[n.to_index(self.size)] {
+                        seen[n.to_index(self.size)] = true;
+                        stack.push(*n);
+                    }
+                }
+            }
+            // A region is vital to a chain if all its empty points
+            // are liberties of the chain.
+            let vital_to = region.chains.iter()
+                .cloned()
+                .filter(|&id| {
+                    region.coords.iter()
+                        .filter(|c| self.color(c) == Empty)
+                        .all(|&c| self.chains[id].liberties().contains(c))
+                })
+                .collect();
+            region.vital_to = vital_to;
+            regions.push(region);
+        }
+        regions
+    }
+
+}
@@ -22,19 +22,18 @@
 
 use board::Color;
 use board::Coord;
-
-use std::collections::HashSet;
+use board::Liberties;
 
 #[derive(Clone, Eq, PartialEq, Debug)]
 pub struct Chain {
     color:  Color,
     coords: Vec<Coord>,
     id:     usize,
-    libs:   HashSet<Coord>,
+    libs:   Liberties,
 }
 
 impl Chain {
-    pub fn new(id: usize, color: Color, c: Coord, libs: HashSet<Coord>) -> Chain {
+    pub fn new(id: usize, color: Color, c: Coord, libs: Liberties) -> Chain {
         Chain {
             color:  color,
             coords: vec!(c),
@@ -51,7 +50,7 @@ impl Chain {
         &self.coords
     }
 
-    pub fn liberties(&self) -> &HashSet<Coord> {
+    pub fn liberties(&self) -> &Liberties {
         &self.libs
     }
 
@@ -63,20 +62,26 @@ impl Chain {
         self.id = id;
     }
 
-    pub fn add_liberty(&mut self, coord: Coord) {
-        self.libs.insert(coord);
+    // Returns true if the liberty wasn't already present.
+    pub fn add_liberty(&mut self, coord: Coord) -> bool {
+        self.libs.insert(coord)
     }
 
-    pub fn remove_liberty(&mut self, coord: Coord) {
-        self.libs.remove(&coord);
+    // Returns true if the liberty was present.
+    pub fn remove_liberty(&mut self, coord: Coord) -> bool {
+        self.libs.remove(coord)
     }
 
     pub fn add_coord(&mut self, coord: Coord) {
         self.coords.push(coord);
     }
 
+    pub fn remove_last_coord(&mut self) {
+        self.coords.pop();
+    }
+
     pub fn is_captured(&self) -> bool {
-        self.libs.len() == 0
+        self.libs.is_empty()
     }
 
 }
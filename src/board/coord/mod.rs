@@ -20,6 +20,10 @@
  * along with Iomrascálaí.  If not, see <http://www.gnu.org/licenses/>. *
  *                                                                      *
  ************************************************************************/
+use board::GTPError;
+use board::MAX_SIZE;
+use board::Size;
+
 use core::fmt;
 
 mod test;
@@ -35,17 +39,17 @@ impl Coord {
         Coord {col: col, row: row}
     }
 
-    pub fn for_board_size(size: u8) -> Vec<Coord> {
+    pub fn for_board_size(size: Size) -> Vec<Coord> {
         let mut coords = Vec::new();
-        for i in 0..size {
-            for j in 0..size {
+        for i in 0..size.height {
+            for j in 0..size.width {
                 coords.push(Coord::new(j+1, i+1));
             }
         }
         coords
     }
 
-    pub fn neighbours(&self, board_size: u8) -> Vec<Coord> {
+    pub fn neighbours(&self, board_size: Size) -> Vec<Coord> {
         let mut neighbours = Vec::new();
 
         for i in -1i8..2 {
@@ -60,7 +64,7 @@ impl Coord {
         neighbours
     }
 
-    pub fn diagonals(&self, board_size: u8) -> Vec<Coord> {
+    pub fn diagonals(&self, board_size: Size) -> Vec<Coord> {
         vec!(
             Coord::new(self.col-1, self.row-1),
             Coord::new(self.col+1, self.row-1),
@@ -85,29 +89,29 @@ impl Coord {
             Coord::new(self.col-1, self.row))   // W
     }
 
-    pub fn to_index(&self, board_size: u8) -> usize {
-        (self.col as usize-1 + (self.row as usize-1)*board_size as usize)
+    pub fn to_index(&self, board_size: Size) -> usize {
+        (self.col as usize-1 + (self.row as usize-1)*board_size.width as usize)
     }
 
     #[test]
-    pub fn from_index(index: usize, board_size: u8) -> Coord {
-        let col = (index as isize % board_size as isize) + 1;
-        let row = ((index as isize - col + 1) / board_size as isize) + 1;
+    pub fn from_index(index: usize, board_size: Size) -> Coord {
+        let col = (index as isize % board_size.width as isize) + 1;
+        let row = ((index as isize - col + 1) / board_size.width as isize) + 1;
         Coord::new(col as u8, row as u8)
     }
 
-    pub fn is_inside(&self, board_size: u8) -> bool {
-        1 <= self.col && self.col <= board_size && 1 <= self.row && self.row <= board_size
+    pub fn is_inside(&self, board_size: Size) -> bool {
+        1 <= self.col && self.col <= board_size.width && 1 <= self.row && self.row <= board_size.height
     }
 
-    pub fn distance_to_border(&self, board_size: u8) -> u8 {
-        *[self.col-1, self.row-1, board_size - self.col, board_size - self.row]
+    pub fn distance_to_border(&self, board_size: Size) -> u8 {
+        *[self.col-1, self.row-1, board_size.width - self.col, board_size.height - self.row]
             .iter()
             .min()
             .unwrap()
     }
 
-    pub fn manhattan_distance_three_neighbours(&self, board_size: u8) -> Vec<Coord> {
+    pub fn manhattan_distance_three_neighbours(&self, board_size: Size) -> Vec<Coord> {
         let offsets = vec!(
                                       (0, 3),
                              (-1, 2), (0, 2), (1, 2),
@@ -125,19 +129,32 @@ impl Coord {
             .collect()
     }
 
-    // Note: there is no I column.
-    pub fn from_gtp(gtp_vertex: &str) -> Coord {
-        let col_letter = gtp_vertex.chars().next().unwrap().to_lowercase().next().unwrap();
-
+    // Note: there is no I column. Only vertices that fit on the
+    // largest supported board are accepted.
+    pub fn from_gtp(gtp_vertex: &str) -> Result<Coord, GTPError> {
+        let invalid = || GTPError::InvalidVertex(gtp_vertex.to_string());
+        let mut chars = gtp_vertex.chars();
+        let col_letter = match chars.next().and_then(|c| c.to_lowercase().next()) {
+            Some(l) if l >= 'a' && l <= 'z' && l != 'i' => l,
+            _ => return Err(invalid())
+        };
         let col = if col_letter > 'i' {
             col_letter as u8 - 'a' as u8
         } else {
             col_letter as u8 - 'a' as u8 + 1
         };
-
-        let row = (gtp_vertex[1..gtp_vertex.len()]).parse::<u8>().unwrap();
-
-        Coord::new(col, row)
+        let digits = chars.as_str();
+        if digits.is_empty() || !digits.chars().all(|c| c.is_digit(10)) {
+            return Err(invalid());
+        }
+        let row = match digits.parse::<u8>() {
+            Ok(row) => row,
+            Err(_) => return Err(invalid())
+        };
+        if col > MAX_SIZE || row < 1 || row > MAX_SIZE {
+            return Err(invalid());
+        }
+        Ok(Coord::new(col, row))
     }
 
     // Note: there is no I column.
@@ -150,6 +167,14 @@ impl Coord {
         let gtp_row = self.row;
         format!("{}{}", gtp_col, gtp_row)
     }
+
+    // SGF counts the rows from top to bottom and uses letters for
+    // both the columns and the rows.
+    pub fn to_sgf(&self, size: Size) -> String {
+        let col = (b'a' + self.col - 1) as char;
+        let row = (b'a' + size.height - self.row) as char;
+        format!("{}{}", col, row)
+    }
 }
 
 impl fmt::Debug for Coord {
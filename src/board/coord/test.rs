@@ -23,10 +23,12 @@
 pub use hamcrest::prelude::*;
 
 pub use super::Coord;
+pub use board::GTPError;
+pub use board::Size;
 
 #[test]
 fn test_neighbours_contain_n_s_e_w() {
-  let n = Coord::new(10,10).neighbours(19);
+  let n = Coord::new(10,10).neighbours(Size::square(19));
 
   assert!(n.iter().find(|c| c.col == 10 && c.row == 9 ).is_some());
   assert!(n.iter().find(|c| c.col == 9  && c.row == 10).is_some());
@@ -36,7 +38,7 @@ fn test_neighbours_contain_n_s_e_w() {
 
 #[test]
 fn test_neighbours_do_not_contain_diagonals() {
-  let n = Coord::new(10,10).neighbours(19);
+  let n = Coord::new(10,10).neighbours(Size::square(19));
 
   assert!(n.iter().find(|c| c.col == 11 && c.row == 11).is_none());
   assert!(n.iter().find(|c| c.col == 9  && c.row == 11).is_none());
@@ -46,43 +48,50 @@ fn test_neighbours_do_not_contain_diagonals() {
 
 #[test]
 fn test_neighbours_do_not_contain_itself() {
-  let n = Coord::new(10,10).neighbours(19);
+  let n = Coord::new(10,10).neighbours(Size::square(19));
 
   assert!(n.iter().find(|c| c.col == 10 && c.row == 10).is_none());
 }
 
 #[test]
 fn is_inside_valid_coords_pass() {
-  assert!(Coord::new(1,1).is_inside(19));
-  assert!(Coord::new(19,19).is_inside(19));
-  assert!(Coord::new(10,10).is_inside(19));
+  assert!(Coord::new(1,1).is_inside(Size::square(19)));
+  assert!(Coord::new(19,19).is_inside(Size::square(19)));
+  assert!(Coord::new(10,10).is_inside(Size::square(19)));
 }
 
 #[test]
 fn is_inside_0_0_fails() {
-  assert!(!Coord::new(0,0).is_inside(19));
+  assert!(!Coord::new(0,0).is_inside(Size::square(19)));
 }
 
 #[test]
 fn is_inside_invalid_coords_fail() {
-  assert!(!Coord::new(4,21).is_inside(19));
-  assert!(!Coord::new(21,4).is_inside(19));
+  assert!(!Coord::new(4,21).is_inside(Size::square(19)));
+  assert!(!Coord::new(21,4).is_inside(Size::square(19)));
 
-  assert!(!Coord::new(18,18).is_inside(9));
+  assert!(!Coord::new(18,18).is_inside(Size::square(9)));
 }
 
 #[test]
 fn from_gtp_converts_correctly() {
-  assert_eq!(Coord::new(10,10), Coord::from_gtp("K10"));
-  assert_eq!(Coord::new(10,10), Coord::from_gtp("k10"));
+  assert_eq!(Ok(Coord::new(10,10)), Coord::from_gtp("K10"));
+  assert_eq!(Ok(Coord::new(10,10)), Coord::from_gtp("k10"));
 
-  assert_eq!(Coord::new(16,15), Coord::from_gtp("Q15"));
+  assert_eq!(Ok(Coord::new(16,15)), Coord::from_gtp("Q15"));
 
-  assert_eq!(Coord::new(1,1), Coord::from_gtp("A1"));
-  assert_eq!(Coord::new(19,19), Coord::from_gtp("T19"));
+  assert_eq!(Ok(Coord::new(1,1)), Coord::from_gtp("A1"));
+  assert_eq!(Ok(Coord::new(19,19)), Coord::from_gtp("T19"));
 
-  assert_eq!(Coord::new(9,10), Coord::from_gtp("J10"));
-  assert_eq!(Coord::new(8,10), Coord::from_gtp("H10"));
+  assert_eq!(Ok(Coord::new(9,10)), Coord::from_gtp("J10"));
+  assert_eq!(Ok(Coord::new(8,10)), Coord::from_gtp("H10"));
+}
+
+#[test]
+fn from_gtp_rejects_invalid_vertices() {
+  for vertex in ["", "a", "1a", "i5", "I5", "a0", "a26", "z26", "a-1", "a+1", "a1.5", "a256", "ä1"].iter() {
+    assert_eq!(Err(GTPError::InvalidVertex(vertex.to_string())), Coord::from_gtp(vertex));
+  }
 }
 
 #[test]
@@ -95,21 +104,52 @@ fn to_gtp_converts_correctly() {
   assert_eq!(Coord::new(8,10).to_gtp(), String::from("H10"));
 }
 
+#[test]
+fn to_sgf_counts_rows_from_the_top() {
+  let size = Size::new(19, 13).unwrap();
+  assert_eq!(Coord::new(1,13).to_sgf(size), String::from("aa"));
+  assert_eq!(Coord::new(1,1).to_sgf(size), String::from("am"));
+  assert_eq!(Coord::new(19,1).to_sgf(size), String::from("sm"));
+}
+
+#[test]
+fn gtp_vertices_beyond_t_are_supported() {
+  assert_eq!(Ok(Coord::new(20,20)), Coord::from_gtp("U20"));
+  assert_eq!(Ok(Coord::new(25,25)), Coord::from_gtp("Z25"));
+  assert_eq!(Coord::new(20,1).to_gtp(), String::from("U1"));
+  assert_eq!(Coord::new(25,25).to_gtp(), String::from("Z25"));
+}
+
+#[test]
+fn is_inside_checks_width_and_height_of_rectangular_boards() {
+  let size = Size::new(7, 5).unwrap();
+  assert!(Coord::new(7,5).is_inside(size));
+  assert!(!Coord::new(5,7).is_inside(size));
+}
+
+#[test]
+fn to_index_and_from_index_use_the_width_of_the_board() {
+  let size = Size::new(7, 5).unwrap();
+  assert_eq!(7, Coord::new(1,2).to_index(size));
+  assert_eq!(Coord::new(7,5), Coord::from_index(34, size));
+  assert_eq!(35, Coord::for_board_size(size).len());
+}
+
 #[test]
 fn for_board_size_returns_the_right_number_of_coords() {
-    let coords = Coord::for_board_size(3);
+    let coords = Coord::for_board_size(Size::square(3));
     assert_eq!(9, coords.len());
 }
 
 #[test]
 fn for_board_size_sets_the_coordinates_correctly() {
-    let coords = Coord::for_board_size(1);
+    let coords = Coord::for_board_size(Size::square(1));
     assert_eq!(coords[0], Coord::new(1,1));
 }
 
 #[test]
 fn distance_to_border() {
-    let size = 9;
+    let size = Size::square(9);
     assert_eq!(0, Coord::new(1,5).distance_to_border(size));
     assert_eq!(0, Coord::new(5,1).distance_to_border(size));
     assert_eq!(0, Coord::new(9,5).distance_to_border(size));
@@ -121,38 +161,38 @@ fn distance_to_border() {
 #[test]
 fn manhattan_distance_three_neighbours_middle_of_board() {
     let coord = Coord::new(5, 5);
-    let size = 9;
+    let size = Size::square(9);
     assert_eq!(24, coord.manhattan_distance_three_neighbours(size).len());
 }
 
 #[test]
 fn manhattan_distance_three_neighbours_in_a_corner() {
     let coord = Coord::new(1, 1);
-    let size = 9;
+    let size = Size::square(9);
     assert_eq!(9, coord.manhattan_distance_three_neighbours(size).len());
 }
 
 describe! indexing {
 
     it "a1" {
-        let coord = Coord::from_gtp("a1");
+        let coord = Coord::from_gtp("a1").unwrap();
         assert_that!(coord, is(equal_to(Coord::new(1,1))));
-        assert_that!(coord.to_index(9), is(equal_to(0)));
-        assert_that!(Coord::from_index(0, 9), is(equal_to(coord)));
+        assert_that!(coord.to_index(Size::square(9)), is(equal_to(0)));
+        assert_that!(Coord::from_index(0, Size::square(9)), is(equal_to(coord)));
     }
 
     it "j9" {
-        let coord = Coord::from_gtp("j9");
+        let coord = Coord::from_gtp("j9").unwrap();
         assert_that!(coord, is(equal_to(Coord::new(9,9))));
-        assert_that!(coord.to_index(9), is(equal_to(80)));
-        assert_that!(Coord::from_index(80, 9), is(equal_to(coord)));
+        assert_that!(coord.to_index(Size::square(9)), is(equal_to(80)));
+        assert_that!(Coord::from_index(80, Size::square(9)), is(equal_to(coord)));
     }
 
     it "g6" {
-        let coord = Coord::from_gtp("g6");
+        let coord = Coord::from_gtp("g6").unwrap();
         assert_that!(coord, is(equal_to(Coord::new(7,6))));
-        assert_that!(coord.to_index(9), is(equal_to(51)));
-        assert_that!(Coord::from_index(51, 9), is(equal_to(coord)));
+        assert_that!(coord.to_index(Size::square(9)), is(equal_to(51)));
+        assert_that!(Coord::from_index(51, Size::square(9)), is(equal_to(coord)));
     }
 
 }
@@ -71,7 +71,7 @@ use smallvec::SmallVec;
     pub fn new_chain_liberties_greater_than_zero(&self, m: Move) -> bool {
         for &c in self.neighbours(m.coord()).iter() {
             if self.color(&c) == *m.color() {
-                for &liberty in self.get_chain(c).unwrap().liberties() {
+                for liberty in self.get_chain(c).unwrap().liberties().iter() {
                     if liberty != m.coord() {
                         return true;
                     }
@@ -88,7 +88,7 @@ use smallvec::SmallVec;
         let mut first_liberty: Option<Coord> = None;
         for &c in self.neighbours(m.coord()).iter() {
             if self.color(&c) == *m.color() {
-                for &liberty in self.get_chain(c).unwrap().liberties() {
+                for liberty in self.get_chain(c).unwrap().liberties().iter() {
                     if liberty != m.coord() && first_liberty.is_none() {
                         first_liberty = Some(liberty);
                     } else if liberty != m.coord() && first_liberty.is_some() {
@@ -112,16 +112,17 @@ use smallvec::SmallVec;
     pub fn new_chain_liberties_greater_than(&self, m: Move, limit: usize) -> bool {
         let liberty_iterator = self.neighbours(m.coord()).iter()
             .filter(|c| self.color(&c) == *m.color())
-            .flat_map(|&c| self.get_chain(c).unwrap().liberties())
-            .filter(|&liberty| *liberty != m.coord());
+            .flat_map(|&c| self.get_chain(c).unwrap().liberties().iter())
+            .filter(|&liberty| liberty != m.coord());
 
          let empty_iterator = self.neighbours(m.coord()).iter()
-            .filter(|c| self.color(&c) == Empty);
+            .filter(|c| self.color(&c) == Empty)
+            .cloned();
 
          let mut liberties = SmallVec::<[Coord;4]>::new();
          for liberty in liberty_iterator.chain(empty_iterator) {
-            if !liberties.contains(liberty) {
-                liberties.push(*liberty);
+            if !liberties.contains(&liberty) {
+                liberties.push(liberty);
             }
 
             if liberties.len() > limit {
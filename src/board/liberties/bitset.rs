@@ -0,0 +1,124 @@
+/************************************************************************
+ *                                                                      *
+ * Copyright 2016 Urban Hafner                                          *
+ *                                                                      *
+ * This file is part of Iomrascálaí.                                    *
+ *                                                                      *
+ * Iomrascálaí is free software: you can redistribute it and/or modify  *
+ * it under the terms of the GNU General Public License as published by *
+ * the Free Software Foundation, either version 3 of the License, or    *
+ * (at your option) any later version.                                  *
+ *                                                                      *
+ * Iomrascálaí is distributed in the hope that it will be useful,       *
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of       *
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the        *
+ * GNU General Public License for more details.                         *
+ *                                                                      *
+ * You should have received a copy of the GNU General Public License    *
+ * along with Iomrascálaí.  If not, see <http://www.gnu.org/licenses/>. *
+ *                                                                      *
+ ************************************************************************/
+
+use board::Coord;
+use board::MAX_SIZE;
+
+use std::iter::FromIterator;
+
+// Every coordinate gets one bit. The index doesn't depend on the
+// size of the board so that the liberties can be used without
+// knowing it.
+const STRIDE: usize = MAX_SIZE as usize;
+const WORDS: usize = (STRIDE * STRIDE + 63) / 64;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Liberties {
+    words: [u64; WORDS],
+}
+
+impl Liberties {
+
+    pub fn new() -> Liberties {
+        Liberties { words: [0; WORDS] }
+    }
+
+    pub fn insert(&mut self, coord: Coord) -> bool {
+        let (word, bit) = Self::position(coord);
+        let missing = self.words[word] & (1 << bit) == 0;
+        self.words[word] |= 1 << bit;
+        missing
+    }
+
+    pub fn remove(&mut self, coord: Coord) -> bool {
+        let (word, bit) = Self::position(coord);
+        let present = self.words[word] & (1 << bit) != 0;
+        self.words[word] &= !(1 << bit);
+        present
+    }
+
+    pub fn contains(&self, coord: Coord) -> bool {
+        let (word, bit) = Self::position(coord);
+        self.words[word] & (1 << bit) != 0
+    }
+
+    pub fn extend(&mut self, other: &Liberties) {
+        for i in 0..WORDS {
+            self.words[i] |= other.words[i];
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.iter().all(|&w| w == 0)
+    }
+
+    pub fn iter(&self) -> Iter {
+        Iter { current: self.words[0], liberties: self, word: 0 }
+    }
+
+    fn position(coord: Coord) -> (usize, usize) {
+        let index = (coord.row as usize - 1) * STRIDE + coord.col as usize - 1;
+        (index / 64, index % 64)
+    }
+
+}
+
+impl FromIterator<Coord> for Liberties {
+
+    fn from_iter<I: IntoIterator<Item=Coord>>(iter: I) -> Liberties {
+        let mut liberties = Liberties::new();
+        for coord in iter {
+            liberties.insert(coord);
+        }
+        liberties
+    }
+
+}
+
+pub struct Iter<'a> {
+    current: u64,
+    liberties: &'a Liberties,
+    word: usize,
+}
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = Coord;
+
+    fn next(&mut self) -> Option<Coord> {
+        while self.current == 0 {
+            self.word += 1;
+            if self.word >= WORDS {
+                return None;
+            }
+            self.current = self.liberties.words[self.word];
+        }
+        let bit = self.current.trailing_zeros() as usize;
+        // Clear the lowest set bit.
+        self.current &= self.current - 1;
+        let index = self.word * 64 + bit;
+        Some(Coord::new((index % STRIDE) as u8 + 1, (index / STRIDE) as u8 + 1))
+    }
+
+}
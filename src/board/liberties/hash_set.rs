@@ -0,0 +1,78 @@
+/************************************************************************
+ *                                                                      *
+ * Copyright 2016 Urban Hafner                                          *
+ *                                                                      *
+ * This file is part of Iomrascálaí.                                    *
+ *                                                                      *
+ * Iomrascálaí is free software: you can redistribute it and/or modify  *
+ * it under the terms of the GNU General Public License as published by *
+ * the Free Software Foundation, either version 3 of the License, or    *
+ * (at your option) any later version.                                  *
+ *                                                                      *
+ * Iomrascálaí is distributed in the hope that it will be useful,       *
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of       *
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the        *
+ * GNU General Public License for more details.                         *
+ *                                                                      *
+ * You should have received a copy of the GNU General Public License    *
+ * along with Iomrascálaí.  If not, see <http://www.gnu.org/licenses/>. *
+ *                                                                      *
+ ************************************************************************/
+
+use board::Coord;
+
+use std::collections::HashSet;
+use std::collections::hash_set;
+use std::iter::Cloned;
+use std::iter::FromIterator;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Liberties {
+    coords: HashSet<Coord>,
+}
+
+pub type Iter<'a> = Cloned<hash_set::Iter<'a, Coord>>;
+
+impl Liberties {
+
+    pub fn new() -> Liberties {
+        Liberties { coords: HashSet::new() }
+    }
+
+    pub fn insert(&mut self, coord: Coord) -> bool {
+        self.coords.insert(coord)
+    }
+
+    pub fn remove(&mut self, coord: Coord) -> bool {
+        self.coords.remove(&coord)
+    }
+
+    pub fn contains(&self, coord: Coord) -> bool {
+        self.coords.contains(&coord)
+    }
+
+    pub fn extend(&mut self, other: &Liberties) {
+        self.coords.extend(other.coords.iter().cloned());
+    }
+
+    pub fn len(&self) -> usize {
+        self.coords.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.coords.is_empty()
+    }
+
+    pub fn iter(&self) -> Iter {
+        self.coords.iter().cloned()
+    }
+
+}
+
+impl FromIterator<Coord> for Liberties {
+
+    fn from_iter<I: IntoIterator<Item=Coord>>(iter: I) -> Liberties {
+        Liberties { coords: iter.into_iter().collect() }
+    }
+
+}
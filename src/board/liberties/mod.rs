@@ -0,0 +1,42 @@
+/************************************************************************
+ *                                                                      *
+ * Copyright 2016 Urban Hafner                                          *
+ *                                                                      *
+ * This file is part of Iomrascálaí.                                    *
+ *                                                                      *
+ * Iomrascálaí is free software: you can redistribute it and/or modify  *
+ * it under the terms of the GNU General Public License as published by *
+ * the Free Software Foundation, either version 3 of the License, or    *
+ * (at your option) any later version.                                  *
+ *                                                                      *
+ * Iomrascálaí is distributed in the hope that it will be useful,       *
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of       *
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the        *
+ * GNU General Public License for more details.                         *
+ *                                                                      *
+ * You should have received a copy of the GNU General Public License    *
+ * along with Iomrascálaí.  If not, see <http://www.gnu.org/licenses/>. *
+ *                                                                      *
+ ************************************************************************/
+
+// The liberties of a chain. By default they are stored in a hash
+// set. Building with the `bitset-liberties` feature uses a fixed
+// size bit set instead which makes copying the board and merging chains much
+// cheaper. Both implementations have the same interface and
+// iterating over them yields the coordinates by value. Inserting and
+// removing returns whether the liberties changed.
+
+#[cfg(not(feature = "bitset-liberties"))]
+pub use self::hash_set::Iter;
+#[cfg(not(feature = "bitset-liberties"))]
+pub use self::hash_set::Liberties;
+#[cfg(feature = "bitset-liberties")]
+pub use self::bitset::Iter;
+#[cfg(feature = "bitset-liberties")]
+pub use self::bitset::Liberties;
+
+#[cfg(feature = "bitset-liberties")]
+mod bitset;
+#[cfg(not(feature = "bitset-liberties"))]
+mod hash_set;
+mod test;
@@ -0,0 +1,77 @@
+/************************************************************************
+ *                                                                      *
+ * Copyright 2016 Urban Hafner                                          *
+ *                                                                      *
+ * This file is part of Iomrascálaí.                                    *
+ *                                                                      *
+ * Iomrascálaí is free software: you can redistribute it and/or modify  *
+ * it under the terms of the GNU General Public License as published by *
+ * the Free Software Foundation, either version 3 of the License, or    *
+ * (at your option) any later version.                                  *
+ *                                                                      *
+ * Iomrascálaí is distributed in the hope that it will be useful,       *
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of       *
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the        *
+ * GNU General Public License for more details.                         *
+ *                                                                      *
+ * You should have received a copy of the GNU General Public License    *
+ * along with Iomrascálaí.  If not, see <http://www.gnu.org/licenses/>. *
+ *                                                                      *
+ ************************************************************************/
+
+#![cfg(test)]
+
+use board::Coord;
+use board::MAX_SIZE;
+use board::Size;
+use super::Liberties;
+
+fn sorted(liberties: &Liberties) -> Vec<Coord> {
+    let mut coords: Vec<Coord> = liberties.iter().collect();
+    coords.sort();
+    coords
+}
+
+#[test]
+fn new_is_empty() {
+    let liberties = Liberties::new();
+    assert!(liberties.is_empty());
+    assert_eq!(0, liberties.len());
+    assert_eq!(None, liberties.iter().next());
+}
+
+#[test]
+fn inserting_the_same_coord_twice_counts_once() {
+    let mut liberties = Liberties::new();
+    liberties.insert(Coord::new(3, 4));
+    liberties.insert(Coord::new(3, 4));
+    assert_eq!(1, liberties.len());
+    assert!(liberties.contains(Coord::new(3, 4)));
+    assert!(!liberties.contains(Coord::new(4, 3)));
+}
+
+#[test]
+fn remove_removes_only_the_given_coord() {
+    let mut liberties: Liberties = vec!(Coord::new(1, 1), Coord::new(19, 19)).into_iter().collect();
+    liberties.remove(Coord::new(1, 1));
+    liberties.remove(Coord::new(5, 5));
+    assert_eq!(vec!(Coord::new(19, 19)), sorted(&liberties));
+}
+
+#[test]
+fn iter_returns_all_coords_of_a_full_board() {
+    let coords = Coord::for_board_size(Size::square(MAX_SIZE));
+    let liberties: Liberties = coords.iter().cloned().collect();
+    let mut expected = coords.clone();
+    expected.sort();
+    assert_eq!(coords.len(), liberties.len());
+    assert_eq!(expected, sorted(&liberties));
+}
+
+#[test]
+fn extend_builds_the_union() {
+    let mut liberties: Liberties = vec!(Coord::new(1, 2), Coord::new(2, 1)).into_iter().collect();
+    let other: Liberties = vec!(Coord::new(2, 1), Coord::new(10, 10)).into_iter().collect();
+    liberties.extend(&other);
+    assert_eq!(vec!(Coord::new(1, 2), Coord::new(2, 1), Coord::new(10, 10)), sorted(&liberties));
+}
@@ -25,29 +25,38 @@ pub use self::Color::Empty;
 pub use self::Color::White;
 pub use self::chain::Chain;
 pub use self::coord::Coord;
+pub use self::liberties::Liberties;
 pub use self::movement::Move;
 pub use self::movement::NoMove;
 pub use self::movement::Pass;
 pub use self::movement::Play;
 pub use self::movement::Resign;
+pub use self::size::Size;
 
 use ruleset::Ruleset;
 use score::Score;
 use self::point::Point;
+use self::undo::Change;
 
 use quicksort::quicksort;
-use std::collections::HashSet;
+use rand::Rng;
+use rand::SeedableRng;
+use rand::XorShiftRng;
 use std::fmt;
 use std::sync::Arc;
 use smallvec::SmallVec;
 
+mod benson;
 mod chain;
 mod coord;
 mod hypotheticals;
+mod liberties;
 mod movement;
 mod point;
 mod reading;
+mod size;
 mod test;
+mod undo;
 
 #[derive(Debug, Eq, PartialEq)]
 pub enum IllegalMove {
@@ -67,6 +76,34 @@ impl fmt::Display for IllegalMove {
     }
 }
 
+// The longest side of a board that can be set up (and whose vertices
+// can be read from GTP). There are 25 letters for the columns as I
+// isn't used.
+pub const MAX_SIZE: u8 = 25;
+
+/// Errors when converting colors, vertices, and moves from and to
+/// their GTP representation, or when playing a GTP move.
+#[derive(Debug, Eq, PartialEq)]
+pub enum GTPError {
+    IllegalMove(IllegalMove),
+    InvalidColor(String),
+    InvalidVertex(String),
+    NoMove,
+}
+
+impl fmt::Display for GTPError {
+
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match *self {
+            GTPError::IllegalMove(ref e) => format!("illegal move ({})", e),
+            GTPError::InvalidColor(ref color) => format!("invalid color {:?}", color),
+            GTPError::InvalidVertex(ref vertex) => format!("invalid vertex {:?}", vertex),
+            GTPError::NoMove => "NoMove has no GTP representation".to_string(),
+        };
+        s.fmt(f)
+    }
+}
+
 
 #[derive(Clone, Debug, Eq, PartialEq, Hash, Copy)]
 pub enum Color {
@@ -84,33 +121,77 @@ impl Color {
         }
     }
 
-    pub fn from_gtp(gtp_color: &str) -> Color {
-        let lower_gtp_color: String = gtp_color.chars().map(|c| c.to_lowercase().next().unwrap()).collect();
-        match lower_gtp_color.as_ref() {
-            "w" | "white" => White,
-            "b" | "black" => Black,
-            err           => panic!("Can't read the GTP color: {}", err)
+    pub fn from_gtp(gtp_color: &str) -> Result<Color, GTPError> {
+        match gtp_color.to_lowercase().as_ref() {
+            "w" | "white" => Ok(White),
+            "b" | "black" => Ok(Black),
+            _             => Err(GTPError::InvalidColor(gtp_color.to_string()))
         }
     }
 }
 
+// Values of the two bits used for each of the eight neighbours in
+// the 3x3 pattern codes. Building with the `tree-walk-patterns`
+// feature leaves the codes out (to measure what maintaining them
+// costs and saves).
+#[cfg(not(feature = "tree-walk-patterns"))]
+const PATTERN_EMPTY: u16 = 0;
+#[cfg(not(feature = "tree-walk-patterns"))]
+const PATTERN_BLACK: u16 = 1;
+#[cfg(not(feature = "tree-walk-patterns"))]
+const PATTERN_WHITE: u16 = 2;
+#[cfg(not(feature = "tree-walk-patterns"))]
+const PATTERN_OFF_BOARD: u16 = 3;
+
+// Fixed seed so that the Zobrist keys (and therefore the hashes of
+// the positions) are the same for all boards of the same size.
+const ZOBRIST_SEED: [u32; 4] = [0x5a3c1e87, 0x0b9d42f6, 0xe2716c39, 0x47f8a0d5];
+
 #[derive(Debug)]
 struct Cache {
     diagonals: Vec<Vec<Coord>>,
+    #[cfg(not(feature = "tree-walk-patterns"))]
+    empty_pattern_codes: Vec<u16>,
     neighbours: Vec<Vec<Coord>>,
-    neighbours8_unchecked: Vec<Vec<Coord>>
+    neighbours8_unchecked: Vec<Vec<Coord>>,
+    zobrist_keys: Vec<[u64; 2]>,
 }
 
 impl Cache {
-    pub fn new(size: u8) -> Cache {
+    pub fn new(size: Size) -> Cache {
         Cache {
             diagonals: Self::setup_diagonals(size),
+            #[cfg(not(feature = "tree-walk-patterns"))]
+            empty_pattern_codes: Self::setup_empty_pattern_codes(size),
             neighbours: Self::setup_neighbours(size),
             neighbours8_unchecked: Self::setup_neighbours8_unchecked(size),
+            zobrist_keys: Self::setup_zobrist_keys(size),
+        }
+    }
+
+    fn setup_zobrist_keys(size: Size) -> Vec<[u64; 2]> {
+        let mut rng = XorShiftRng::from_seed(ZOBRIST_SEED);
+        Coord::for_board_size(size).iter()
+            .map(|_| [rng.gen(), rng.gen()])
+            .collect()
+    }
+
+    #[cfg(not(feature = "tree-walk-patterns"))]
+    fn setup_empty_pattern_codes(size: Size) -> Vec<u16> {
+        let mut codes = Vec::new();
+        for coord in Coord::for_board_size(size).iter() {
+            let mut code = 0;
+            for (i, n) in coord.neighbours8_unchecked().iter().enumerate() {
+                if !n.is_inside(size) {
+                    code |= PATTERN_OFF_BOARD << (2 * i);
+                }
+            }
+            codes.push(code);
         }
+        codes
     }
 
-    fn setup_neighbours(size: u8) -> Vec<Vec<Coord>> {
+    fn setup_neighbours(size: Size) -> Vec<Vec<Coord>> {
         let mut neighbours = Vec::new();
         for coord in Coord::for_board_size(size).iter() {
             neighbours.push(coord.neighbours(size));
@@ -118,7 +199,7 @@ impl Cache {
         neighbours
     }
 
-    fn setup_diagonals(size: u8) -> Vec<Vec<Coord>> {
+    fn setup_diagonals(size: Size) -> Vec<Vec<Coord>> {
         let mut diagonals = Vec::new();
         for coord in Coord::for_board_size(size).iter() {
             diagonals.push(coord.diagonals(size));
@@ -126,7 +207,7 @@ impl Cache {
         diagonals
     }
 
-    fn setup_neighbours8_unchecked(size: u8) -> Vec<Vec<Coord>> {
+    fn setup_neighbours8_unchecked(size: Size) -> Vec<Vec<Coord>> {
         let mut neighbours8 = Vec::new();
         for coord in Coord::for_board_size(size).iter() {
             neighbours8.push(coord.neighbours8_unchecked());
@@ -140,50 +221,95 @@ pub struct Board {
     adv_stones_removed:    Vec<Coord>,
     board:                 Vec<Point>,
     chains:                Vec<Chain>,
+    changes:               Vec<Change>,
     consecutive_passes:    u8,
     cache:                 Arc<Cache>,
     friend_stones_removed: Vec<Coord>,
+    hash:                  u64,
     ko:                    Option<Coord>,
     komi:                  f32,
+    #[cfg(not(feature = "tree-walk-patterns"))]
+    pattern_codes:         Vec<u16>,
     previous_player:       Color,
+    record_changes:        bool,
     resigned_by:           Color,
     ruleset:               Ruleset,
-    size:                  u8,
+    size:                  Size,
     vacant:                Vec<Coord>,
 }
 
+// The moves recorded for make_move()/unmake_move() aren't copied.
 impl Clone for Board {
     fn clone(&self) -> Board {
         Board {
             adv_stones_removed:    self.adv_stones_removed.clone(),
             board:                 self.board.clone(),
             chains:                self.chains.clone(),
+            changes:               Vec::new(),
             cache:                 self.cache.clone(),
             consecutive_passes:    self.consecutive_passes,
             friend_stones_removed: self.friend_stones_removed.clone(),
+            hash:                  self.hash,
             ko:                    self.ko,
             komi:                  self.komi,
+            #[cfg(not(feature = "tree-walk-patterns"))]
+            pattern_codes:         self.pattern_codes.clone(),
             previous_player:       self.previous_player,
+            record_changes:        false,
             resigned_by:           self.resigned_by,
             ruleset:               self.ruleset,
             size:                  self.size,
             vacant:                self.vacant.clone(),
         }
     }
+
+    // Reuses the allocations of the board, which makes it cheaper
+    // than clone() for boards that are copied over and over again
+    // (like the one of each playout).
+    fn clone_from(&mut self, source: &Board) {
+        self.adv_stones_removed.clone_from(&source.adv_stones_removed);
+        self.board.clone_from(&source.board);
+        self.chains.clone_from(&source.chains);
+        self.changes.clear();
+        self.cache = source.cache.clone();
+        self.consecutive_passes = source.consecutive_passes;
+        self.friend_stones_removed.clone_from(&source.friend_stones_removed);
+        self.hash = source.hash;
+        self.ko = source.ko;
+        self.komi = source.komi;
+        self.clone_pattern_codes_from(source);
+        self.previous_player = source.previous_player;
+        self.record_changes = false;
+        self.resigned_by = source.resigned_by;
+        self.ruleset = source.ruleset;
+        self.size = source.size;
+        self.vacant.clone_from(&source.vacant);
+    }
 }
 
 impl Board {
     pub fn new(size: u8, komi: f32, ruleset: Ruleset) -> Board {
+        Self::with_size(Size::square(size), komi, ruleset)
+    }
+
+    /// Same as `new()` but the board doesn't have to be square.
+    pub fn with_size(size: Size, komi: f32, ruleset: Ruleset) -> Board {
+        let cache = Cache::new(size);
         Board {
             adv_stones_removed:    Vec::new(),
-            board:                 (0..size as usize*size as usize).map(|_| Point::new()).collect(),
+            board:                 (0..size.points()).map(|_| Point::new()).collect(),
             chains:                Vec::new(),
+            changes:               Vec::new(),
             consecutive_passes:    0,
-            cache:                 Arc::new(Cache::new(size)),
+            #[cfg(not(feature = "tree-walk-patterns"))]
+            pattern_codes:         cache.empty_pattern_codes.clone(),
+            cache:                 Arc::new(cache),
             friend_stones_removed: Vec::new(),
+            hash:                  0,
             ko:                    None,
             komi:                  komi,
             previous_player:       White,
+            record_changes:        false,
             resigned_by:           Empty,
             ruleset:               ruleset,
             size:                  size,
@@ -213,6 +339,17 @@ impl Board {
         }).collect()
     }
 
+    /// The 3x3 neighbourhood of the point packed into 16 bits. Each
+    /// of the eight neighbours (in the order NW, N, NE, E, SE, S, SW,
+    /// W starting with the lowest bits) uses two bits: 0 for an empty
+    /// intersection, 1 for black, 2 for white, and 3 for off board.
+    /// The codes are updated incrementally whenever a stone is added
+    /// or removed.
+    #[cfg(not(feature = "tree-walk-patterns"))]
+    pub fn pattern_code(&self, c: &Coord) -> u16 {
+        self.pattern_codes[c.to_index(self.size)]
+    }
+
     pub fn points(&self) -> &Vec<Point> {
         &self.board
     }
@@ -225,10 +362,81 @@ impl Board {
         self.board[c.to_index(self.size)].color
     }
 
+    /// The Zobrist hash of the stones on the board (without the
+    /// player to move). It's updated incrementally, so it stays
+    /// correct when moves are taken back with `unmake_move()`.
+    pub fn hash(&self) -> u64 {
+        self.hash
+    }
+
+    /// The Zobrist hash of the position where every stone is moved
+    /// to the point returned by `transform`. The transformed points
+    /// have to be on the board.
+    pub fn transformed_hash<F>(&self, transform: F) -> u64 where F: Fn(Coord) -> Coord {
+        let mut hash = 0;
+        for (index, point) in self.board.iter().enumerate() {
+            if point.color != Empty {
+                let coord = transform(Coord::from_index(index, self.size));
+                hash ^= self.zobrist_key(coord.to_index(self.size), point.color);
+            }
+        }
+        hash
+    }
+
     /// Remove dead stone for scoring. Board becomes unplayable after
     /// doing this!
     pub fn remove_dead_stone(&mut self, c: &Coord) {
-        self.board[c.to_index(self.size)].color = Empty;
+        self.set_color(c, Empty);
+    }
+
+    // All changes to the color of a point have to go through this
+    // method so that the pattern codes of the neighbours and the
+    // hash stay in sync.
+    fn set_color(&mut self, c: &Coord, color: Color) {
+        let index = c.to_index(self.size);
+        let old_color = self.board[index].color;
+        self.record(Change::ColorChanged { color: old_color, coord: *c });
+        self.board[index].color = color;
+        self.hash ^= self.zobrist_key(index, old_color) ^ self.zobrist_key(index, color);
+        self.update_pattern_codes(index, color);
+    }
+
+    #[cfg(not(feature = "tree-walk-patterns"))]
+    fn update_pattern_codes(&mut self, index: usize, color: Color) {
+        let value = match color {
+            Empty => PATTERN_EMPTY,
+            Black => PATTERN_BLACK,
+            White => PATTERN_WHITE,
+        };
+        for i in 0..8 {
+            let neighbour = self.cache.neighbours8_unchecked[index][i];
+            if neighbour.is_inside(self.size) {
+                // We're the opposite direction as seen from the
+                // neighbour (e.g. its S if it is our N).
+                let shift = 2 * ((i + 4) % 8);
+                let code = &mut self.pattern_codes[neighbour.to_index(self.size)];
+                *code = (*code & !(3 << shift)) | (value << shift);
+            }
+        }
+    }
+
+    #[cfg(feature = "tree-walk-patterns")]
+    fn update_pattern_codes(&mut self, _: usize, _: Color) {}
+
+    #[cfg(not(feature = "tree-walk-patterns"))]
+    fn clone_pattern_codes_from(&mut self, source: &Board) {
+        self.pattern_codes.clone_from(&source.pattern_codes);
+    }
+
+    #[cfg(feature = "tree-walk-patterns")]
+    fn clone_pattern_codes_from(&mut self, _: &Board) {}
+
+    fn zobrist_key(&self, index: usize, color: Color) -> u64 {
+        match color {
+            Black => self.cache.zobrist_keys[index][0],
+            White => self.cache.zobrist_keys[index][1],
+            Empty => 0,
+        }
     }
 
     pub fn chain_id(&self, c: &Coord) -> usize {
@@ -413,6 +621,8 @@ impl Board {
         self.vacant.swap_remove(pos);
         self.vacant.extend_from_slice(self.adv_stones_removed.as_ref());
         self.vacant.extend_from_slice(self.friend_stones_removed.as_ref());
+        let added = self.adv_stones_removed.len() + self.friend_stones_removed.len();
+        self.record(Change::VacantRemoved { added: added, coord: m.coord(), index: pos });
     }
 
     fn add_removed_adv_stones_as_libs(&mut self, m: &Move) {
@@ -425,7 +635,7 @@ impl Board {
                 .collect();
 
             for &chain_id in chain_ids.iter() {
-                self.chains[chain_id].add_liberty(coord); //it's a hash set so we can add multiple times
+                self.add_chain_liberty(chain_id, coord); //it's a set so we can add multiple times
             }
         }
     }
@@ -440,7 +650,7 @@ impl Board {
                 .collect();
 
             for &chain_id in chain_ids.iter() {
-                self.chains[chain_id].add_liberty(coord); //it's a hash set so we can add multiple times
+                self.add_chain_liberty(chain_id, coord); //it's a set so we can add multiple times
             }
         }
     }
@@ -455,7 +665,7 @@ impl Board {
             .collect();
 
         for &id in adv_chains_ids.iter() {
-            self.chains[id].remove_liberty(coord);
+            self.remove_chain_liberty(id, coord);
         }
     }
 
@@ -480,27 +690,94 @@ impl Board {
         let new_chain_id = self.create_new_chain(m);
         chain_ids.push(new_chain_id);
         let final_chain_id = chain_ids[0];
-        let mut nb_removed_chains = 0;
-        for &other_chain_old_id in chain_ids.iter() {
-            if other_chain_old_id != final_chain_id {
-                let other_chain_id = other_chain_old_id - nb_removed_chains;
+        // Going through the chains from the highest id down means
+        // that discarding a chain never changes the id of one that
+        // still has to be merged.
+        for &other_chain_id in chain_ids.iter().rev() {
+            if other_chain_id != final_chain_id {
                 // We merge the other chain into the final chain.
-                let other_chain = self.chains.remove(other_chain_id);
+                let other_chain = self.discard_chain(other_chain_id);
                 for &coord in other_chain.coords().iter() {
                     self.board[coord.to_index(self.size)].chain_id = final_chain_id;
                     self.chains[final_chain_id].add_coord(coord);
+                    self.record(Change::CoordAdded(final_chain_id));
                 }
-                for &lib in other_chain.liberties().iter() {
-                    self.chains[final_chain_id].add_liberty(lib);
+                for lib in other_chain.liberties().iter() {
+                    self.add_chain_liberty(final_chain_id, lib);
                 }
-                self.update_all_after_id(other_chain_id);
-                nb_removed_chains += 1;
+                self.record(Change::ChainDiscarded { chain: other_chain, id: other_chain_id });
             }
         }
         // Removes the played stone from the liberty
-        self.chains[final_chain_id].remove_liberty(m.coord());
+        self.remove_chain_liberty(final_chain_id, m.coord());
+    }
+
+    fn add_chain_liberty(&mut self, id: usize, coord: Coord) {
+        if self.chains[id].add_liberty(coord) {
+            self.record(Change::LibertyAdded(id, coord));
+        }
     }
 
+    fn remove_chain_liberty(&mut self, id: usize, coord: Coord) {
+        if self.chains[id].remove_liberty(coord) {
+            self.record(Change::LibertyRemoved(id, coord));
+        }
+    }
+
+    // Keeps track of the changes to the board while a move is played
+    // with make_move() so that it can be taken back later.
+    fn record(&mut self, change: Change) {
+        if self.record_changes {
+            self.changes.push(change);
+        }
+    }
+
+    // Removes the chain from the list of chains and returns it. The
+    // stones of the chain are left on the board. Only the ids of
+    // chains with a higher id than the discarded one may change.
+    #[cfg(not(feature = "bitset-liberties"))]
+    fn discard_chain(&mut self, id: usize) -> Chain {
+        let chain = self.chains.remove(id);
+        self.update_all_after_id(id);
+        chain
+    }
+
+    // With bit set liberties the last chain takes the place of the
+    // discarded one so that only its stones need a new id.
+    #[cfg(feature = "bitset-liberties")]
+    fn discard_chain(&mut self, id: usize) -> Chain {
+        let chain = self.chains.swap_remove(id);
+        if id < self.chains.len() {
+            self.relabel_chain(id);
+        }
+        chain
+    }
+
+    // Reverts discard_chain().
+    #[cfg(not(feature = "bitset-liberties"))]
+    fn restore_chain(&mut self, id: usize, chain: Chain) {
+        self.chains.insert(id, chain);
+        self.update_all_after_id(id);
+    }
+
+    #[cfg(feature = "bitset-liberties")]
+    fn restore_chain(&mut self, id: usize, chain: Chain) {
+        self.chains.push(chain);
+        let last = self.chains.len() - 1;
+        self.chains.swap(id, last);
+        self.relabel_chain(id);
+        self.relabel_chain(last);
+    }
+
+    #[cfg(feature = "bitset-liberties")]
+    fn relabel_chain(&mut self, id: usize) {
+        self.chains[id].set_id(id);
+        for &coord in self.chains[id].coords().iter() {
+            self.board[coord.to_index(self.size)].chain_id = id;
+        }
+    }
+
+    #[cfg(not(feature = "bitset-liberties"))]
     fn update_board_ids_after_id(&mut self, id: usize) {
         for i in id..self.chains.len() {
             for &coord in self.chains[i].coords().iter() {
@@ -509,12 +786,14 @@ impl Board {
         }
     }
 
+    #[cfg(not(feature = "bitset-liberties"))]
     fn update_chains_ids_after_id(&mut self, removed_chain_id: usize) {
         for i in removed_chain_id..self.chains.len() {
             self.chains[i].set_id(i);
         }
     }
 
+    #[cfg(not(feature = "bitset-liberties"))]
     fn update_all_after_id(&mut self, id: usize) {
         self.update_board_ids_after_id(id);
         self.update_chains_ids_after_id(id);
@@ -551,10 +830,8 @@ impl Board {
 
         quicksort(&mut *chains_to_remove);
 
-        let mut nb_of_removed_chains = 0;
-        for &id in chains_to_remove.iter() {
-            self.remove_chain(id-nb_of_removed_chains);
-            nb_of_removed_chains += 1;
+        for &id in chains_to_remove.iter().rev() {
+            self.remove_chain(id);
         }
         coords_to_remove
     }
@@ -567,16 +844,12 @@ impl Board {
     }
 
     fn remove_chain(&mut self, id: usize) {
-        {
-            let coords_to_remove = self.chains[id].coords().iter();
+        let chain = self.discard_chain(id);
 
-            for &coord in coords_to_remove {
-                self.board[coord.to_index(self.size)].color = Empty; //remove stone
-            }
+        for coord in chain.coords() {
+            self.set_color(coord, Empty); //remove stone
         }
-
-        self.chains.remove(id);
-        self.update_all_after_id(id);
+        self.record(Change::ChainDiscarded { chain: chain, id: id });
     }
 
     fn create_new_chain(&mut self, m: &Move) -> usize {
@@ -584,12 +857,13 @@ impl Board {
         let new_chain    = Chain::new(
             new_chain_id, *m.color(), m.coord(), self.liberties(&m.coord()));
         self.chains.push(new_chain);
+        self.record(Change::ChainCreated);
         self.board[m.coord().to_index(self.size)].chain_id = new_chain_id;
-        self.board[m.coord().to_index(self.size)].color = *m.color();
+        self.set_color(&m.coord(), *m.color());
         new_chain_id
     }
 
-    fn liberties(&self, c: &Coord) -> HashSet<Coord> {
+    fn liberties(&self, c: &Coord) -> Liberties {
         self.neighbours(*c).iter().filter(|&c| self.color(c) == Empty).cloned().collect()
     }
 
@@ -617,7 +891,7 @@ impl Board {
         self.resigned_by = Empty;
     }
 
-    pub fn size(&self) -> u8 {
+    pub fn size(&self) -> Size {
         self.size
     }
 
@@ -632,19 +906,18 @@ impl Board {
     pub fn as_string(&self) -> String {
         let mut s = String::new();
                 // First we print the board
-        for row in (1u8..self.size()+1).rev() {
+        for row in (1u8..self.size.height+1).rev() {
 
             // Prints the row number
             s.push_str(format!("{:2} ", row).as_ref());
 
             // Prints the actual row
-            for col in 1u8..self.size()+1 {
+            for col in 1u8..self.size.width+1 {
                 let current_coords = Coord::new(col, row);
 
                 match self.color(&current_coords) {
                     Empty => {
-                        let hoshis = &[4u8,10,16];
-                        if  hoshis.contains(&row) && hoshis.contains(&col) {
+                        if self.size.is_star_point(&current_coords) {
                             s.push_str("+ ");
                         } else {
                             s.push_str(". ");
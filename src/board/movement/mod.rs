@@ -25,6 +25,7 @@ pub use self::Move::Resign;
 pub use self::Move::NoMove;
 use board::Color;
 use board::Coord;
+use board::GTPError;
 
 mod test;
 
@@ -37,26 +38,24 @@ pub enum Move {
 }
 
 impl Move {
-    pub fn from_gtp(gtp_color: &str, gtp_vertex: &str) -> Move {
-        let color = Color::from_gtp(gtp_color);
-        let lower_gtp_vertex: String = gtp_vertex.chars().map(|c| c.to_lowercase().next().unwrap()).collect();
-
-        match lower_gtp_vertex.as_ref() {
-            "pass"   => { Pass(color) },
-            "resign" => { Resign(color) },
+    pub fn from_gtp(gtp_color: &str, gtp_vertex: &str) -> Result<Move, GTPError> {
+        let color = try!(Color::from_gtp(gtp_color));
+        match gtp_vertex.to_lowercase().as_ref() {
+            "pass"   => Ok(Pass(color)),
+            "resign" => Ok(Resign(color)),
             _        => {
-                let coord = Coord::from_gtp(gtp_vertex);
-                Play(color, coord.col, coord.row)
+                let coord = try!(Coord::from_gtp(gtp_vertex));
+                Ok(Play(color, coord.col, coord.row))
             }
         }
     }
 
-    pub fn to_gtp(&self) -> String {
+    pub fn to_gtp(&self) -> Result<String, GTPError> {
         match *self {
-            Pass(_)           => String::from("pass"),
-            Play(_, col, row) => Coord::new(col, row).to_gtp(),
-            Resign(_)         => String::from("resign"),
-            NoMove            => panic!("Can't turn Move::NoMove into a GTP move!"),
+            Pass(_)           => Ok(String::from("pass")),
+            Play(_, col, row) => Ok(Coord::new(col, row).to_gtp()),
+            Resign(_)         => Ok(String::from("resign")),
+            NoMove            => Err(GTPError::NoMove),
         }
     }
 
@@ -21,34 +21,57 @@
 #![cfg(test)]
 
 use board::Black;
+use board::GTPError;
 use board::White;
 use super::Move;
+use super::NoMove;
 use super::Pass;
+use super::Play;
 use super::Resign;
 
 #[test]
 fn parse_gtp_pass() {
-    let m = Move::from_gtp("B", "PASS");
+    let m = Move::from_gtp("B", "PASS").unwrap();
     assert_eq!(m, Pass(Black));
 }
 
 #[test]
 fn parse_lower_case_gtp_pass() {
-    let m = Move::from_gtp("B", "pass");
+    let m = Move::from_gtp("B", "pass").unwrap();
     assert_eq!(m, Pass(Black));
 }
 
 #[test]
 // TODO: Will this ever happen?
 fn parse_resign() {
-    let m = Move::from_gtp("W", "resign");
+    let m = Move::from_gtp("W", "resign").unwrap();
     assert_eq!(m, Resign(White));
 }
 
 #[test]
 fn produce_gtp_resign() {
     let m = Resign(White);
-    assert_eq!("resign", m.to_gtp());
+    assert_eq!(Ok("resign".to_string()), m.to_gtp());
+}
+
+#[test]
+fn parse_gtp_play() {
+    assert_eq!(Ok(Play(White, 4, 16)), Move::from_gtp("white", "D16"));
+}
+
+#[test]
+fn parse_invalid_color() {
+    assert_eq!(Err(GTPError::InvalidColor("red".to_string())), Move::from_gtp("red", "D16"));
+}
+
+#[test]
+fn parse_invalid_vertex() {
+    assert_eq!(Err(GTPError::InvalidVertex("I9".to_string())), Move::from_gtp("b", "I9"));
+}
+
+#[test]
+fn produce_gtp_no_move() {
+    assert_eq!(Err(GTPError::NoMove), NoMove.to_gtp());
 }
 
 #[test]
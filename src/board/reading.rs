@@ -65,7 +65,7 @@ impl Board {
                 .filter(|coord| self.color(coord) == enemy)
                 .map(|&coord| self.get_chain(coord).unwrap().liberties())
                 .filter(|ref libs| libs.len() == 1)
-                .flat_map(|set| set.into_iter())
+                .flat_map(|libs| libs.iter())
                 .map(|coord| Play(player, coord.col,coord.row))
                 .filter(|&play| self.is_legal(play).is_ok());
 
@@ -90,7 +90,7 @@ impl Board {
                 } else if self.new_chain_liberties_greater_than_one(m) {
                     let mut cloned = self.clone();
                     cloned.play_legal_move(m);
-                    let gr = cloned.get_chain(*liberty).cloned();
+                    let gr = cloned.get_chain(liberty).cloned();
                     
                     if let Some(g) = gr {
                         if cloned.capture_ladder(&g).is_none() {
@@ -143,13 +143,13 @@ impl Board {
             }
         };
         
-        let cap = decide(*liberty1, *liberty2);
+        let cap = decide(liberty1, liberty2);
         if cap.is_some() {
             return cap;
         }
         
         //do the same test for the other liberty
-        let cap2 = decide(*liberty2, *liberty1);
+        let cap2 = decide(liberty2, liberty1);
         
         if cap2.is_some() {
             return cap2;
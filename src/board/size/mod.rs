@@ -0,0 +1,126 @@
+/************************************************************************
+ *                                                                      *
+ * Copyright 2016 Urban Hafner                                          *
+ *                                                                      *
+ * This file is part of Iomrascálaí.                                    *
+ *                                                                      *
+ * Iomrascálaí is free software: you can redistribute it and/or modify  *
+ * it under the terms of the GNU General Public License as published by *
+ * the Free Software Foundation, either version 3 of the License, or    *
+ * (at your option) any later version.                                  *
+ *                                                                      *
+ * Iomrascálaí is distributed in the hope that it will be useful,       *
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of       *
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the        *
+ * GNU General Public License for more details.                         *
+ *                                                                      *
+ * You should have received a copy of the GNU General Public License    *
+ * along with Iomrascálaí.  If not, see <http://www.gnu.org/licenses/>. *
+ *                                                                      *
+ ************************************************************************/
+
+use board::Coord;
+use board::MAX_SIZE;
+
+use std::fmt;
+use std::str::FromStr;
+
+mod test;
+
+/// The dimensions of a board. Boards don't have to be square, but
+/// neither side can be longer than `MAX_SIZE`.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Size {
+    pub height: u8,
+    pub width: u8,
+}
+
+impl Size {
+
+    pub fn new(width: u8, height: u8) -> Result<Size, String> {
+        if width < 1 || width > MAX_SIZE || height < 1 || height > MAX_SIZE {
+            Err(format!("unacceptable size {}x{}", width, height))
+        } else {
+            Ok(Size { height: height, width: width })
+        }
+    }
+
+    pub fn square(size: u8) -> Size {
+        Size { height: size, width: size }
+    }
+
+    pub fn is_square(&self) -> bool {
+        self.width == self.height
+    }
+
+    /// The number of intersections on the board.
+    pub fn points(&self) -> usize {
+        self.width as usize * self.height as usize
+    }
+
+    /// Reads the value of the SGF `SZ` property, either a single
+    /// number or `width:height`.
+    pub fn from_sgf(value: &str) -> Result<Size, String> {
+        Self::parse(value, ':').ok_or(format!("invalid SGF size '{}'", value))
+            .and_then(|(width, height)| Self::new(width, height))
+    }
+
+    /// The value of the SGF `SZ` property.
+    pub fn to_sgf(&self) -> String {
+        if self.is_square() {
+            format!("{}", self.width)
+        } else {
+            format!("{}:{}", self.width, self.height)
+        }
+    }
+
+    /// The star points are on the third line of boards smaller than
+    /// 13x13 and on the fourth line otherwise. Odd sized boards also
+    /// get a center point, and only boards of 13x13 and larger have
+    /// star points on the middle of the sides.
+    pub fn is_star_point(&self, c: &Coord) -> bool {
+        let corner = |n: u8, x: u8| {
+            let line = if n >= 13 { 4 } else { 3 };
+            n >= 7 && (x == line || x == n + 1 - line)
+        };
+        let middle = |n: u8, x: u8| n >= 9 && n % 2 == 1 && x == (n + 1) / 2;
+        let large = self.width >= 13 && self.height >= 13;
+        (corner(self.width, c.col) && corner(self.height, c.row)) ||
+            (middle(self.width, c.col) && middle(self.height, c.row)) ||
+            (large && middle(self.width, c.col) && corner(self.height, c.row)) ||
+            (large && corner(self.width, c.col) && middle(self.height, c.row))
+    }
+
+    // Either a single number for square boards or the width and the
+    // height joined by the separator.
+    fn parse(s: &str, separator: char) -> Option<(u8, u8)> {
+        let numbers: Result<Vec<u8>, _> = s.split(separator).map(|n| n.trim().parse()).collect();
+        match numbers {
+            Ok(ref n) if n.len() == 1 => Some((n[0], n[0])),
+            Ok(ref n) if n.len() == 2 => Some((n[0], n[1])),
+            _ => None
+        }
+    }
+}
+
+// Accepts "19" as well as "19x13".
+impl FromStr for Size {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Size, String> {
+        Self::parse(s, 'x').ok_or(format!("invalid board size '{}'", s))
+            .and_then(|(width, height)| Self::new(width, height))
+    }
+}
+
+impl fmt::Display for Size {
+
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = if self.is_square() {
+            format!("{}", self.width)
+        } else {
+            format!("{}x{}", self.width, self.height)
+        };
+        s.fmt(f)
+    }
+}
@@ -0,0 +1,77 @@
+/************************************************************************
+ *                                                                      *
+ * Copyright 2016 Urban Hafner                                          *
+ *                                                                      *
+ * This file is part of Iomrascálaí.                                    *
+ *                                                                      *
+ * Iomrascálaí is free software: you can redistribute it and/or modify  *
+ * it under the terms of the GNU General Public License as published by *
+ * the Free Software Foundation, either version 3 of the License, or    *
+ * (at your option) any later version.                                  *
+ *                                                                      *
+ * Iomrascálaí is distributed in the hope that it will be useful,       *
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of       *
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the        *
+ * GNU General Public License for more details.                         *
+ *                                                                      *
+ * You should have received a copy of the GNU General Public License    *
+ * along with Iomrascálaí.  If not, see <http://www.gnu.org/licenses/>. *
+ *                                                                      *
+ ************************************************************************/
+
+#![cfg(test)]
+
+use board::Coord;
+use super::Size;
+
+#[test]
+fn sizes_larger_than_the_maximum_are_rejected() {
+    assert!(Size::new(25, 25).is_ok());
+    assert!(Size::new(26, 19).is_err());
+    assert!(Size::new(19, 0).is_err());
+}
+
+#[test]
+fn points_counts_all_intersections() {
+    assert_eq!(19 * 13, Size::new(19, 13).unwrap().points());
+}
+
+#[test]
+fn from_str_reads_square_and_rectangular_sizes() {
+    assert_eq!(Ok(Size::square(9)), "9".parse());
+    assert_eq!(Ok(Size::new(19, 13).unwrap()), "19x13".parse());
+    assert!("19x".parse::<Size>().is_err());
+    assert!("30".parse::<Size>().is_err());
+}
+
+#[test]
+fn sgf_sizes_can_be_read_and_written() {
+    assert_eq!(Ok(Size::square(19)), Size::from_sgf("19"));
+    assert_eq!(Ok(Size::new(7, 5).unwrap()), Size::from_sgf("7:5"));
+    assert_eq!("19", Size::square(19).to_sgf());
+    assert_eq!("7:5", Size::new(7, 5).unwrap().to_sgf());
+}
+
+#[test]
+fn star_points_on_19x19() {
+    let size = Size::square(19);
+    let stars = Coord::for_board_size(size).into_iter().filter(|c| size.is_star_point(c)).count();
+    assert_eq!(9, stars);
+    assert!(size.is_star_point(&Coord::new(4, 16)));
+    assert!(size.is_star_point(&Coord::new(10, 4)));
+}
+
+#[test]
+fn star_points_on_9x9() {
+    let size = Size::square(9);
+    let stars: Vec<Coord> = Coord::for_board_size(size).into_iter().filter(|c| size.is_star_point(c)).collect();
+    assert_eq!(vec!(Coord::new(3, 3), Coord::new(7, 3), Coord::new(5, 5), Coord::new(3, 7), Coord::new(7, 7)), stars);
+}
+
+#[test]
+fn star_points_on_rectangular_boards() {
+    let size = Size::new(19, 9).unwrap();
+    assert!(size.is_star_point(&Coord::new(4, 3)));
+    assert!(size.is_star_point(&Coord::new(16, 7)));
+    assert!(!size.is_star_point(&Coord::new(10, 3)));
+}
@@ -0,0 +1,135 @@
+/************************************************************************
+ *                                                                      *
+ * Copyright 2016 Urban Hafner                                          *
+ *                                                                      *
+ * This file is part of Iomrascálaí.                                    *
+ *                                                                      *
+ * Iomrascálaí is free software: you can redistribute it and/or modify  *
+ * it under the terms of the GNU General Public License as published by *
+ * the Free Software Foundation, either version 3 of the License, or    *
+ * (at your option) any later version.                                  *
+ *                                                                      *
+ * Iomrascálaí is distributed in the hope that it will be useful,       *
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of       *
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the        *
+ * GNU General Public License for more details.                         *
+ *                                                                      *
+ * You should have received a copy of the GNU General Public License    *
+ * along with Iomrascálaí.  If not, see <http://www.gnu.org/licenses/>. *
+ *                                                                      *
+ ************************************************************************/
+
+#![cfg(test)]
+
+use board::Black;
+use board::Board;
+use board::Color;
+use board::Coord;
+use board::Empty;
+use board::Pass;
+use board::Play;
+use board::Size;
+use board::White;
+use ruleset::KgsChinese;
+use sgf::Parser;
+
+use std::cmp;
+use std::path::Path;
+use test::Bencher;
+
+// Plays the black and white stones alternately, passing for the
+// player that runs out of stones first.
+fn board_with(black: Vec<(u8, u8)>, white: Vec<(u8, u8)>) -> Board {
+    let mut board = Board::new(9, 6.5, KgsChinese);
+    for i in 0..cmp::max(black.len(), white.len()) {
+        match black.get(i) {
+            Some(&(col, row)) => board.play_legal_move(Play(Black, col, row)),
+            None => board.play_legal_move(Pass(Black)),
+        }
+        match white.get(i) {
+            Some(&(col, row)) => board.play_legal_move(Play(White, col, row)),
+            None => board.play_legal_move(Pass(White)),
+        }
+    }
+    board
+}
+
+// A black wall on the second line with stones on B1 and D1 which
+// leaves it with three eyes.
+fn black_wall(extra: Vec<(u8, u8)>) -> Vec<(u8, u8)> {
+    let mut stones: Vec<(u8, u8)> = (1..10).map(|col| (col, 2)).collect();
+    stones.extend(extra);
+    stones
+}
+
+fn white_wall() -> Vec<(u8, u8)> {
+    (1..10).map(|col| (col, 9)).collect()
+}
+
+fn area_at(area: &Vec<Color>, board: &Board, col: u8, row: u8) -> Color {
+    area[Coord::new(col, row).to_index(board.size())]
+}
+
+#[test]
+fn a_chain_with_two_vital_regions_is_alive() {
+    let board = board_with(black_wall(vec!((2, 1), (4, 1))), white_wall());
+    let id = board.chain_id(&Coord::new(1, 2));
+    assert_eq!(vec!(id), board.unconditionally_alive_chains(Black));
+    assert!(board.unconditionally_alive_chains(White).is_empty());
+}
+
+#[test]
+fn a_chain_with_one_region_is_not_alive() {
+    let board = board_with(black_wall(vec!()), white_wall());
+    assert!(board.unconditionally_alive_chains(Black).is_empty());
+}
+
+#[test]
+fn pass_alive_area_contains_the_chain_and_its_eyes() {
+    let board = board_with(black_wall(vec!((2, 1), (4, 1))), white_wall());
+    let area = board.pass_alive_area();
+    assert_eq!(Black, area_at(&area, &board, 1, 1));
+    assert_eq!(Black, area_at(&area, &board, 3, 1));
+    assert_eq!(Black, area_at(&area, &board, 7, 1));
+    assert_eq!(Black, area_at(&area, &board, 5, 2));
+    // The rest of the board isn't vital to the black chain.
+    assert_eq!(Empty, area_at(&area, &board, 5, 5));
+    assert_eq!(Empty, area_at(&area, &board, 5, 9));
+}
+
+#[test]
+fn stones_inside_pass_alive_area_are_dead() {
+    let board = board_with(black_wall(vec!((2, 1), (4, 1))), vec!((7, 1), (5, 5)));
+    let area = board.pass_alive_area();
+    assert_eq!(White, board.color(&Coord::new(7, 1)));
+    assert_eq!(Black, area_at(&area, &board, 7, 1));
+    assert_eq!(Empty, area_at(&area, &board, 5, 5));
+}
+
+#[test]
+fn settled_points_leave_out_regions_with_stones() {
+    let board = board_with(black_wall(vec!((2, 1), (4, 1))), vec!((7, 1), (5, 5)));
+    let settled = board.settled_points();
+    assert!(settled[Coord::new(1, 1).to_index(Size::square(9))]);
+    assert!(settled[Coord::new(3, 1).to_index(Size::square(9))]);
+    assert!(!settled[Coord::new(6, 1).to_index(Size::square(9))]);
+    assert!(!settled[Coord::new(5, 6).to_index(Size::square(9))]);
+}
+
+#[test]
+fn is_settled_ignores_the_settled_points_of_other_sizes() {
+    let settled = vec![true; Size::square(9).points()];
+    assert!(Board::is_settled(&settled, Size::square(9), &Coord::new(9, 9)));
+    assert!(!Board::is_settled(&settled, Size::square(19), &Coord::new(19, 19)));
+    assert!(!Board::is_settled(&vec!(), Size::square(9), &Coord::new(1, 1)));
+}
+
+// The search calculates the settled points once per position instead
+// of for every expansion and playout. Compare with the playout_*
+// benchmarks to see why.
+#[bench]
+fn settled_points_19x19(b: &mut Bencher) {
+    let parser = Parser::from_path(Path::new("fixtures/sgf/ladders.sgf")).unwrap();
+    let board = parser.game().unwrap().board();
+    b.iter(|| board.settled_points());
+}
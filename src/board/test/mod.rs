@@ -38,11 +38,15 @@ use sgf::Parser;
 
 use std::path::Path;
 
+mod benson;
 mod diagonals;
 mod eye;
 mod ko;
 mod hypotheticals;
+#[cfg(not(feature = "tree-walk-patterns"))]
+mod pattern_codes;
 mod reading;
+mod undo;
 
 #[test]
 fn getting_a_valid_coord_returns_a_color() {
@@ -495,3 +499,53 @@ fn adv_stones_removed_only_contains_each_coord_once() {
     removed_coords.dedup();
     assert_eq!(removed_coords.len(), board.adv_stones_removed().len());
 }
+
+#[test]
+fn the_empty_board_has_a_hash_of_zero() {
+    let b = Board::new(9, 6.5, KgsChinese);
+    assert_eq!(0, b.hash());
+}
+
+#[test]
+fn the_hash_only_depends_on_the_stones() {
+    let mut b1 = Board::new(9, 6.5, Minimal);
+    b1.play(Play(Black, 3, 3));
+    b1.play(Play(White, 5, 5));
+    let mut b2 = Board::new(9, 6.5, Minimal);
+    b2.play(Play(White, 5, 5));
+    b2.play(Play(Black, 3, 3));
+    assert!(b1.hash() != 0);
+    assert_eq!(b1.hash(), b2.hash());
+}
+
+#[test]
+fn capturing_a_stone_removes_it_from_the_hash() {
+    let mut b1 = Board::new(9, 6.5, Minimal);
+    b1.play(Play(White, 1, 1));
+    b1.play(Play(Black, 1, 2));
+    b1.play(Play(Black, 2, 1));
+    let mut b2 = Board::new(9, 6.5, Minimal);
+    b2.play(Play(Black, 1, 2));
+    b2.play(Play(Black, 2, 1));
+    assert_eq!(Empty, b1.color(&Coord::new(1, 1)));
+    assert_eq!(b2.hash(), b1.hash());
+}
+
+#[test]
+fn transformed_hash_with_the_identity_is_the_hash() {
+    let mut b = Board::new(9, 6.5, Minimal);
+    b.play(Play(Black, 3, 3));
+    b.play(Play(White, 5, 7));
+    assert_eq!(b.hash(), b.transformed_hash(|c| c));
+}
+
+#[test]
+fn transformed_hash_is_the_hash_of_the_transformed_position() {
+    let mut b1 = Board::new(9, 6.5, Minimal);
+    b1.play(Play(Black, 3, 3));
+    b1.play(Play(White, 5, 7));
+    let mut b2 = Board::new(9, 6.5, Minimal);
+    b2.play(Play(Black, 7, 3));
+    b2.play(Play(White, 5, 7));
+    assert_eq!(b2.hash(), b1.transformed_hash(|c| Coord::new(10 - c.col, c.row)));
+}
@@ -0,0 +1,84 @@
+/************************************************************************
+ *                                                                      *
+ * Copyright 2016 Urban Hafner                                          *
+ *                                                                      *
+ * This file is part of Iomrascálaí.                                    *
+ *                                                                      *
+ * Iomrascálaí is free software: you can redistribute it and/or modify  *
+ * it under the terms of the GNU General Public License as published by *
+ * the Free Software Foundation, either version 3 of the License, or    *
+ * (at your option) any later version.                                  *
+ *                                                                      *
+ * Iomrascálaí is distributed in the hope that it will be useful,       *
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of       *
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the        *
+ * GNU General Public License for more details.                         *
+ *                                                                      *
+ * You should have received a copy of the GNU General Public License    *
+ * along with Iomrascálaí.  If not, see <http://www.gnu.org/licenses/>. *
+ *                                                                      *
+ ************************************************************************/
+
+#![cfg(test)]
+
+use board::Black;
+use board::Board;
+use board::Coord;
+use board::Empty;
+use board::Play;
+use board::White;
+use ruleset::KgsChinese;
+
+use rand::Rng;
+use rand::weak_rng;
+
+fn expected_code(board: &Board, coord: &Coord) -> u16 {
+    board.neighbours8_checked(*coord).iter().enumerate().fold(0u16, |code, (i, color)| {
+        let value: u16 = match *color {
+            Some(Empty) => 0,
+            Some(Black) => 1,
+            Some(White) => 2,
+            None => 3,
+        };
+        code | (value << (2 * i))
+    })
+}
+
+fn assert_codes_match(board: &Board) {
+    for coord in Coord::for_board_size(board.size()) {
+        assert_eq!(expected_code(board, &coord), board.pattern_code(&coord), "at {:?}", coord);
+    }
+}
+
+#[test]
+fn the_corners_of_an_empty_board_have_off_board_neighbours() {
+    let board = Board::new(9, 6.5, KgsChinese);
+    // NW, SE, S, SW, and W are off board
+    let code = (3 << 0) | (3 << 8) | (3 << 10) | (3 << 12) | (3 << 14);
+    assert_eq!(code, board.pattern_code(&Coord::new(1, 1)));
+    assert_codes_match(&board);
+}
+
+#[test]
+fn the_codes_stay_in_sync_during_a_game() {
+    let mut board = Board::new(9, 6.5, KgsChinese);
+    let mut rng = weak_rng();
+    for _ in 0..300 {
+        let moves = board.legal_moves_without_superko_check();
+        if moves.is_empty() {
+            break;
+        }
+        let m = moves[rng.gen::<usize>() % moves.len()];
+        board.play_legal_move(m);
+        assert_codes_match(&board);
+    }
+}
+
+#[test]
+fn removing_dead_stones_updates_the_codes() {
+    let mut board = Board::new(9, 6.5, KgsChinese);
+    board.play_legal_move(Play(Black, 5, 5));
+    board.remove_dead_stone(&Coord::new(5, 5));
+    assert_eq!(0, board.pattern_code(&Coord::new(5, 6)));
+    assert_codes_match(&board);
+}
@@ -0,0 +1,163 @@
+/************************************************************************
+ *                                                                      *
+ * Copyright 2016 Urban Hafner                                          *
+ *                                                                      *
+ * This file is part of Iomrascálaí.                                    *
+ *                                                                      *
+ * Iomrascálaí is free software: you can redistribute it and/or modify  *
+ * it under the terms of the GNU General Public License as published by *
+ * the Free Software Foundation, either version 3 of the License, or    *
+ * (at your option) any later version.                                  *
+ *                                                                      *
+ * Iomrascálaí is distributed in the hope that it will be useful,       *
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of       *
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the        *
+ * GNU General Public License for more details.                         *
+ *                                                                      *
+ * You should have received a copy of the GNU General Public License    *
+ * along with Iomrascálaí.  If not, see <http://www.gnu.org/licenses/>. *
+ *                                                                      *
+ ************************************************************************/
+
+#![cfg(test)]
+
+use board::Black;
+use board::Board;
+use board::Coord;
+use board::Empty;
+use board::IllegalMove;
+use board::Pass;
+use board::Play;
+use board::White;
+use ruleset::AnySizeTrompTaylor;
+use ruleset::KgsChinese;
+use ruleset::Ruleset;
+
+use rand::Rng;
+use rand::weak_rng;
+
+fn assert_same(expected: &Board, actual: &Board) {
+    for coord in Coord::for_board_size(expected.size()) {
+        assert_eq!(expected.color(&coord), actual.color(&coord), "color at {:?}", coord);
+        if expected.color(&coord) != Empty {
+            assert_eq!(expected.chain_id(&coord), actual.chain_id(&coord), "chain id at {:?}", coord);
+        }
+    }
+    assert_eq!(expected.chains, actual.chains);
+    assert_eq!(expected.vacant, actual.vacant);
+    assert_eq!(expected.ko, actual.ko);
+    assert_eq!(expected.adv_stones_removed, actual.adv_stones_removed);
+    assert_eq!(expected.friend_stones_removed, actual.friend_stones_removed);
+    assert_eq!(expected.previous_player, actual.previous_player);
+    assert_eq!(expected.consecutive_passes, actual.consecutive_passes);
+    assert_eq!(expected.resigned_by, actual.resigned_by);
+    assert_eq!(expected.hash(), actual.hash());
+    assert_same_pattern_codes(expected, actual);
+}
+
+#[cfg(not(feature = "tree-walk-patterns"))]
+fn assert_same_pattern_codes(expected: &Board, actual: &Board) {
+    for coord in Coord::for_board_size(expected.size()) {
+        assert_eq!(expected.pattern_code(&coord), actual.pattern_code(&coord), "pattern code at {:?}", coord);
+    }
+}
+
+#[cfg(feature = "tree-walk-patterns")]
+fn assert_same_pattern_codes(_: &Board, _: &Board) {}
+
+// Plays a random game and takes back every single move right after
+// it was played, and then the whole game at the end.
+fn random_game(ruleset: Ruleset) {
+    let mut board = Board::new(9, 6.5, ruleset);
+    let mut history = vec!();
+    let mut rng = weak_rng();
+    for _ in 0..250 {
+        let moves = board.legal_moves_without_superko_check();
+        if moves.is_empty() {
+            break;
+        }
+        let m = moves[rng.gen::<usize>() % moves.len()];
+        let before = board.clone();
+        board.make_legal_move(m);
+        let after = board.clone();
+        board.unmake_move();
+        assert_same(&before, &board);
+        board.make_legal_move(m);
+        assert_same(&after, &board);
+        history.push(before);
+    }
+    while let Some(before) = history.pop() {
+        board.unmake_move();
+        assert_same(&before, &board);
+    }
+    assert_eq!(0, board.undoable_moves());
+}
+
+#[test]
+fn random_games_can_be_taken_back() {
+    for _ in 0..10 {
+        random_game(KgsChinese);
+    }
+}
+
+#[test]
+fn random_games_with_suicide_can_be_taken_back() {
+    for _ in 0..10 {
+        random_game(AnySizeTrompTaylor);
+    }
+}
+
+#[test]
+fn taking_back_a_ko_capture_restores_the_position() {
+    let mut board = Board::new(5, 6.5, KgsChinese);
+    for &m in [Play(Black, 2, 3), Play(White, 4, 4), Play(Black, 3, 4), Play(White, 4, 2),
+               Play(Black, 3, 2), Play(White, 5, 3), Play(Black, 1, 1), Play(White, 3, 3)].iter() {
+        board.play(m).unwrap();
+    }
+    let before = board.clone();
+    board.make_move(Play(Black, 4, 3)).unwrap();
+    assert_eq!(Err(IllegalMove::Ko), board.is_legal(Play(White, 3, 3)));
+    board.unmake_move();
+    assert_same(&before, &board);
+    board.make_move(Play(Black, 4, 3)).unwrap();
+    board.make_move(Play(White, 5, 5)).unwrap();
+    board.make_move(Play(Black, 1, 5)).unwrap();
+    board.make_move(Play(White, 3, 3)).unwrap();
+    assert_eq!(Empty, board.color(&Coord::new(4, 3)));
+    for _ in 0..4 {
+        board.unmake_move();
+    }
+    assert_same(&before, &board);
+    assert_eq!(0, board.undoable_moves());
+}
+
+#[test]
+fn passes_can_be_taken_back() {
+    let mut board = Board::new(9, 6.5, KgsChinese);
+    board.make_move(Pass(Black)).unwrap();
+    board.make_move(Pass(White)).unwrap();
+    assert!(board.is_game_over());
+    board.unmake_move();
+    assert!(!board.is_game_over());
+    assert_eq!(White, board.next_player());
+}
+
+#[test]
+#[should_panic]
+fn unmake_without_a_move_fails() {
+    let mut board = Board::new(9, 6.5, KgsChinese);
+    board.play_legal_move(Play(Black, 1, 1));
+    board.unmake_move();
+}
+
+#[test]
+fn clone_from_copies_the_position_but_not_the_recorded_moves() {
+    let mut board = Board::new(9, 6.5, KgsChinese);
+    board.play(Play(Black, 3, 3)).unwrap();
+    board.make_move(Play(White, 4, 4)).unwrap();
+    let mut copy = Board::new(5, 0.5, KgsChinese);
+    copy.play(Play(Black, 1, 1)).unwrap();
+    copy.clone_from(&board);
+    assert_same(&board, &copy);
+    assert_eq!(0, copy.undoable_moves());
+}
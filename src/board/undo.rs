@@ -0,0 +1,143 @@
+/************************************************************************
+ *                                                                      *
+ * Copyright 2016 Urban Hafner                                          *
+ *                                                                      *
+ * This file is part of Iomrascálaí.                                    *
+ *                                                                      *
+ * Iomrascálaí is free software: you can redistribute it and/or modify  *
+ * it under the terms of the GNU General Public License as published by *
+ * the Free Software Foundation, either version 3 of the License, or    *
+ * (at your option) any later version.                                  *
+ *                                                                      *
+ * Iomrascálaí is distributed in the hope that it will be useful,       *
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of       *
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the        *
+ * GNU General Public License for more details.                         *
+ *                                                                      *
+ * You should have received a copy of the GNU General Public License    *
+ * along with Iomrascálaí.  If not, see <http://www.gnu.org/licenses/>. *
+ *                                                                      *
+ ************************************************************************/
+
+use board::Board;
+use board::Chain;
+use board::Color;
+use board::Coord;
+use board::IllegalMove;
+use board::Move;
+
+// A single change to the board. The changes of a move are recorded
+// in the order they happen and are reverted in the opposite order.
+// This way the chain ids stored in the log are always valid when the
+// change is reverted.
+#[derive(Debug)]
+pub enum Change {
+    // Start of a move, contains everything needed to restore the
+    // bookkeeping fields of the board.
+    Move {
+        adv_stones_removed:    Vec<Coord>,
+        consecutive_passes:    u8,
+        friend_stones_removed: Vec<Coord>,
+        ko:                    Option<Coord>,
+        previous_player:       Color,
+        resigned_by:           Color,
+    },
+    ChainCreated,
+    ChainDiscarded {
+        chain: Chain,
+        id:    usize,
+    },
+    ColorChanged {
+        color: Color,
+        coord: Coord,
+    },
+    CoordAdded(usize),
+    LibertyAdded(usize, Coord),
+    LibertyRemoved(usize, Coord),
+    VacantRemoved {
+        added: usize,
+        coord: Coord,
+        index: usize,
+    },
+}
+
+impl Board {
+
+    /// Plays the move after checking that it is legal and records
+    /// all changes so that it can be taken back with
+    /// `unmake_move()`.
+    pub fn make_move(&mut self, m: Move) -> Result<(), IllegalMove> {
+        try!(self.is_legal(m));
+        self.make_legal_move(m);
+        Ok(())
+    }
+
+    /// Same as `make_move()` but for moves that are already known to
+    /// be legal.
+    pub fn make_legal_move(&mut self, m: Move) {
+        let start = Change::Move {
+            adv_stones_removed:    self.adv_stones_removed.clone(),
+            consecutive_passes:    self.consecutive_passes,
+            friend_stones_removed: self.friend_stones_removed.clone(),
+            ko:                    self.ko,
+            previous_player:       self.previous_player,
+            resigned_by:           self.resigned_by,
+        };
+        self.changes.push(start);
+        self.record_changes = true;
+        self.play_legal_move(m);
+        self.record_changes = false;
+    }
+
+    /// Takes back the last move played with `make_move()` or
+    /// `make_legal_move()`. Moves played with `play()` or
+    /// `play_legal_move()` in between can't be taken back and
+    /// corrupt the board!
+    pub fn unmake_move(&mut self) {
+        while let Some(change) = self.changes.pop() {
+            match change {
+                Change::Move { adv_stones_removed, consecutive_passes, friend_stones_removed, ko, previous_player, resigned_by } => {
+                    self.adv_stones_removed = adv_stones_removed;
+                    self.consecutive_passes = consecutive_passes;
+                    self.friend_stones_removed = friend_stones_removed;
+                    self.ko = ko;
+                    self.previous_player = previous_player;
+                    self.resigned_by = resigned_by;
+                    return;
+                },
+                Change::ChainCreated => {
+                    self.chains.pop();
+                },
+                Change::ChainDiscarded { chain, id } => {
+                    self.restore_chain(id, chain);
+                },
+                Change::ColorChanged { color, coord } => {
+                    self.set_color(&coord, color);
+                },
+                Change::CoordAdded(id) => {
+                    self.chains[id].remove_last_coord();
+                },
+                Change::LibertyAdded(id, coord) => {
+                    self.chains[id].remove_liberty(coord);
+                },
+                Change::LibertyRemoved(id, coord) => {
+                    self.chains[id].add_liberty(coord);
+                },
+                Change::VacantRemoved { added, coord, index } => {
+                    let len = self.vacant.len() - added;
+                    self.vacant.truncate(len);
+                    self.vacant.push(coord);
+                    self.vacant.swap(index, len);
+                },
+            }
+        }
+        panic!("unmake_move() called without a move to take back");
+    }
+
+    /// The number of moves that can be taken back with
+    /// `unmake_move()`.
+    pub fn undoable_moves(&self) -> usize {
+        self.changes.iter().filter(|c| match **c { Change::Move { .. } => true, _ => false }).count()
+    }
+
+}
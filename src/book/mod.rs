@@ -0,0 +1,336 @@
+/************************************************************************
+ *                                                                      *
+ * Copyright 2016 Urban Hafner                                          *
+ *                                                                      *
+ * This file is part of Iomrascálaí.                                    *
+ *                                                                      *
+ * Iomrascálaí is free software: you can redistribute it and/or modify  *
+ * it under the terms of the GNU General Public License as published by *
+ * the Free Software Foundation, either version 3 of the License, or    *
+ * (at your option) any later version.                                  *
+ *                                                                      *
+ * Iomrascálaí is distributed in the hope that it will be useful,       *
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of       *
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the        *
+ * GNU General Public License for more details.                         *
+ *                                                                      *
+ * You should have received a copy of the GNU General Public License    *
+ * along with Iomrascálaí.  If not, see <http://www.gnu.org/licenses/>. *
+ *                                                                      *
+ ************************************************************************/
+
+use board::Coord;
+use board::Move;
+use board::Play;
+use board::Size;
+use board::White;
+use config::BookConfig;
+use game::Game;
+use ruleset::Minimal;
+use sgf::Parser;
+
+use rand::Rng;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::prelude::*;
+
+mod test;
+
+const MAGIC: &'static [u8] = b"IOMB";
+const VERSION: u8 = 1;
+// Xor-ed into the key of positions where white is to move.
+const WHITE_TO_MOVE: u64 = 0x9e3779b97f4a7c15;
+
+/// An opening book that maps positions to the moves played in them
+/// and how often they were played. The positions are normalized over
+/// all symmetries of the board, so that e.g. all four corners share
+/// the same entries.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Book {
+    entries: HashMap<u64, Vec<(Coord, u32)>>,
+    size: Size,
+}
+
+impl Book {
+
+    pub fn new(size: Size) -> Book {
+        Book {
+            entries: HashMap::new(),
+            size: size,
+        }
+    }
+
+    pub fn from_file(filename: &str) -> Result<Book, String> {
+        let mut bytes = Vec::new();
+        try!(File::open(filename)
+             .and_then(|mut file| file.read_to_end(&mut bytes))
+             .map_err(|e| format!("can't read opening book {}: {}", filename, e)));
+        Self::from_bytes(&bytes)
+    }
+
+    /// The format is the magic bytes and the version, followed by the
+    /// width and height of the board and the number of positions.
+    /// For every position there's the key, the number of moves, and
+    /// the column, row, and count of every move. All numbers are
+    /// little endian.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Book, String> {
+        if bytes.len() < 11 || &bytes[0..4] != MAGIC {
+            return Err("not an opening book".to_string());
+        }
+        if bytes[4] != VERSION {
+            return Err(format!("unsupported opening book version {}", bytes[4]));
+        }
+        let size = try!(Size::new(bytes[5], bytes[6]));
+        let mut book = Book::new(size);
+        let mut reader = ByteReader { bytes: bytes, position: 7 };
+        let positions = try!(reader.u32());
+        for _ in 0..positions {
+            let key = try!(reader.u64());
+            let count = try!(reader.u16());
+            let mut moves = Vec::with_capacity(count as usize);
+            for _ in 0..count {
+                let coord = Coord::new(try!(reader.u8()), try!(reader.u8()));
+                if !coord.is_inside(size) {
+                    return Err("opening book move outside of the board".to_string());
+                }
+                moves.push((coord, try!(reader.u32())));
+            }
+            book.entries.insert(key, moves);
+        }
+        Ok(book)
+    }
+
+    pub fn save(&self, filename: &str) -> Result<(), String> {
+        File::create(filename)
+            .and_then(|mut file| file.write_all(&self.to_bytes()))
+            .map_err(|e| format!("can't write opening book {}: {}", filename, e))
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = MAGIC.to_vec();
+        bytes.push(VERSION);
+        bytes.push(self.size.width);
+        bytes.push(self.size.height);
+        push_u32(&mut bytes, self.entries.len() as u32);
+        // Sorted so that the same book is always written the same way.
+        let mut keys: Vec<&u64> = self.entries.keys().collect();
+        keys.sort();
+        for key in keys {
+            let moves = &self.entries[key];
+            push_u64(&mut bytes, *key);
+            push_u16(&mut bytes, moves.len() as u16);
+            for &(coord, count) in moves {
+                bytes.push(coord.col);
+                bytes.push(coord.row);
+                push_u32(&mut bytes, count);
+            }
+        }
+        bytes
+    }
+
+    /// The number of positions in the book.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn size(&self) -> Size {
+        self.size
+    }
+
+    /// Records that `m` was played in the position of `game`. Passes,
+    /// resignations, moves out of turn, and positions of other board
+    /// sizes are ignored.
+    pub fn add(&mut self, game: &Game, m: Move) {
+        if game.size() != self.size || m.is_pass() || m.is_resign() || *m.color() != game.next_player() {
+            return;
+        }
+        let (key, symmetries) = self.key(game);
+        // In symmetric positions equivalent moves are stored as the
+        // same one.
+        let coord = symmetries.iter().map(|&s| transform(m.coord(), s, self.size)).min().unwrap();
+        let moves = self.entries.entry(key).or_insert_with(Vec::new);
+        match moves.iter().position(|&(c, _)| c == coord) {
+            Some(index) => moves[index].1 += 1,
+            None => moves.push((coord, 1)),
+        }
+    }
+
+    /// Adds the first `max_depth` moves of the game in the SGF file.
+    /// Returns `false` if the game was skipped because it has a
+    /// different board size or setup stones (e.g. handicap stones).
+    pub fn add_game(&mut self, parser: &Parser, max_depth: usize) -> bool {
+        if parser.size() != self.size || !parser.points("AB").is_empty() || !parser.points("AW").is_empty() {
+            return false;
+        }
+        let mut game = Game::with_super_ko(self.size, 6.5, Minimal, Minimal.super_ko());
+        for &m in parser.moves().iter().take(max_depth) {
+            self.add(&game, m);
+            game = match game.play(m) {
+                Ok(g) => g,
+                Err(_) => break,
+            };
+        }
+        true
+    }
+
+    /// The book moves for the position of `game` and how often they
+    /// were played.
+    pub fn moves(&self, game: &Game) -> Vec<(Move, u32)> {
+        if game.size() != self.size {
+            return vec!();
+        }
+        let (key, symmetries) = self.key(game);
+        let symmetry = symmetries[0];
+        let color = game.next_player();
+        match self.entries.get(&key) {
+            Some(moves) => moves.iter()
+                .map(|&(coord, count)| {
+                    let c = transform(coord, inverse(symmetry), self.size);
+                    (Play(color, c.col, c.row), count)
+                })
+                .collect(),
+            None => vec!(),
+        }
+    }
+
+    /// Picks one of the legal book moves that were played at least
+    /// `min_count` times. Returns `None` if there are none or if the
+    /// game is already past `max_depth` moves.
+    pub fn pick<R: Rng>(&self, game: &Game, config: &BookConfig, rng: &mut R) -> Option<Move> {
+        if game.move_number() >= config.max_depth {
+            return None;
+        }
+        let moves: Vec<(Move, u32)> = self.moves(game).into_iter()
+            .filter(|&(m, count)| count as usize >= config.min_count && game.play(m).is_ok())
+            .collect();
+        if moves.is_empty() {
+            return None;
+        }
+        if config.randomness <= 0.0 {
+            return moves.iter().max_by_key(|&&(_, count)| count).map(|&(m, _)| m);
+        }
+        let weights: Vec<f64> = moves.iter()
+            .map(|&(_, count)| (count as f64).powf(1.0 / config.randomness as f64))
+            .collect();
+        let total: f64 = weights.iter().sum();
+        let mut r = rng.gen::<f64>() * total;
+        for (&(m, _), weight) in moves.iter().zip(weights) {
+            if r < weight {
+                return Some(m);
+            }
+            r -= weight;
+        }
+        moves.last().map(|&(m, _)| m)
+    }
+
+    // The smallest hash of all symmetric positions and the symmetries
+    // that produce it. There's more than one if the position itself
+    // is symmetric (e.g. the empty board).
+    fn key(&self, game: &Game) -> (u64, Vec<usize>) {
+        let board = game.board();
+        let size = self.size;
+        let to_move = if game.next_player() == White { WHITE_TO_MOVE } else { 0 };
+        let hashes: Vec<(u64, usize)> = symmetries(size).into_iter()
+            .map(|symmetry| (board.transformed_hash(|c| transform(c, symmetry, size)) ^ to_move, symmetry))
+            .collect();
+        let key = hashes.iter().map(|&(hash, _)| hash).min().unwrap();
+        let symmetries = hashes.into_iter()
+            .filter(|&(hash, _)| hash == key)
+            .map(|(_, symmetry)| symmetry)
+            .collect();
+        (key, symmetries)
+    }
+
+}
+
+// Rectangular boards can't be rotated by 90 degrees or be mirrored
+// along a diagonal.
+fn symmetries(size: Size) -> Vec<usize> {
+    if size.is_square() {
+        (0..8).collect()
+    } else {
+        vec!(0, 2, 4, 5)
+    }
+}
+
+// Same numbering as the symmetries of the large patterns. The
+// coordinates are doubled so that the center of even sized boards
+// doesn't have to be a point.
+fn transform(coord: Coord, symmetry: usize, size: Size) -> Coord {
+    let x = 2 * coord.col as isize - (size.width as isize + 1);
+    let y = 2 * coord.row as isize - (size.height as isize + 1);
+    let (x, y) = match symmetry {
+        0 => ( x,  y),
+        1 => (-y,  x),
+        2 => (-x, -y),
+        3 => ( y, -x),
+        4 => (-x,  y),
+        5 => ( x, -y),
+        6 => ( y,  x),
+        _ => (-y, -x),
+    };
+    Coord::new(((x + size.width as isize + 1) / 2) as u8, ((y + size.height as isize + 1) / 2) as u8)
+}
+
+fn inverse(symmetry: usize) -> usize {
+    match symmetry {
+        1 => 3,
+        3 => 1,
+        s => s,
+    }
+}
+
+fn push_u16(bytes: &mut Vec<u8>, n: u16) {
+    for i in 0..2 {
+        bytes.push((n >> (8 * i)) as u8);
+    }
+}
+
+fn push_u32(bytes: &mut Vec<u8>, n: u32) {
+    for i in 0..4 {
+        bytes.push((n >> (8 * i)) as u8);
+    }
+}
+
+fn push_u64(bytes: &mut Vec<u8>, n: u64) {
+    for i in 0..8 {
+        bytes.push((n >> (8 * i)) as u8);
+    }
+}
+
+struct ByteReader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> ByteReader<'a> {
+
+    fn read(&mut self, n: usize) -> Result<u64, String> {
+        if self.position + n > self.bytes.len() {
+            return Err("truncated opening book".to_string());
+        }
+        let mut value = 0;
+        for i in 0..n {
+            value |= (self.bytes[self.position + i] as u64) << (8 * i);
+        }
+        self.position += n;
+        Ok(value)
+    }
+
+    fn u8(&mut self) -> Result<u8, String> {
+        self.read(1).map(|n| n as u8)
+    }
+
+    fn u16(&mut self) -> Result<u16, String> {
+        self.read(2).map(|n| n as u16)
+    }
+
+    fn u32(&mut self) -> Result<u32, String> {
+        self.read(4).map(|n| n as u32)
+    }
+
+    fn u64(&mut self) -> Result<u64, String> {
+        self.read(8)
+    }
+
+}
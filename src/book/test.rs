@@ -0,0 +1,140 @@
+/************************************************************************
+ *                                                                      *
+ * Copyright 2016 Urban Hafner                                          *
+ *                                                                      *
+ * This file is part of Iomrascálaí.                                    *
+ *                                                                      *
+ * Iomrascálaí is free software: you can redistribute it and/or modify  *
+ * it under the terms of the GNU General Public License as published by *
+ * the Free Software Foundation, either version 3 of the License, or    *
+ * (at your option) any later version.                                  *
+ *                                                                      *
+ * Iomrascálaí is distributed in the hope that it will be useful,       *
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of       *
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the        *
+ * GNU General Public License for more details.                         *
+ *                                                                      *
+ * You should have received a copy of the GNU General Public License    *
+ * along with Iomrascálaí.  If not, see <http://www.gnu.org/licenses/>. *
+ *                                                                      *
+ ************************************************************************/
+
+#![cfg(test)]
+
+use board::Black;
+use board::Pass;
+use board::Play;
+use board::Size;
+use board::White;
+use config::Config;
+use game::Game;
+use ruleset::KgsChinese;
+use sgf::Parser;
+use super::Book;
+
+use rand::weak_rng;
+
+fn game() -> Game {
+    Game::new(9, 6.5, KgsChinese)
+}
+
+#[test]
+fn add_and_look_up_a_move() {
+    let mut book = Book::new(Size::square(9));
+    book.add(&game(), Play(Black, 3, 3));
+    book.add(&game(), Play(Black, 3, 3));
+    assert_eq!(vec!((Play(Black, 3, 3), 2)), book.moves(&game()));
+}
+
+#[test]
+fn symmetric_positions_share_the_same_entry() {
+    let mut book = Book::new(Size::square(9));
+    let g = game().play(Play(Black, 3, 3)).unwrap();
+    book.add(&g, Play(White, 7, 7));
+    assert_eq!(1, book.len());
+    // The same position rotated by 90 degrees.
+    let rotated = game().play(Play(Black, 7, 3)).unwrap();
+    assert_eq!(vec!((Play(White, 3, 7), 1)), book.moves(&rotated));
+}
+
+#[test]
+fn equivalent_moves_in_symmetric_positions_are_counted_together() {
+    let mut book = Book::new(Size::square(9));
+    book.add(&game(), Play(Black, 3, 3));
+    book.add(&game(), Play(Black, 7, 7));
+    book.add(&game(), Play(Black, 3, 7));
+    assert_eq!(1, book.moves(&game()).len());
+    assert_eq!(3, book.moves(&game())[0].1);
+}
+
+#[test]
+fn the_player_to_move_is_part_of_the_position() {
+    let mut book = Book::new(Size::square(9));
+    let g = game().play(Play(Black, 3, 3)).unwrap().play(Pass(White)).unwrap();
+    book.add(&g, Play(Black, 7, 7));
+    let other = game().play(Play(Black, 3, 3)).unwrap();
+    assert!(book.moves(&other).is_empty());
+}
+
+#[test]
+fn passes_and_other_sizes_are_ignored() {
+    let mut book = Book::new(Size::square(9));
+    book.add(&game(), Pass(Black));
+    book.add(&Game::new(13, 6.5, KgsChinese), Play(Black, 4, 4));
+    assert_eq!(0, book.len());
+}
+
+#[test]
+fn survives_a_round_trip_through_bytes() {
+    let mut book = Book::new(Size::new(9, 7).unwrap());
+    let g = Game::with_super_ko(Size::new(9, 7).unwrap(), 6.5, KgsChinese, KgsChinese.super_ko());
+    book.add(&g, Play(Black, 3, 3));
+    book.add(&g.play(Play(Black, 3, 3)).unwrap(), Play(White, 5, 4));
+    assert_eq!(Ok(book.clone()), Book::from_bytes(&book.to_bytes()));
+}
+
+#[test]
+fn rejects_broken_files() {
+    assert_eq!(Err("not an opening book".to_string()), Book::from_bytes(b"nope"));
+    let mut bytes = Book::new(Size::square(9)).to_bytes();
+    bytes[7] = 1;
+    assert_eq!(Err("truncated opening book".to_string()), Book::from_bytes(&bytes));
+}
+
+#[test]
+fn add_game_skips_games_with_setup_stones() {
+    let mut book = Book::new(Size::square(9));
+    let parser = Parser::new(String::from("(;SZ[9]AB[cc];W[gg])"));
+    assert!(!book.add_game(&parser, 10));
+    let parser = Parser::new(String::from("(;SZ[9];B[cc];W[gg];B[cg])"));
+    assert!(book.add_game(&parser, 2));
+    assert_eq!(2, book.len());
+}
+
+#[test]
+fn pick_respects_the_maximum_depth_and_the_minimum_count() {
+    let mut config = Config::test_config();
+    config.book.min_count = 2;
+    config.book.randomness = 0.0;
+    let mut book = Book::new(Size::square(9));
+    book.add(&game(), Play(Black, 3, 3));
+    let mut rng = weak_rng();
+    assert_eq!(None, book.pick(&game(), &config.book, &mut rng));
+    book.add(&game(), Play(Black, 3, 3));
+    assert_eq!(Some(Play(Black, 3, 3)), book.pick(&game(), &config.book, &mut rng));
+    config.book.max_depth = 0;
+    assert_eq!(None, book.pick(&game(), &config.book, &mut rng));
+}
+
+#[test]
+fn pick_without_randomness_plays_the_most_common_move() {
+    let mut config = Config::test_config();
+    config.book.min_count = 1;
+    config.book.randomness = 0.0;
+    let mut book = Book::new(Size::square(9));
+    book.add(&game(), Play(Black, 3, 3));
+    book.add(&game(), Play(Black, 5, 5));
+    book.add(&game(), Play(Black, 5, 5));
+    let mut rng = weak_rng();
+    assert_eq!(Some(Play(Black, 5, 5)), book.pick(&game(), &config.book, &mut rng));
+}
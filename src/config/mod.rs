@@ -22,54 +22,226 @@
 
 #![deny(missing_docs)]
 
+use board::Size;
+use komi::KomiMode;
+use logger::Logger;
 use ruleset::Ruleset;
+use ruleset::SuperKo;
+use tree_dump::DumpFormat;
+use tree_dump::TreeDump;
 
 use num_cpus;
+use std::cmp;
+use std::fmt;
 use std::fs::File;
 use std::io::prelude::*;
 use std::io::stderr;
-use std::process::exit;
+use std::str::FromStr;
+use std::sync::Arc;
 use toml;
 use toml::value::Table;
 use toml::Value;
 
-trait FromToml {
+mod test;
+
+// The sections that are layered over the rest of the configuration
+// depending on the game.
+const OVERLAYS: [&'static str; 2] = ["rules", "size"];
+
+// These are only used when the engine starts up.
+const STARTUP_ONLY: [&'static str; 3] = ["book.file", "patterns.large", "patterns.small"];
+
+/// A single problem found while loading the configuration, e.g. a
+/// value of the wrong type or outside of its allowed range.
+#[derive(Debug, PartialEq)]
+pub struct ConfigProblem {
+    /// The TOML path of the offending value (e.g. `tree.rave_equiv`).
+    pub path: String,
+    /// What is wrong with the value.
+    pub message: String,
+}
+
+impl fmt::Display for ConfigProblem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.path.is_empty() {
+            write!(f, "{}", self.message)
+        } else {
+            write!(f, "{}: {}", self.path, self.message)
+        }
+    }
+}
+
+/// All problems found while loading the configuration.
+#[derive(Debug, PartialEq)]
+pub struct ConfigError {
+    /// The problems in the order they were found.
+    pub problems: Vec<ConfigProblem>,
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let lines: Vec<String> = self.problems.iter().map(|p| p.to_string()).collect();
+        write!(f, "{}", lines.join("\n"))
+    }
+}
+
+// The values a numeric field accepts.
+#[derive(Clone, Copy)]
+enum Bounds {
+    Any,
+    AtLeast(f64),
+    Between(f64, f64),
+    Positive,
+}
+
+impl Bounds {
+
+    fn check(&self, value: f64) -> Option<String> {
+        match *self {
+            Bounds::AtLeast(min) if value < min => Some(format!("{} is less than {}", value, min)),
+            Bounds::Between(min, max) if value < min || value > max => Some(format!("{} is not between {} and {}", value, min, max)),
+            Bounds::Positive if value <= 0.0 => Some(format!("{} is not greater than 0", value)),
+            _ => None
+        }
+    }
+
+}
 
-    fn as_float(table: &Table, field: &'static str) -> f32 {
-        let value = &table[field];
-        match value.type_str() {
-            "integer" => value.as_integer().unwrap() as f32,
-            "float" => value.as_float().unwrap() as f32,
-            _ => Self::fail(field, value, "float")
+// Reads the fields of one section of the configuration. Every field
+// is declared with its type and the values it accepts. Problems are
+// collected instead of aborting so that they can all be reported at
+// once.
+struct Fields {
+    known: Vec<&'static str>,
+    name: Option<&'static str>,
+    problems: Vec<ConfigProblem>,
+    table: Table,
+    user_keys: Vec<String>,
+}
+
+impl Fields {
+
+    fn float(&mut self, field: &'static str, bounds: Bounds) -> f32 {
+        let value = match self.value(field) {
+            Some(Value::Integer(i)) => i as f64,
+            Some(Value::Float(f)) => f,
+            Some(other) => { self.wrong_type(field, &other, "float"); return 0.0 },
+            None => return 0.0
+        };
+        self.check(field, value, bounds);
+        value as f32
+    }
+
+    fn integer(&mut self, field: &'static str, bounds: Bounds) -> usize {
+        let value = match self.value(field) {
+            Some(Value::Integer(i)) => i,
+            Some(other) => { self.wrong_type(field, &other, "integer"); return 0 },
+            None => return 0
+        };
+        if value < 0 {
+            self.problem(field, format!("{} is negative", value));
+            return 0;
+        }
+        self.check(field, value as f64, bounds);
+        value as usize
+    }
+
+    fn string(&mut self, field: &'static str) -> String {
+        match self.value(field) {
+            Some(Value::String(s)) => s,
+            Some(other) => { self.wrong_type(field, &other, "string"); String::new() },
+            None => String::new()
+        }
+    }
+
+    fn parse<T: FromStr<Err=String>>(&mut self, field: &'static str, fallback: T) -> T {
+        let s = self.string(field);
+        match s.parse() {
+            Ok(value) => value,
+            Err(e) => { self.problem(field, e); fallback }
+        }
+    }
+
+    // A whole section of the configuration (only used for the top
+    // level).
+    fn section(&mut self, name: &'static str) -> Value {
+        self.known.push(name);
+        self.table[name].clone()
+    }
+
+    // Also reports the keys in the configuration file that don't
+    // belong to any field (e.g. typos).
+    fn finish(mut self) -> Vec<ConfigProblem> {
+        for key in self.user_keys.clone() {
+            if !self.known.iter().any(|known| *known == key) {
+                self.problem(&key, "unknown key".to_string());
+            }
         }
+        self.problems
     }
 
-    fn as_integer(table: &Table, field: &'static str) -> usize {
-        let value = &table[field];
-        match value.type_str() {
-            "integer" => value.as_integer().unwrap() as usize,
-            "float" => value.as_float().unwrap() as usize,
-            _ => Self::fail(field, value, "integer")
+    fn value(&mut self, field: &'static str) -> Option<Value> {
+        self.known.push(field);
+        match self.table.get(field) {
+            Some(value) => Some(value.clone()),
+            None => {
+                self.problem(field, "missing value".to_string());
+                None
+            }
         }
     }
 
-    fn as_bool(table: &Table, field: &'static str) -> bool {
-        let value = &table[field];
-        match value.as_bool() {
-            Some(v) => v,
-            None => Self::fail(field, value, "boolean")
+    fn check(&mut self, field: &'static str, value: f64, bounds: Bounds) {
+        if let Some(message) = bounds.check(value) {
+            self.problem(field, message);
         }
     }
 
-    fn fail(field: &'static str, value: &Value, expected: &'static str) -> ! {
-        let long_name = match Self::name() {
+    fn wrong_type(&mut self, field: &'static str, value: &Value, expected: &'static str) {
+        self.problem(field, format!("expected {} but found {}", expected, value.type_str()));
+    }
+
+    fn problem(&mut self, field: &str, message: String) {
+        let path = Self::path(self.name, field);
+        self.problems.push(ConfigProblem { path: path, message: message });
+    }
+
+    fn path(name: Option<&'static str>, field: &str) -> String {
+        match name {
             Some(name) => format!("{}.{}", name, field),
-            None => format!("{}", field)
-        };
-        println!("Expected {} for {:?} but found {}", expected, long_name, value.type_str());
-        exit(1)
+            None => field.to_string()
+        }
     }
 
+}
+
+trait FromToml {
+
+    // Merges the section from the configuration file into the
+    // defaults.
+    fn fields(value: Value, default: Value) -> Fields {
+        let mut problems = vec!();
+        let mut table = default.as_table().unwrap().clone();
+        let mut user_keys = vec!();
+        match value {
+            Value::Table(opts) => {
+                user_keys = opts.keys().cloned().collect();
+                table.extend(opts);
+            },
+            other => {
+                let path = Self::name().unwrap_or("").to_string();
+                let message = format!("expected table but found {}", other.type_str());
+                problems.push(ConfigProblem { path: path, message: message });
+            }
+        }
+        Fields {
+            known: vec!(),
+            name: Self::name(),
+            problems: problems,
+            table: table,
+            user_keys: user_keys,
+        }
+    }
 
     fn name() -> Option<&'static str>;
 
@@ -84,6 +256,10 @@ pub struct TreeConfig {
     /// algorithm. There's no clear way to set this value. It's best
     /// to use parameter optimization to find the best value.
     pub rave_equiv: f32,
+    /// Win ratio of the best move above which the move with the
+    /// highest expected score is played instead. Set it to 1.0 to
+    /// always play the move with the most playouts.
+    pub score_maximization: f32,
     /// A float between 0.0 and 1.0 that is the part of a win recorded
     /// in the tree nodes to signify the score of the playout.
     pub score_weight: f32,
@@ -91,17 +267,16 @@ pub struct TreeConfig {
 
 impl TreeConfig {
 
-    fn new(value: Value, default: Value) -> TreeConfig {
-        let opts = value.as_table().unwrap().clone();
-        let default_table = default.as_table().unwrap().clone();
-        let mut table = Table::new();
-        table.extend(default_table);
-        table.extend(opts);
-        TreeConfig {
-            expand_after: Self::as_integer(&table, "expand_after"),
-            rave_equiv: Self::as_float(&table, "rave_equiv"),
-            score_weight: Self::as_float(&table, "score_weight"),
-        }
+    fn new(value: Value, default: Value, problems: &mut Vec<ConfigProblem>) -> TreeConfig {
+        let mut fields = Self::fields(value, default);
+        let config = TreeConfig {
+            expand_after: fields.integer("expand_after", Bounds::Any),
+            rave_equiv: fields.float("rave_equiv", Bounds::Positive),
+            score_maximization: fields.float("score_maximization", Bounds::Between(0.0, 1.0)),
+            score_weight: fields.float("score_weight", Bounds::Between(0.0, 1.0)),
+        };
+        problems.extend(fields.finish());
+        config
     }
 
 }
@@ -110,6 +285,97 @@ impl FromToml for TreeConfig {
     fn name() -> Option<&'static str> { Some("tree") }
 }
 
+/// Holds the settings for the opening book (see the `book` module).
+#[derive(Debug, PartialEq)]
+pub struct BookConfig {
+    /// Path to the opening book built with `--build-book`. No book is
+    /// used if it's empty.
+    pub file: String,
+    /// The book is only consulted for the first `max_depth` moves of
+    /// a game. It's also the number of moves stored per game when
+    /// building a book.
+    pub max_depth: usize,
+    /// Book moves that were seen fewer times than this are ignored.
+    pub min_count: usize,
+    /// The number of playouts run for every position when building a
+    /// book from searches.
+    pub playouts: usize,
+    /// How random the choice between the book moves is. With 0.0 the
+    /// most common move is always played, with 1.0 a move is picked
+    /// with a probability proportional to how often it was seen.
+    pub randomness: f32,
+}
+
+impl BookConfig {
+
+    fn new(value: Value, default: Value, problems: &mut Vec<ConfigProblem>) -> BookConfig {
+        let mut fields = Self::fields(value, default);
+        let config = BookConfig {
+            file: fields.string("file"),
+            max_depth: fields.integer("max_depth", Bounds::Any),
+            min_count: fields.integer("min_count", Bounds::Any),
+            playouts: fields.integer("playouts", Bounds::Positive),
+            randomness: fields.float("randomness", Bounds::AtLeast(0.0)),
+        };
+        problems.extend(fields.finish());
+        config
+    }
+
+}
+
+impl FromToml for BookConfig {
+    fn name() -> Option<&'static str> { Some("book") }
+}
+
+/// Holds the settings for dynamic komi, i.e. the komi used to score
+/// the playouts in games where one side is far ahead (e.g. handicap
+/// games). The real komi is always used for the final score.
+#[derive(Debug, PartialEq)]
+pub struct DynamicKomiConfig {
+    /// In adaptive mode the engine gives away `step` more points of
+    /// komi after a search with a win rate above this value.
+    pub green_zone: f32,
+    /// In linear mode the komi each handicap stone is worth at the
+    /// start of the game.
+    pub handicap_value: f32,
+    /// Either `"off"`, `"linear"` (compensate for the handicap
+    /// stones), or `"adaptive"` (change the komi depending on the
+    /// win rate).
+    pub mode: KomiMode,
+    /// In linear mode the number of moves after which the real komi
+    /// is used again.
+    pub moves: usize,
+    /// In adaptive mode the engine takes back `step` points of the
+    /// komi it gave away after a search with a win rate below this
+    /// value.
+    pub red_zone: f32,
+    /// In adaptive mode the amount the komi changes by after each
+    /// move.
+    pub step: f32,
+}
+
+impl DynamicKomiConfig {
+
+    fn new(value: Value, default: Value, problems: &mut Vec<ConfigProblem>) -> DynamicKomiConfig {
+        let mut fields = Self::fields(value, default);
+        let config = DynamicKomiConfig {
+            green_zone: fields.float("green_zone", Bounds::Between(0.0, 1.0)),
+            handicap_value: fields.float("handicap_value", Bounds::AtLeast(0.0)),
+            mode: fields.parse("mode", KomiMode::Off),
+            moves: fields.integer("moves", Bounds::Any),
+            red_zone: fields.float("red_zone", Bounds::Between(0.0, 1.0)),
+            step: fields.float("step", Bounds::AtLeast(0.0)),
+        };
+        problems.extend(fields.finish());
+        config
+    }
+
+}
+
+impl FromToml for DynamicKomiConfig {
+    fn name() -> Option<&'static str> { Some("dynamic_komi") }
+}
+
 /// Holds all settings related to initializing the leaves of the
 /// search tree with prior values for plays and wins.
 #[derive(Debug, PartialEq)]
@@ -129,18 +395,31 @@ pub struct PriorsConfig {
     /// Same as `capture_many` but for the case where a move captures
     /// a single stone.
     pub capture_one: usize,
+    /// The prior for a move that connects two of our chains that the
+    /// tactical reader found could otherwise be cut, or that cuts two
+    /// opponent chains. It is an even prior.
+    pub connection: usize,
     /// The prior to assign a move that plays close to the border.
     /// It's a negative prior (i.e. only prior plays are increased)
     /// when playing on the 1st and 2nd line and an even prior for
     /// moves on the third line. This is only applied if the area
     /// around the move of a Manhattan distance of three is empty.
     pub empty: usize,
+    /// The prior to assign when one of the large patterns matches.
+    /// The weight of the largest matching pattern (read from the
+    /// pattern dictionary) is multiplied by this value. It is an
+    /// even prior.
+    pub large_patterns: usize,
     /// The number of prior plays to start with. This is useful to
     /// simplify the calculations as we can avoid 0 values.
     pub neutral_plays: usize,
     /// The number of prior wins to start with. This is normally 0.5
     /// of `neutral_plays` so that we start of with a win rate of 50%.
     pub neutral_wins: usize,
+    /// The prior for a move that wins a capturing race (as read out
+    /// by the tactical reader) that we would lose if the opponent
+    /// moved first. It is an even prior.
+    pub semeai: usize,
     /// The prior to assign when one of the 3x3 pattern matches. This
     /// is an even prior.
     pub small_patterns: usize,
@@ -148,26 +427,37 @@ pub struct PriorsConfig {
     /// in self atari. This is a negative prior (i.e. only prior plays
     /// are increased).
     pub self_atari: usize,
+    /// The prior for a move that the tactical reader found captures
+    /// an opponent chain with two or more liberties. It is an even
+    /// prior.
+    pub tactics_capture: usize,
+    /// The prior for a move that the tactical reader found saves one
+    /// of our chains that could otherwise be captured. It is an even
+    /// prior.
+    pub tactics_save: usize,
 }
 
 impl PriorsConfig {
 
-    fn new(value: Value, default: Value) -> PriorsConfig {
-        let opts = value.as_table().unwrap().clone();
-        let default_table = default.as_table().unwrap().clone();
-        let mut table = Table::new();
-        table.extend(default_table);
-        table.extend(opts);
-        PriorsConfig {
-            best_move_factor: Self::as_float(&table, "best_move_factor"),
-            capture_many: Self::as_integer(&table, "capture_many"),
-            capture_one: Self::as_integer(&table, "capture_one"),
-            empty: Self::as_integer(&table, "empty"),
-            neutral_plays: Self::as_integer(&table, "neutral_plays"),
-            neutral_wins: Self::as_integer(&table, "neutral_wins"),
-            self_atari: Self::as_integer(&table, "self_atari"),
-            small_patterns: Self::as_integer(&table, "small_patterns"),
-        }
+    fn new(value: Value, default: Value, problems: &mut Vec<ConfigProblem>) -> PriorsConfig {
+        let mut fields = Self::fields(value, default);
+        let config = PriorsConfig {
+            best_move_factor: fields.float("best_move_factor", Bounds::AtLeast(0.0)),
+            capture_many: fields.integer("capture_many", Bounds::Any),
+            capture_one: fields.integer("capture_one", Bounds::Any),
+            connection: fields.integer("connection", Bounds::Any),
+            empty: fields.integer("empty", Bounds::Any),
+            large_patterns: fields.integer("large_patterns", Bounds::Any),
+            neutral_plays: fields.integer("neutral_plays", Bounds::Any),
+            neutral_wins: fields.integer("neutral_wins", Bounds::Any),
+            semeai: fields.integer("semeai", Bounds::Any),
+            self_atari: fields.integer("self_atari", Bounds::Any),
+            small_patterns: fields.integer("small_patterns", Bounds::Any),
+            tactics_capture: fields.integer("tactics_capture", Bounds::Any),
+            tactics_save: fields.integer("tactics_save", Bounds::Any),
+        };
+        problems.extend(fields.finish());
+        config
     }
 
     /// Returns false if the empty prior is zero, which allows for
@@ -183,12 +473,117 @@ impl PriorsConfig {
     pub fn use_small_patterns(&self) -> bool {
         self.small_patterns > 0
     }
+
+    /// Returns false if the large patterns prior is zero, which
+    /// allows for turning of the code that matches the large
+    /// patterns altogether.
+    pub fn use_large_patterns(&self) -> bool {
+        self.large_patterns > 0
+    }
+
+    /// Returns false if all the priors that depend on the tactical
+    /// reader are zero, which allows for turning off the reader
+    /// altogether.
+    pub fn use_tactics(&self) -> bool {
+        self.tactics_capture > 0 || self.tactics_save > 0 || self.semeai > 0 || self.connection > 0
+    }
 }
 
 impl FromToml for PriorsConfig {
     fn name() -> Option<&'static str> { Some("priors") }
 }
 
+/// Holds the settings related to the pattern databases.
+#[derive(Debug, PartialEq)]
+pub struct PatternsConfig {
+    /// Path to the dictionary of large (diamond shaped) patterns. No
+    /// large patterns are used if it's empty.
+    pub large: String,
+    /// Path to a file with 3x3 patterns. The built-in patterns are
+    /// used if it's empty. Run the program with `--dump-patterns` to
+    /// see the built-in patterns and the file format.
+    pub small: String,
+}
+
+impl PatternsConfig {
+
+    fn new(value: Value, default: Value, problems: &mut Vec<ConfigProblem>) -> PatternsConfig {
+        let mut fields = Self::fields(value, default);
+        let config = PatternsConfig {
+            large: fields.string("large"),
+            small: fields.string("small"),
+        };
+        problems.extend(fields.finish());
+        config
+    }
+
+}
+
+impl FromToml for PatternsConfig {
+    fn name() -> Option<&'static str> { Some("patterns") }
+}
+
+/// Holds the settings for the tactical reader (see the `tactics`
+/// module) that is used for some of the priors.
+#[derive(Debug, PartialEq)]
+pub struct TacticsConfig {
+    /// The maximum number of moves the reader looks ahead. Moves that
+    /// put a chain in atari aren't counted so that ladders are read
+    /// out completely.
+    pub depth: usize,
+    /// Chains with more liberties than this are considered safe and
+    /// aren't read out.
+    pub liberties: usize,
+    /// The maximum number of moves a single search may play before
+    /// it gives up.
+    pub nodes: usize,
+}
+
+impl TacticsConfig {
+
+    fn new(value: Value, default: Value, problems: &mut Vec<ConfigProblem>) -> TacticsConfig {
+        let mut fields = Self::fields(value, default);
+        let config = TacticsConfig {
+            depth: fields.integer("depth", Bounds::Any),
+            liberties: fields.integer("liberties", Bounds::Any),
+            nodes: fields.integer("nodes", Bounds::Any),
+        };
+        problems.extend(fields.finish());
+        config
+    }
+
+}
+
+impl FromToml for TacticsConfig {
+    fn name() -> Option<&'static str> { Some("tactics") }
+}
+
+/// Holds the settings for the life-and-death solver (see the
+/// `tsumego` module).
+#[derive(Debug, PartialEq)]
+pub struct TsumegoConfig {
+    /// The maximum number of nodes the solver creates before it gives
+    /// up on a problem.
+    pub nodes: usize,
+}
+
+impl TsumegoConfig {
+
+    fn new(value: Value, default: Value, problems: &mut Vec<ConfigProblem>) -> TsumegoConfig {
+        let mut fields = Self::fields(value, default);
+        let config = TsumegoConfig {
+            nodes: fields.integer("nodes", Bounds::Positive),
+        };
+        problems.extend(fields.finish());
+        config
+    }
+
+}
+
+impl FromToml for TsumegoConfig {
+    fn name() -> Option<&'static str> { Some("tsumego") }
+}
+
 /// Holds all settings related to time control.
 #[derive(Debug, PartialEq)]
 pub struct TimeControlConfig {
@@ -213,18 +608,16 @@ pub struct TimeControlConfig {
 
 impl TimeControlConfig {
 
-    fn new(value: Value, default: Value) -> TimeControlConfig {
-        let opts = value.as_table().unwrap().clone();
-        let default_table = default.as_table().unwrap().clone();
-        let mut table = Table::new();
-        table.extend(default_table);
-        table.extend(opts);
-        TimeControlConfig {
-            c: Self::as_float(&table, "c"),
-            fastplay_budget: Self::as_float(&table, "fastplay_budget"),
-            fastplay_threshold: Self::as_float(&table, "fastplay_threshold"),
-            min_stones: Self::as_integer(&table, "min_stones"),
-        }
+    fn new(value: Value, default: Value, problems: &mut Vec<ConfigProblem>) -> TimeControlConfig {
+        let mut fields = Self::fields(value, default);
+        let config = TimeControlConfig {
+            c: fields.float("c", Bounds::Positive),
+            fastplay_budget: fields.float("fastplay_budget", Bounds::Between(0.0, 1.0)),
+            fastplay_threshold: fields.float("fastplay_threshold", Bounds::Between(0.0, 1.0)),
+            min_stones: fields.integer("min_stones", Bounds::Any),
+        };
+        problems.extend(fields.finish());
+        config
     }
 }
 
@@ -256,19 +649,17 @@ pub struct PlayoutConfig {
 
 impl PlayoutConfig {
 
-    fn new(value: Value, default: Value) -> PlayoutConfig {
-        let opts = value.as_table().unwrap().clone();
-        let default_table = default.as_table().unwrap().clone();
-        let mut table = Table::new();
-        table.extend(default_table);
-        table.extend(opts);
-        PlayoutConfig {
-            atari_check: Self::as_float(&table, "atari_check"),
-            ladder_check: Self::as_float(&table, "ladder_check"),
-            last_moves_for_heuristics: Self::as_integer(&table, "last_moves_for_heuristics"),
-            pattern_probability: Self::as_float(&table, "pattern_probability"),
-            play_in_middle_of_eye: Self::as_float(&table, "play_in_middle_of_eye"),
-        }
+    fn new(value: Value, default: Value, problems: &mut Vec<ConfigProblem>) -> PlayoutConfig {
+        let mut fields = Self::fields(value, default);
+        let config = PlayoutConfig {
+            atari_check: fields.float("atari_check", Bounds::Between(0.0, 1.0)),
+            ladder_check: fields.float("ladder_check", Bounds::Between(0.0, 1.0)),
+            last_moves_for_heuristics: fields.integer("last_moves_for_heuristics", Bounds::Any),
+            pattern_probability: fields.float("pattern_probability", Bounds::Between(0.0, 1.0)),
+            play_in_middle_of_eye: fields.float("play_in_middle_of_eye", Bounds::Between(0.0, 1.0)),
+        };
+        problems.extend(fields.finish());
+        config
     }
 
 }
@@ -296,18 +687,16 @@ pub struct ScoringConfig {
 
 impl ScoringConfig {
 
-    fn new(value: Value, default: Value) -> ScoringConfig {
-        let opts = value.as_table().unwrap().clone();
-        let default_table = default.as_table().unwrap().clone();
-        let mut table = Table::new();
-        table.extend(default_table);
-        table.extend(opts);
-        ScoringConfig {
-            ownership_prior: Self::as_integer(&table, "ownership_prior"),
-            ownership_cutoff: Self::as_float(&table, "ownership_cutoff"),
-            playouts: Self::as_integer(&table, "playouts"),
-            resignation_percentage: Self::as_float(&table, "resignation_percentage"),
-        }
+    fn new(value: Value, default: Value, problems: &mut Vec<ConfigProblem>) -> ScoringConfig {
+        let mut fields = Self::fields(value, default);
+        let config = ScoringConfig {
+            ownership_prior: fields.integer("ownership_prior", Bounds::Any),
+            ownership_cutoff: fields.float("ownership_cutoff", Bounds::Between(0.0, 1.0)),
+            playouts: fields.integer("playouts", Bounds::Positive),
+            resignation_percentage: fields.float("resignation_percentage", Bounds::Between(0.0, 1.0)),
+        };
+        problems.extend(fields.finish());
+        config
     }
 
 }
@@ -322,14 +711,32 @@ impl FromToml for ScoringConfig {
 /// be set in a configuration file in TOML format.
 #[derive(Debug, PartialEq)]
 pub struct Config {
+    // The configuration before the layers for the board size and the
+    // ruleset were applied.
+    base: Table,
+    /// Holds a configuration object that contains everything related
+    /// to the opening book.
+    pub book: BookConfig,
+    /// The directory the search tree is written to before each
+    /// generated move (see `tree_dump`), or `None` if it isn't. It
+    /// can be set on the command line with `--dump-tree`.
+    pub dump_dir: Option<String>,
+    /// Holds a configuration object that contains everything related
+    /// to dynamic komi.
+    pub dynamic_komi: DynamicKomiConfig,
     /// If `true` output GoGui live graphics commands on stderr so
     /// that you can see what the engine is "thinking" when playing or
     /// observing a game via GoGui
     pub gfx: bool,
-    /// If `true` the various information (e.g. the number of
-    /// simulations played) is printed to stderr while the engine is
-    /// running.
-    pub log: bool,
+    /// Where the various information (e.g. the number of
+    /// simulations played) is logged to while the engine is
+    /// running. Use the `log!`, `info!`, etc. macros to write to it.
+    pub logger: Arc<Logger>,
+    // The `[size.*]` and `[rules.*]` sections of the config file.
+    overlays: Table,
+    /// Holds a configuration object that contains everything related
+    /// to the pattern databases.
+    pub patterns: PatternsConfig,
     /// Holds a configuration object that contains everything related
     /// to the playout policy.
     pub playout: PlayoutConfig,
@@ -341,6 +748,15 @@ pub struct Config {
     /// Holds a configuration object that contains everything related
     /// to estimating the score of a board
     pub scoring: ScoringConfig,
+    /// The superko rule. It defaults to the one of the ruleset and
+    /// can be changed on the command line with `--superko`.
+    pub super_ko: SuperKo,
+    /// Holds a configuration object that contains everything related
+    /// to the tactical reader.
+    pub tactics: TacticsConfig,
+    // The defaults merged with the values from the config file. It's
+    // used to list and change the parameters at runtime.
+    table: Table,
     /// The number of threads to use for the workers. The default is
     /// one less that the number of cores of the machine you're running
     /// on.
@@ -353,21 +769,30 @@ pub struct Config {
     /// to the tree search (when to expand the leaves, RAVE
     /// configuration, etc.)
     pub tree: TreeConfig,
+    /// How much of the search tree is written to `dump_dir` and by
+    /// `imrscl-dump_tree` (unless given there) and in which format.
+    pub tree_dump: TreeDump,
+    /// Holds a configuration object that contains everything related
+    /// to the life-and-death solver.
+    pub tsumego: TsumegoConfig,
 }
 
 impl Config {
 
     #[test]
     pub fn test_config() -> Config {
-        let mut config = Self::default(false, false, Ruleset::KgsChinese, Some(1));
-        config.scoring.playouts = 100;
-        config
+        Self::default(false, false, Ruleset::KgsChinese, Some(1))
+            .with_param("scoring.playouts", "100")
+            .unwrap()
     }
 
     /// Uses the TOML returned by `Config::toml()` and returns a
     /// `Config` object that encodes this data.
     pub fn default(log: bool, gfx: bool, ruleset: Ruleset, threads: Option<usize>) -> Config {
-        Self::new(String::from(""), Self::toml(), log, gfx, ruleset, threads)
+        match Self::from_toml(String::from(""), log, gfx, ruleset, threads) {
+            Ok(config) => config,
+            Err(e) => panic!("Invalid default configuration:\n{}", e)
+        }
     }
 
     /// Returns a string representation of the default configuration
@@ -380,63 +805,270 @@ impl Config {
     /// encoded file. It reads that file and generates a `Config`
     /// object from the data. The file doesn't need to contain all
     /// possible fields of `Config` or the various structs it
-    /// contains. What's missing is taken from `Config::toml()`.
-    pub fn from_file(filename: String, log: bool, gfx: bool, ruleset: Ruleset, threads: Option<usize>) -> Config {
-        let mut file = File::open(filename).unwrap();
+    /// contains. What's missing is taken from `Config::toml()`. All
+    /// problems with the file (unknown keys, values of the wrong type
+    /// or out of range) are returned at once.
+    pub fn from_file(filename: String, log: bool, gfx: bool, ruleset: Ruleset, threads: Option<usize>) -> Result<Config, ConfigError> {
         let mut contents = String::new();
-        file.read_to_string(&mut contents).unwrap();
-        Self::new(contents, Self::toml(), log, gfx, ruleset, threads)
-    }
-
-    fn new(toml_str: String, default_toml_str: String, log: bool, gfx: bool, ruleset: Ruleset, threads: Option<usize>) -> Config {
-        let opts : Table = toml::from_str(&toml_str).unwrap();
-        let default_table : Table = toml::from_str(&default_toml_str).unwrap();
-        let mut table = Table::new();
-        table.extend(default_table.clone());
-        let default_threads : Table = toml::from_str(
-            &format!("threads = {}", num_cpus::get()-1)
-        ).unwrap();
-        table.extend(default_threads);
-        // The threads value set in the config file overrides the default threads value.
-        table.extend(opts.clone());
+        let read = File::open(&filename).and_then(|mut file| file.read_to_string(&mut contents));
+        if let Err(e) = read {
+            let problem = ConfigProblem { path: String::new(), message: format!("can't read {}: {}", filename, e) };
+            return Err(ConfigError { problems: vec!(problem) });
+        }
+        Self::from_toml(contents, log, gfx, ruleset, threads)
+    }
+
+    /// Same as `Config::from_file()` but takes the contents of the
+    /// file as a string.
+    pub fn from_toml(toml_str: String, log: bool, gfx: bool, ruleset: Ruleset, threads: Option<usize>) -> Result<Config, ConfigError> {
+        Self::new(toml_str, Self::toml(), log, gfx, ruleset, threads)
+    }
+
+    fn new(toml_str: String, default_toml_str: String, log: bool, gfx: bool, ruleset: Ruleset, threads: Option<usize>) -> Result<Config, ConfigError> {
+        let mut opts: Table = match toml::from_str(&toml_str) {
+            Ok(opts) => opts,
+            Err(e) => {
+                let problem = ConfigProblem { path: String::new(), message: format!("invalid TOML: {}", e) };
+                return Err(ConfigError { problems: vec!(problem) });
+            }
+        };
+        let default_table: Table = toml::from_str(&default_toml_str).unwrap();
+        Self::from_tables(opts, default_table, log, gfx, ruleset, threads)
+    }
+
+    fn from_tables(mut opts: Table, mut default_table: Table, log: bool, gfx: bool, ruleset: Ruleset, threads: Option<usize>) -> Result<Config, ConfigError> {
+        let default_threads = cmp::max(1, num_cpus::get() - 1);
+        default_table.insert("threads".to_string(), Value::Integer(default_threads as i64));
         // The threads command line switch overrides the threads value set in the config file.
-        match threads {
-            Some(ts) => {
-                let cmd_threads : Table = toml::from_str(
-                    &format!("threads = {}", ts)
-                ).unwrap();
-                table.extend(cmd_threads);
-            },
-            None => {}
+        if let Some(ts) = threads {
+            opts.insert("threads".to_string(), Value::Integer(ts as i64));
+        }
+        let mut overlays = Table::new();
+        for kind in OVERLAYS.iter() {
+            if let Some(value) = opts.remove(*kind) {
+                overlays.insert(kind.to_string(), value);
+            }
+        }
+        let mut problems = vec!();
+        // The `-l` switch logs at the info level to stderr. The logger
+        // can be replaced with a more specific one afterwards.
+        let logger = if log {
+            Logger::stderr("info").unwrap()
+        } else {
+            Logger::off()
         };
-        Config {
+        let mut config = Self::build(opts, &default_table, Arc::new(logger), gfx, ruleset, &mut problems);
+        Self::check_overlays(&overlays, &default_table, &mut problems);
+        config.overlays = overlays;
+        if problems.is_empty() {
+            Ok(config)
+        } else {
+            Err(ConfigError { problems: problems })
+        }
+    }
+
+    fn build(opts: Table, default_table: &Table, logger: Arc<Logger>, gfx: bool, ruleset: Ruleset, problems: &mut Vec<ConfigProblem>) -> Config {
+        let table = Self::merge(default_table, &opts);
+        let mut fields = Self::fields(Value::Table(opts), Value::Table(default_table.clone()));
+        let config = Config {
+            base: table.clone(),
+            book: BookConfig::new(fields.section("book"), default_table["book"].clone(), problems),
+            dump_dir: None,
+            dynamic_komi: DynamicKomiConfig::new(fields.section("dynamic_komi"), default_table["dynamic_komi"].clone(), problems),
             gfx: gfx,
-            log: log,
-            playout: PlayoutConfig::new(table["playout"].clone(), default_table["playout"].clone()),
-            priors: PriorsConfig::new(table["priors"].clone(), default_table["priors"].clone()),
+            logger: logger,
+            overlays: Table::new(),
+            patterns: PatternsConfig::new(fields.section("patterns"), default_table["patterns"].clone(), problems),
+            playout: PlayoutConfig::new(fields.section("playout"), default_table["playout"].clone(), problems),
+            priors: PriorsConfig::new(fields.section("priors"), default_table["priors"].clone(), problems),
             ruleset: ruleset,
-            scoring: ScoringConfig::new(table["scoring"].clone(), default_table["scoring"].clone()),
-            threads: Self::as_integer(&table, "threads"),
-            time_control: TimeControlConfig::new(table["time_control"].clone(), default_table["time_control"].clone()),
-            tree: TreeConfig::new(table["tree"].clone(), default_table["tree"].clone()),
+            scoring: ScoringConfig::new(fields.section("scoring"), default_table["scoring"].clone(), problems),
+            super_ko: ruleset.super_ko(),
+            table: table,
+            tactics: TacticsConfig::new(fields.section("tactics"), default_table["tactics"].clone(), problems),
+            threads: fields.integer("threads", Bounds::Positive),
+            time_control: TimeControlConfig::new(fields.section("time_control"), default_table["time_control"].clone(), problems),
+            tree: TreeConfig::new(fields.section("tree"), default_table["tree"].clone(), problems),
+            tree_dump: TreeDump::new(DumpFormat::Json, 3, 10),
+            tsumego: TsumegoConfig::new(fields.section("tsumego"), default_table["tsumego"].clone(), problems),
+        };
+        problems.extend(fields.finish());
+        config
+    }
+
+    // Every layer (e.g. `[size.9]`) has to be valid on its own.
+    fn check_overlays(overlays: &Table, default_table: &Table, problems: &mut Vec<ConfigProblem>) {
+        for (kind, layers) in overlays.iter() {
+            let layers = match *layers {
+                Value::Table(ref layers) => layers,
+                ref other => {
+                    let message = format!("expected table but found {}", other.type_str());
+                    problems.push(ConfigProblem { path: kind.clone(), message: message });
+                    continue;
+                }
+            };
+            for (name, layer) in layers.iter() {
+                let path = format!("{}.{}", kind, name);
+                let valid_name = if kind == "size" {
+                    name.parse::<Size>().map(|_| ())
+                } else {
+                    name.parse::<Ruleset>().map(|_| ())
+                };
+                if let Err(message) = valid_name {
+                    problems.push(ConfigProblem { path: path.clone(), message: message });
+                }
+                let mut layer_problems = vec!();
+                match *layer {
+                    Value::Table(ref layer) => {
+                        for (key, value) in layer.iter() {
+                            if value.as_table().is_none() {
+                                let message = "only sections can be overridden".to_string();
+                                layer_problems.push(ConfigProblem { path: key.clone(), message: message });
+                            }
+                        }
+                        if layer_problems.is_empty() {
+                            Self::build(layer.clone(), default_table, Arc::new(Logger::off()), false, Ruleset::KgsChinese, &mut layer_problems);
+                        }
+                    },
+                    ref other => {
+                        let message = format!("expected table but found {}", other.type_str());
+                        problems.push(ConfigProblem { path: path.clone(), message: message });
+                    }
+                }
+                for problem in layer_problems {
+                    let path = format!("{}.{}", path, problem.path);
+                    problems.push(ConfigProblem { path: path, message: problem.message });
+                }
+            }
         }
     }
 
-    /// If logging is turned on then the string passed will be printed to standard error with a
-    /// newline added to the end. Otherwise it's silently discarded.
-    pub fn log(&self, s: String) {
-        self.write(format!("{}\n", s));
+    fn default_table() -> Table {
+        toml::from_str(&Self::toml()).unwrap()
     }
 
-    /// If logging is turned on then the string will be printed to standard error (without adding a
-    /// newline). Otherwise it's silently discarded.
-    pub fn write(&self, s: String) {
-        if self.log {
-            match stderr().write(s.as_bytes()) {
-                Ok(_) => {},
-                Err(x) => panic!("Unable to write to stderr: {}", x)
+    /// The configuration for games on the given board size. The
+    /// sections for the ruleset (e.g. `[rules.chinese.scoring]`) and
+    /// then the ones for the board size (e.g. `[size.9.tree]` or
+    /// `[size.19x13.tree]`) are layered over the base configuration.
+    /// Returns `None` if that's the configuration already in effect.
+    pub fn for_size(&self, size: Size) -> Option<Config> {
+        let mut table = self.base.clone();
+        let layers = [("rules", self.ruleset.to_string()), ("size", size.to_string())];
+        for &(kind, ref name) in layers.iter() {
+            let layer = self.overlays.get(kind)
+                .and_then(|layers| layers.as_table())
+                .and_then(|layers| layers.get(name))
+                .and_then(|layer| layer.as_table());
+            if let Some(layer) = layer {
+                table = Self::merge(&table, layer);
+            }
+        }
+        if table == self.table {
+            return None;
+        }
+        match self.rebuild(table, self.base.clone()) {
+            Ok(config) => Some(config),
+            // The layers were already checked when the config was loaded.
+            Err(problems) => panic!("invalid config for size {}: {:?}", size, problems),
+        }
+    }
+
+    // Builds the configuration of the merged `table` (with `base` as
+    // the configuration before the layers) and carries over all the
+    // fields that don't come from the tables, e.g. the logger or the
+    // options given on the command line.
+    fn rebuild(&self, table: Table, base: Table) -> Result<Config, Vec<ConfigProblem>> {
+        let mut problems = vec!();
+        let config = Self::build(table, &Self::default_table(), self.logger.clone(), self.gfx, self.ruleset, &mut problems);
+        if !problems.is_empty() {
+            return Err(problems);
+        }
+        Ok(Config {
+            base: base,
+            dump_dir: self.dump_dir.clone(),
+            overlays: self.overlays.clone(),
+            super_ko: self.super_ko,
+            tree_dump: self.tree_dump,
+            ..config
+        })
+    }
+
+    /// The configuration in effect encoded as TOML.
+    pub fn to_toml(&self) -> String {
+        toml::to_string(&Value::Table(self.table.clone())).unwrap()
+    }
+
+    // The values of `opts` replace the ones in `default`, except for
+    // sections where only the values inside are replaced.
+    fn merge(default: &Table, opts: &Table) -> Table {
+        let mut table = default.clone();
+        for (key, value) in opts.iter() {
+            let merged = match (table.get(key), value) {
+                (Some(&Value::Table(ref d)), &Value::Table(ref o)) => Value::Table(Self::merge(d, o)),
+                _ => value.clone()
+            };
+            table.insert(key.clone(), merged);
+        }
+        table
+    }
+
+    /// The parameters that can be changed at runtime with
+    /// `Config::with_param()` and their current values, sorted by
+    /// name (e.g. `tree.rave_equiv`).
+    pub fn params(&self) -> Vec<(String, String)> {
+        let mut params = vec!();
+        for (section, value) in self.table.iter() {
+            if let Value::Table(ref table) = *value {
+                for (key, value) in table.iter() {
+                    let name = format!("{}.{}", section, key);
+                    if !STARTUP_ONLY.iter().any(|s| *s == name) {
+                        let value = match *value {
+                            Value::String(ref s) => s.clone(),
+                            ref other => other.to_string()
+                        };
+                        params.push((name, value));
+                    }
+                }
             }
         }
+        params
+    }
+
+    /// Returns a copy of the configuration where the parameter `name`
+    /// (one of `Config::params()`) is set to `value`. The new
+    /// configuration is validated just like a config file.
+    pub fn with_param(&self, name: &str, value: &str) -> Result<Config, String> {
+        if STARTUP_ONLY.iter().any(|s| *s == name) {
+            return Err(format!("{} can't be changed at runtime", name));
+        }
+        let mut table = self.table.clone();
+        try!(Self::set_param(&mut table, name, value));
+        // Also change the base, so that the value stays when the
+        // board size changes (unless it's overridden for the size).
+        let mut base = self.base.clone();
+        try!(Self::set_param(&mut base, name, value));
+        self.rebuild(table, base).map_err(|problems| ConfigError { problems: problems }.to_string())
+    }
+
+    fn set_param(table: &mut Table, name: &str, value: &str) -> Result<(), String> {
+        let unknown = format!("unknown parameter {}", name);
+        let mut parts = name.splitn(2, '.');
+        let (section, key) = match (parts.next(), parts.next()) {
+            (Some(section), Some(key)) => (section, key),
+            _ => return Err(unknown)
+        };
+        let current = match table.get_mut(section).and_then(|s| s.as_table_mut()).and_then(|t| t.get_mut(key)) {
+            Some(current) => current,
+            None => return Err(unknown)
+        };
+        let new_value = match *current {
+            Value::Integer(_) => Value::Integer(try!(value.parse().map_err(|_| format!("expected integer but found '{}'", value)))),
+            Value::Float(_) => Value::Float(try!(value.parse().map_err(|_| format!("expected float but found '{}'", value)))),
+            _ => Value::String(value.to_string())
+        };
+        *current = new_value;
+        Ok(())
     }
 
     /// If GoGui live graphics support is turned on then this will
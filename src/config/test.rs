@@ -0,0 +1,189 @@
+/************************************************************************
+ *                                                                      *
+ * Copyright 2016 Urban Hafner                                          *
+ *                                                                      *
+ * This file is part of Iomrascálaí.                                    *
+ *                                                                      *
+ * Iomrascálaí is free software: you can redistribute it and/or modify  *
+ * it under the terms of the GNU General Public License as published by *
+ * the Free Software Foundation, either version 3 of the License, or    *
+ * (at your option) any later version.                                  *
+ *                                                                      *
+ * Iomrascálaí is distributed in the hope that it will be useful,       *
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of       *
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the        *
+ * GNU General Public License for more details.                         *
+ *                                                                      *
+ * You should have received a copy of the GNU General Public License    *
+ * along with Iomrascálaí.  If not, see <http://www.gnu.org/licenses/>. *
+ *                                                                      *
+ ************************************************************************/
+
+#![cfg(test)]
+
+use board::Size;
+use ruleset::CGOS;
+use ruleset::KgsChinese;
+use ruleset::SuperKo;
+use super::Config;
+use super::ConfigError;
+use super::ConfigProblem;
+use tree_dump::DumpFormat;
+use tree_dump::TreeDump;
+
+fn load(toml: &str) -> Result<Config, ConfigError> {
+    Config::from_toml(toml.to_string(), false, false, KgsChinese, Some(1))
+}
+
+fn problems(toml: &str) -> Vec<String> {
+    match load(toml) {
+        Ok(_) => vec!(),
+        Err(e) => e.problems.iter().map(|p| p.to_string()).collect()
+    }
+}
+
+#[test]
+fn the_defaults_are_valid() {
+    assert!(load("").is_ok());
+}
+
+#[test]
+fn values_from_the_file_override_the_defaults() {
+    let config = load("[tree]\nrave_equiv = 10.5").unwrap();
+    assert_eq!(10.5, config.tree.rave_equiv);
+}
+
+#[test]
+fn reports_unknown_keys_and_sections() {
+    assert_eq!(vec!("tree.rave_equv: unknown key", "treee: unknown key"), problems("[tree]\nrave_equv = 10.0\n[treee]"));
+}
+
+#[test]
+fn reports_values_of_the_wrong_type() {
+    assert_eq!(vec!("tree.expand_after: expected integer but found string"), problems("[tree]\nexpand_after = \"1\""));
+    assert_eq!(vec!("tree: expected table but found integer"), problems("tree = 1"));
+}
+
+#[test]
+fn reports_values_out_of_range() {
+    assert_eq!(vec!("scoring.ownership_cutoff: 3 is not between 0 and 1"), problems("[scoring]\nownership_cutoff = 3.0"));
+    assert_eq!(vec!("threads: 0 is not greater than 0"), problems("threads = 0"));
+    assert_eq!(vec!("tactics.depth: -1 is negative"), problems("[tactics]\ndepth = -1"));
+}
+
+#[test]
+fn reports_invalid_choices() {
+    assert_eq!(vec!("dynamic_komi.mode: Unknown dynamic komi mode 'fixed'"), problems("[dynamic_komi]\nmode = \"fixed\""));
+}
+
+#[test]
+fn reports_all_problems_at_once() {
+    assert_eq!(3, problems("[tree]\nfoo = 1\nscore_weight = 2.0\n[playout]\natari_check = true").len());
+}
+
+#[test]
+fn the_command_line_overrides_the_threads() {
+    let config = Config::from_toml("threads = 0".to_string(), false, false, KgsChinese, Some(3)).unwrap();
+    assert_eq!(3, config.threads);
+}
+
+#[test]
+fn reports_invalid_toml() {
+    assert_eq!(1, problems("[tree").len());
+}
+
+#[test]
+fn problems_without_a_path_only_show_the_message() {
+    let problem = ConfigProblem { path: String::new(), message: "oops".to_string() };
+    assert_eq!("oops", problem.to_string());
+}
+
+#[test]
+fn params_lists_the_values_by_name() {
+    let config = load("[tree]\nrave_equiv = 10.5").unwrap();
+    let params = config.params();
+    assert!(params.contains(&("tree.rave_equiv".to_string(), "10.5".to_string())));
+    assert!(params.contains(&("dynamic_komi.mode".to_string(), "linear".to_string())));
+    assert!(params.iter().all(|&(ref name, _)| name != "threads" && name != "book.file"));
+}
+
+#[test]
+fn with_param_changes_only_that_parameter() {
+    let config = load("[tree]\nrave_equiv = 10.5").unwrap();
+    let changed = config.with_param("tree.expand_after", "3").unwrap();
+    assert_eq!(3, changed.tree.expand_after);
+    assert_eq!(10.5, changed.tree.rave_equiv);
+    assert_eq!(config.threads, changed.threads);
+}
+
+#[test]
+fn with_param_validates_the_value() {
+    let config = load("").unwrap();
+    assert_eq!(Err("unknown parameter tree.foo".to_string()), config.with_param("tree.foo", "1").map(|_| ()));
+    assert_eq!(Err("book.file can't be changed at runtime".to_string()), config.with_param("book.file", "x").map(|_| ()));
+    assert_eq!(Err("dynamic_komi.mode: Unknown dynamic komi mode 'x'".to_string()), config.with_param("dynamic_komi.mode", "x").map(|_| ()));
+}
+
+#[test]
+fn the_layer_for_the_size_is_applied() {
+    let config = load("[tree]\nrave_equiv = 10.0\n[size.9.tree]\nrave_equiv = 30.0").unwrap();
+    assert_eq!(10.0, config.tree.rave_equiv);
+    let nine = config.for_size(Size::square(9)).unwrap();
+    assert_eq!(30.0, nine.tree.rave_equiv);
+    assert_eq!(config.tree.expand_after, nine.tree.expand_after);
+    assert!(config.for_size(Size::square(19)).is_none());
+}
+
+#[test]
+fn switching_sizes_starts_from_the_base_config() {
+    let config = load("[size.9.tree]\nrave_equiv = 30.0\n[size.13.scoring]\nplayouts = 50").unwrap();
+    let nine = config.for_size(Size::square(9)).unwrap();
+    let thirteen = nine.for_size(Size::square(13)).unwrap();
+    assert_eq!(config.tree.rave_equiv, thirteen.tree.rave_equiv);
+    assert_eq!(50, thirteen.scoring.playouts);
+    let nineteen = thirteen.for_size(Size::square(19)).unwrap();
+    assert_eq!(config, nineteen);
+}
+
+#[test]
+fn the_size_layer_is_applied_after_the_ruleset_layer() {
+    let toml = "[rules.cgos.tree]\nrave_equiv = 5.0\nexpand_after = 4\n[size.19x13.tree]\nrave_equiv = 7.0";
+    let config = Config::from_toml(toml.to_string(), false, false, CGOS, Some(1)).unwrap();
+    let resolved = config.for_size(Size::new(19, 13).unwrap()).unwrap();
+    assert_eq!(7.0, resolved.tree.rave_equiv);
+    assert_eq!(4, resolved.tree.expand_after);
+    // Not the ruleset of the config.
+    let config = Config::from_toml(toml.to_string(), false, false, KgsChinese, Some(1)).unwrap();
+    assert_eq!(config.tree.expand_after, config.for_size(Size::new(19, 13).unwrap()).unwrap().tree.expand_after);
+}
+
+#[test]
+fn reports_problems_in_the_layers_with_their_path() {
+    assert_eq!(vec!("size.9.tree.rave_equv: unknown key"), problems("[size.9.tree]\nrave_equv = 1.0"));
+    assert_eq!(vec!("size.9.scoring.ownership_cutoff: 3 is not between 0 and 1"), problems("[size.9.scoring]\nownership_cutoff = 3.0"));
+    assert_eq!(vec!("rules.go: Unknown ruleset 'go'"), problems("[rules.go.tree]\nrave_equiv = 1.0"));
+    assert_eq!(vec!("size.9.threads: only sections can be overridden"), problems("[size.9]\nthreads = 2"));
+}
+
+#[test]
+fn parameters_set_at_runtime_stay_when_the_size_changes() {
+    let config = load("[size.9.tree]\nrave_equiv = 30.0").unwrap();
+    let changed = config.with_param("tree.expand_after", "5").unwrap();
+    let nine = changed.for_size(Size::square(9)).unwrap();
+    assert_eq!(5, nine.tree.expand_after);
+}
+
+#[test]
+fn the_options_outside_of_the_config_file_stay_when_the_config_is_rebuilt() {
+    let mut config = load("[size.9.tree]\nrave_equiv = 30.0").unwrap();
+    config.dump_dir = Some("trees".to_string());
+    config.super_ko = SuperKo::Situational;
+    config.tree_dump = TreeDump::new(DumpFormat::Dot, 5, 1);
+    let changed = config.with_param("tree.expand_after", "5").unwrap();
+    let nine = changed.for_size(Size::square(9)).unwrap();
+    for rebuilt in [changed, nine].iter() {
+        assert_eq!(config.dump_dir, rebuilt.dump_dir);
+        assert_eq!(config.super_ko, rebuilt.super_ko);
+        assert_eq!(config.tree_dump, rebuilt.tree_dump);
+    }
+}
@@ -22,12 +22,15 @@
 
 use board::Color;
 use board::Move;
+use board::Size;
 use config::Config;
 use engine::Engine;
+use engine::Node;
 use game::Game;
 use ownership::OwnershipStatistics;
 use score::FinalScore;
 use timer::Timer;
+use tree_dump::TreeDump;
 use uct_gfx::UctGfx;
 
 use std::sync::Arc;
@@ -48,11 +51,16 @@ impl EngineController {
         }
     }
 
-    pub fn reset(&mut self, size: u8, komi: f32) {
+    pub fn reset(&mut self, size: Size, komi: f32) {
         self.run_playouts_for_scoring = true;
         self.engine.reset(size, komi);
     }
 
+    pub fn set_config(&mut self, config: Arc<Config>) {
+        self.config = config.clone();
+        self.engine.set_config(config);
+    }
+
     pub fn ownership_statistics(&self) -> String {
         format!("{}", self.ownership())
     }
@@ -61,6 +69,26 @@ impl EngineController {
         format!("{}", UctGfx::new(&self.engine.root))
     }
 
+    pub fn dump_tree(&self, dump: &TreeDump) -> String {
+        dump.dump(&self.engine.root)
+    }
+
+    // One line per move in the root of the current search tree with
+    // the expected score, its standard deviation, and the number of
+    // playouts, ordered by the number of playouts.
+    pub fn expected_score(&self) -> String {
+        let mut children: Vec<&Node> = self.engine.root.children().iter()
+            .filter(|n| n.playouts() > 0)
+            .collect();
+        children.sort_by(|a, b| b.playouts().cmp(&a.playouts()));
+        let lines: Vec<String> = children.iter()
+            .filter_map(|n| n.m().to_gtp().ok().map(|vertex| {
+                format!("{} {:+.1} {:.1} {}", vertex, n.expected_score(), n.score_variance().sqrt(), n.playouts())
+            }))
+            .collect();
+        lines.join("\n")
+    }
+
     pub fn final_score(&mut self, game: &Game) -> String {
         self.run_playouts(game);
         FinalScore::new(self.config.clone(), game, self.ownership()).score()
@@ -27,10 +27,15 @@ use board::Move;
 use board::NoMove;
 use board::Pass;
 use board::Resign;
+use board::Size;
 use board::White;
+use book::Book;
 use config::Config;
 use game::Game;
+use komi::DynamicKomi;
+use logger::Level;
 use ownership::OwnershipStatistics;
+use patterns::LargePatternMatcher;
 use patterns::SmallPatternMatcher;
 use playout::Playout;
 use ruleset::KgsChinese;
@@ -43,6 +48,9 @@ use self::worker::Response;
 use self::worker::Worker;
 use timer::Timer;
 
+use rand::weak_rng;
+use std::fs::File;
+use std::io::Write;
 use std::sync::Arc;
 use std::sync::mpsc::Receiver;
 use std::sync::mpsc::Sender;
@@ -61,7 +69,7 @@ macro_rules! check {
                 $body
             },
             Err(e) => {
-                $config.log(format!("[DEBUG] unwrap failed with {:?} at {}:{}", e, file!(), line!()));
+                error!($config, "unwrap failed with {:?} at {}:{}", e, file!(), line!());
             }
         }
     };
@@ -73,29 +81,44 @@ mod node;
 mod prior;
 mod worker;
 
+/// The size of the search tree after a search.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TreeStats {
+    pub depth: usize,
+    pub nodes: usize,
+    pub playouts: usize,
+}
+
 pub struct Engine {
+    book: Arc<Book>,
     config: Arc<Config>,
     direct_message_senders: Vec<Sender<DirectMessage>>,
     id: usize,
+    komi: DynamicKomi,
+    large_pattern_matcher: Arc<LargePatternMatcher>,
     ownership: OwnershipStatistics,
     playout: Arc<Playout>,
     previous_node_count: usize,
     receive_from_threads: Receiver<Response>,
     root: Node,
     send_to_main: Sender<Response>,
+    settled: Arc<Vec<bool>>,
     small_pattern_matcher: Arc<SmallPatternMatcher>,
     start: PreciseTime,
 }
 
 impl Engine {
 
-    pub fn new(config: Arc<Config>, small_pattern_matcher: Arc<SmallPatternMatcher>) -> Engine {
+    pub fn new(config: Arc<Config>, small_pattern_matcher: Arc<SmallPatternMatcher>, large_pattern_matcher: Arc<LargePatternMatcher>, book: Arc<Book>) -> Engine {
         let (send_to_main, receive_from_threads) = channel();
         let mut engine = Engine {
+            book: book,
             config: config.clone(),
             direct_message_senders: vec!(),
             id: 0,
-            ownership: OwnershipStatistics::new(config.clone(), 0, 0.0),
+            komi: DynamicKomi::new(config.clone()),
+            large_pattern_matcher: large_pattern_matcher,
+            ownership: OwnershipStatistics::new(config.clone(), Size::square(0), 0.0),
             playout: Arc::new(Playout::new(
                 config.clone(),
                 small_pattern_matcher.clone()
@@ -104,6 +127,7 @@ impl Engine {
             receive_from_threads: receive_from_threads,
             root: Node::new(NoMove, config),
             send_to_main: send_to_main,
+            settled: Arc::new(vec!()),
             small_pattern_matcher: small_pattern_matcher.clone(),
             start: PreciseTime::now(),
         };
@@ -111,6 +135,18 @@ impl Engine {
         engine
     }
 
+    pub fn playout(&self) -> Arc<Playout> {
+        self.playout.clone()
+    }
+
+    pub fn tree_stats(&self) -> TreeStats {
+        TreeStats {
+            depth: self.root.depth(),
+            nodes: self.root.node_count(),
+            playouts: self.root.playouts(),
+        }
+    }
+
     pub fn ownership(&self) -> &OwnershipStatistics {
         &self.ownership
     }
@@ -125,21 +161,60 @@ impl Engine {
 
     fn generic_genmove(&mut self, color: Color, game: &Game, timer: &Timer, cleanup: bool) -> (Move,usize) {
         self.genmove_setup(color, game);
+        if !cleanup {
+            if let Some(m) = self.book.pick(game, &self.config.book, &mut weak_rng()) {
+                info!(self.config, "Book move: {:?}", m);
+                self.set_new_root(&game.play(m).unwrap(), color);
+                return (m, 0);
+            }
+        }
         if self.root.has_no_children() {
-            self.config.log(format!("No moves to simulate!"));
+            warn!(self.config, "No moves to simulate!");
             return (Pass(color), self.root.playouts());
         }
+        // The playouts (and the terminal nodes of the tree) are scored
+        // with the dynamic komi, everything else (including the score
+        // margins of the nodes) uses the real one.
+        let komi = self.komi.komi(game, color);
+        debug!(self.config, "Dynamic komi: {} (real komi: {})", komi, game.komi());
+        let mut search_game = game.clone();
+        search_game.set_komi(komi);
         let stop = |win_ratio, _| { timer.ran_out_of_time(win_ratio) };
-        self.search(game, stop);
-        let msg = format!("{} simulations ({}% wins on average)", self.root.playouts(), self.root.win_ratio()*100.0);
-        self.config.log(msg);
+        self.search(&search_game, game.komi(), stop);
+        info!(self.config, "{} simulations ({}% wins on average)", self.root.playouts(), self.root.win_ratio()*100.0);
+        self.komi.update(self.root.win_ratio());
         let playouts = self.root.playouts();
-        let m = self.best_move(game, color, cleanup);
+        let (m, win_ratio) = self.best_move(game, color, cleanup);
+        log!(self.config, Level::Info, "genmove",
+             "move" => m.to_gtp().unwrap_or(String::new()),
+             "move_number" => game.move_number(),
+             "playouts" => playouts,
+             "time_budget_ms" => timer.budget_ms(),
+             "time_spent_ms" => timer.elapsed_ms(),
+             "win_rate" => win_ratio);
+        if let Some(ref dir) = self.config.dump_dir {
+            self.dump_tree(dir, game, color);
+        }
         self.set_new_root(&game.play(m).unwrap(), color);
         (m,playouts)
     }
 
-    fn search<F>(&mut self, game: &Game, stop: F) where F: Fn(f32, usize) -> bool {
+    // Writes the search tree to a file in the directory before the
+    // root moves on to the chosen move (see `Config::dump_dir`).
+    fn dump_tree(&self, dir: &str, game: &Game, color: Color) {
+        let dump = self.config.tree_dump;
+        let player = if color == White { "w" } else { "b" };
+        let filename = format!("{}/move-{:03}-{}.{}", dir, game.move_number() + 1, player, dump.format().extension());
+        let result = File::create(&filename).and_then(|mut file| file.write_all(dump.dump(&self.root).as_bytes()));
+        match result {
+            Ok(_) => debug!(self.config, "Search tree written to {}", filename),
+            Err(e) => error!(self.config, "Can't write the search tree to {}: {}", filename, e),
+        }
+    }
+
+    // `komi` is the real komi of the game, the komi of `game` may be
+    // the dynamic komi.
+    fn search<F>(&mut self, game: &Game, komi: f32, stop: F) where F: Fn(f32, usize) -> bool {
         self.send_new_state_to_workers(game);
         loop {
             let win_ratio = {
@@ -152,7 +227,7 @@ impl Engine {
             if done { return; }
             let r = self.receive_from_threads.recv();
             check!(self.config, res = r => {
-                self.handle_response(res, &game);
+                self.handle_response(res, &game, komi);
             });
         }
     }
@@ -170,14 +245,28 @@ impl Engine {
         let stop = |_, current_playouts: usize| {
             (current_playouts - initial_playouts) > playouts
         };
-        self.search(game, stop);
+        self.search(game, game.komi(), stop);
+    }
+
+    /// Searches the position from scratch for the given number of
+    /// playouts and returns the move with the most playouts. This is
+    /// used to build opening books from searches.
+    pub fn search_best_move(&mut self, game: &Game, playouts: usize) -> Move {
+        self.reset(game.size(), game.komi());
+        self.donplayouts(game, playouts);
+        if self.root.has_no_children() {
+            Pass(game.next_player())
+        } else {
+            let (best, _) = self.root.best();
+            best.m()
+        }
     }
 
     fn dead_stones_on_board(&self, game: &Game) -> bool {
         FinalScore::new(self.config.clone(), game, self.ownership()).dead_stones_on_board()
     }
 
-    fn handle_response(&mut self, response: Response, game: &Game) {
+    fn handle_response(&mut self, response: Response, game: &Game, komi: f32) {
         let (answer, id, send_to_thread) = response;
         // Ignore responses from the previous genmove
         if self.id == id {
@@ -185,7 +274,8 @@ impl Engine {
                 Answer::NewState => {
                     self.expand(game, Path::new())
                 },
-                Answer::RunPlayout {path, playout_result} => {
+                Answer::RunPlayout {path, mut playout_result} => {
+                    playout_result.set_real_komi(komi);
                     self.ownership.merge(playout_result.score());
                     self.root.record_on_path(path.path(), &playout_result);
                     self.expand(game, path)
@@ -200,7 +290,7 @@ impl Engine {
     }
 
     fn expand(&mut self, game: &Game, path: Path) -> Message {
-        let (path, child_moves) = self.root.find_leaf_and_expand(game, path);
+        let (path, child_moves) = self.root.find_leaf_and_expand(game, &self.settled, path);
         let nodes_added = child_moves.len();
         if nodes_added > 0 {
             Message::CalculatePriors {
@@ -214,12 +304,30 @@ impl Engine {
         }
     }
 
-    pub fn reset(&mut self, size: u8, komi: f32) {
+    pub fn reset(&mut self, size: Size, komi: f32) {
+        self.komi.reset();
         self.previous_node_count = 0;
         self.root = Node::new(NoMove, self.config.clone());
         self.ownership = OwnershipStatistics::new(self.config.clone(), size, komi);
     }
 
+    /// Use a new configuration (e.g. after a parameter was changed
+    /// over GTP) starting with the next search. The tree is thrown
+    /// away as its nodes still use the old configuration.
+    pub fn set_config(&mut self, config: Arc<Config>) {
+        self.config = config.clone();
+        self.komi.set_config(config.clone());
+        self.playout = Arc::new(Playout::new(config.clone(), self.small_pattern_matcher.clone()));
+        self.root = Node::new(NoMove, config.clone());
+        for direct_message_sender in &self.direct_message_senders {
+            let dm = DirectMessage::NewConfig {
+                config: config.clone(),
+                playout: self.playout.clone(),
+            };
+            check!(self.config, direct_message_sender.send(dm));
+        }
+    }
+
     fn set_new_root(&mut self, game: &Game, color: Color) {
         self.root = self.root.find_new_root(game, color);
     }
@@ -231,7 +339,7 @@ impl Engine {
         self.set_new_root(game, color);
     }
 
-    fn best_move(&self, game: &Game, color: Color, cleanup: bool) -> Move {
+    fn best_move(&self, game: &Game, color: Color, cleanup: bool) -> (Move,f32) {
         let (best_node, pass) = self.root.best();
         let best_win_ratio = best_node.win_ratio();
         let pass_win_ratio = pass.win_ratio();
@@ -255,17 +363,22 @@ impl Engine {
         } else {
             best_node
         };
+        let n = if !n.m().is_pass() && n.win_ratio() > self.config.tree.score_maximization {
+            self.root.best_score()
+        } else {
+            n
+        };
         let win_ratio = n.win_ratio();
-        let msg = format!("Best move win ratio: {}%", win_ratio*100.0);
-        self.config.log(msg);
+        info!(self.config, "Best move win ratio: {}% (expected score: {})", win_ratio*100.0, n.expected_score());
         // Special case, when we are winning and all moves are played.
-        if win_ratio == 0.0 {
+        let m = if win_ratio == 0.0 {
             Pass(color)
         } else if win_ratio < self.config.scoring.resignation_percentage {
             Resign(color)
         } else {
             n.m()
-        }
+        };
+        (m, win_ratio)
     }
 
     fn spin_down(&mut self) {
@@ -284,10 +397,15 @@ impl Engine {
 
     fn send_new_state_to_workers(&mut self, game: &Game) {
         self.id += 1;
+        // Benson's algorithm is too slow to run for every expansion
+        // and playout. The points settled now stay settled for the
+        // whole search as the tree and the playouts don't play there.
+        self.settled = Arc::new(game.board().settled_points());
         for direct_message_sender in &self.direct_message_senders {
             let dm = DirectMessage::NewState {
                 board: game.board(),
                 id: self.id,
+                settled: self.settled.clone(),
             };
             check!(self.config, direct_message_sender.send(dm));
         }
@@ -298,6 +416,7 @@ impl Engine {
             &self.config,
             &self.playout,
             &self.small_pattern_matcher,
+            &self.large_pattern_matcher,
             &self.send_to_main
         );
         let (send_direct_message, receive_direct_message) = channel();
@@ -26,9 +26,11 @@ use board::Move;
 use board::NoMove;
 use board::Pass;
 use board::Play;
+use board::Size;
 use config::Config;
 use engine::worker::Path;
 use game::Game;
+use game::ZobristHashTable;
 use playout::PlayoutResult;
 use score::Score;
 use super::prior::Prior;
@@ -49,6 +51,9 @@ pub struct Node {
     plays: f32,
     prior_plays: usize,
     prior_wins: usize,
+    score_count: usize,
+    score_m2: f32,
+    score_mean: f32,
     wins: f32,
 }
 
@@ -65,6 +70,9 @@ impl Node {
             plays: 0.0,
             prior_plays: config.priors.neutral_plays,
             prior_wins: config.priors.neutral_wins,
+            score_count: 0,
+            score_m2: 0.0,
+            score_mean: 0.0,
             wins: 0.0,
         }
     }
@@ -114,15 +122,20 @@ impl Node {
         self.plays = 0.0;
         self.prior_plays = 0;
         self.prior_wins = 0;
+        self.score_count = 0;
+        self.score_m2 = 0.0;
+        self.score_mean = 0.0;
         self.wins = 0.0;
     }
 
     pub fn remove_illegal_children(&mut self, game: &Game) {
         let mut to_remove = vec!();
+        let moves = self.children.iter().map(|node| node.m()).collect();
+        let legal = game.check_moves(&moves);
 
         for (index, node) in self.children.iter().enumerate() {
             match node.m() {
-                Play(..) => if game.play(node.m()).is_err() {
+                Play(..) => if !legal[index] {
                     to_remove.push(index);
                 },
                 Pass(_) => if game.winner() != game.next_player() {
@@ -137,14 +150,21 @@ impl Node {
         }
     }
 
-    pub fn find_leaf_and_expand(&mut self, game: &Game, mut path: Path) -> (Path, Vec<Move>) {
+    /// Finds the next leaf to simulate and expands it. `settled` are
+    /// the settled points of the position the search started from
+    /// (see `Board::settled_points()`), they stay settled as long as
+    /// nobody plays inside them.
+    pub fn find_leaf_and_expand(&mut self, game: &Game, settled: &Vec<bool>, mut path: Path) -> (Path, Vec<Move>) {
         path.clear();
         let (path, leaf) = self.find_leaf_and_mark(path);
         let mut board = game.board();
+        let history = game.zobrist_hash_table();
+        let mut path_keys = Vec::with_capacity(path.moves().len());
         for &m in path.moves().iter() {
             board.play_legal_move(m);
+            path_keys.extend(history.key_after(&m, &board));
         }
-        let (not_terminal, child_moves) = leaf.expand(&board);
+        let (not_terminal, child_moves) = leaf.expand(&board, history, &path_keys, settled);
         if !not_terminal {
             let is_win = board.winner() == leaf.color();
             leaf.mark_as_terminal(is_win);
@@ -170,20 +190,32 @@ impl Node {
 
     fn expand_root(&mut self, game: &Game) {
         if !game.is_over() {
+            let settled = game.board().settled_points();
             self.children = game.legal_moves_without_eyes()
                 .iter()
+                .filter(|m| !is_settled(&settled, game.size(), m))
                 .map(|&m| Node::new(m, self.config.clone()))
                 .collect();
             self.children.push(Node::new(Pass(game.next_player()), self.config.clone()));
         }
  }
 
-    pub fn expand(&mut self, board: &Board) -> (bool, Vec<Move>) {
+    /// Adds the children of the leaf. The history contains the
+    /// positions of the game and `path` the keys of the positions
+    /// along the path leading to the leaf so that no child violates
+    /// the superko rule. No children are added for the settled points.
+    pub fn expand(&mut self, board: &Board, history: &ZobristHashTable, path: &[u64], settled: &Vec<bool>) -> (bool, Vec<Move>) {
         let not_terminal = !board.is_game_over();
         if not_terminal && self.playouts >= self.config.tree.expand_after {
-            self.children = board.legal_moves_without_eyes()
-                .iter()
-                .map(|m| self.new_leaf(m))
+            let moves: Vec<Move> = board.legal_moves_without_eyes()
+                .into_iter()
+                .filter(|m| !is_settled(settled, board.size(), m))
+                .collect();
+            let legal = history.check_moves(board, path, &moves);
+            self.children = moves.iter()
+                .zip(legal.into_iter())
+                .filter(|&(_, is_legal)| is_legal)
+                .map(|(m, _)| self.new_leaf(m))
                 .collect();
             self.children.push(Node::new(Pass(board.next_player()), self.config.clone()));
         }
@@ -220,6 +252,7 @@ impl Node {
     pub fn record_on_path(&mut self, path: &[usize], playout_result: &PlayoutResult) {
         let winner = playout_result.winner();
         let amaf = playout_result.amaf();
+        self.record_score(playout_result.score());
         if self.color() == winner {
             self.record_win(playout_result.score());
         }
@@ -244,6 +277,16 @@ impl Node {
         }
     }
 
+    // Marks the path as played without searching, so that the tests
+    // of other modules can build trees with playouts.
+    #[cfg(test)]
+    pub fn record_play_on_path(&mut self, path: &[usize]) {
+        self.record_play();
+        if path.len() > 0 {
+            self.children[path[0]].record_play_on_path(&path[1..]);
+        }
+    }
+
     pub fn record_priors(&mut self, path: &[usize], priors: Vec<Prior>) {
         if path.len() == 0 {
             for (index, prior) in priors.iter().enumerate() {
@@ -267,6 +310,21 @@ impl Node {
         (best, pass)
     }
 
+    // Once the game is decided the win ratio doesn't tell the moves
+    // apart anymore so we pick the move with the best expected score
+    // among the moves that were searched about as much as the best one.
+    pub fn best_score(&self) -> &Node {
+        let (best, _) = self.best();
+        let min_plays = best.plays_with_prior_factor() / 2.0;
+        let mut best_score = best;
+        for n in self.children.iter() {
+            if !n.m().is_pass() && n.plays_with_prior_factor() >= min_plays && n.expected_score() > best_score.expected_score() {
+                best_score = n;
+            }
+        }
+        best_score
+    }
+
     fn weighted_win(&self, score: &Score) -> f32 {
         let weight = self.config.tree.score_weight;
         (weight * score.adjusted()) + (1.0 - weight)
@@ -276,6 +334,16 @@ impl Node {
         self.wins += self.weighted_win(score);
     }
 
+    // Welford's online algorithm, so that we don't need to keep all
+    // the scores around to calculate the variance.
+    fn record_score(&mut self, score: &Score) {
+        let margin = score.margin(self.color());
+        self.score_count += 1;
+        let delta = margin - self.score_mean;
+        self.score_mean += delta / self.score_count as f32;
+        self.score_m2 += delta * (margin - self.score_mean);
+    }
+
     fn record_amaf_win(&mut self, score: &Score) {
         self.amaf_wins += self.weighted_win(score);
     }
@@ -305,6 +373,36 @@ impl Node {
         self.playouts
     }
 
+    pub fn plays(&self) -> f32 {
+        self.plays
+    }
+
+    pub fn wins(&self) -> f32 {
+        self.wins
+    }
+
+    pub fn amaf_plays(&self) -> f32 {
+        self.amaf_plays
+    }
+
+    pub fn amaf_wins(&self) -> f32 {
+        self.amaf_wins
+    }
+
+    pub fn prior_plays(&self) -> usize {
+        self.prior_plays
+    }
+
+    pub fn prior_wins(&self) -> usize {
+        self.prior_wins
+    }
+
+    /// The value (UCT mixed with RAVE) the search uses to pick the
+    /// child of `parent` to descend into next.
+    pub fn uct_value(&self, parent: &Node) -> f32 {
+        self.child_value(parent.plays_with_prior_factor())
+    }
+
     pub fn find_child(&self, m: Move) -> Node {
         match self.children.iter().find(|c| c.m() == m) {
             Some(node) => node.clone(),
@@ -358,6 +456,20 @@ impl Node {
         }
     }
 
+    /// The average final score of the playouts through this node from
+    /// the point of view of the player who played its move.
+    pub fn expected_score(&self) -> f32 {
+        self.score_mean
+    }
+
+    pub fn score_variance(&self) -> f32 {
+        if self.score_count < 2 {
+            0.0
+        } else {
+            self.score_m2 / (self.score_count - 1) as f32
+        }
+    }
+
     pub fn win_ratio(&self) -> f32 {
         if self.plays == 0.0 {
             0f32
@@ -374,4 +486,22 @@ impl Node {
         &self.children
     }
 
+    /// The number of nodes in the tree starting at this node
+    /// (including the node itself).
+    pub fn node_count(&self) -> usize {
+        1 + self.children.iter().map(|c| c.node_count()).sum::<usize>()
+    }
+
+    /// The number of moves on the longest path from this node to a
+    /// leaf.
+    pub fn depth(&self) -> usize {
+        self.children.iter().map(|c| c.depth() + 1).max().unwrap_or(0)
+    }
+
+}
+
+// Moves inside pass-alive territory can't change the outcome of the
+// game so there's no need to search them.
+fn is_settled(settled: &Vec<bool>, size: Size, m: &Move) -> bool {
+    !m.is_pass() && Board::is_settled(settled, size, &m.coord())
 }
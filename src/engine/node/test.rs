@@ -64,6 +64,17 @@ fn root_expands_the_children() {
     assert_eq!(5, root.children.len());
 }
 
+#[test]
+fn node_count_and_depth() {
+    let game = Game::new(2, 0.5, KgsChinese);
+    let mut root = Node::root(&game, Black, config());
+    assert_eq!(6, root.node_count());
+    assert_eq!(1, root.depth());
+    root.find_leaf_and_expand(&game, &game.board().settled_points(), WorkerPath::new());
+    assert!(root.node_count() > 6);
+    assert_eq!(2, root.depth());
+}
+
 // expand()
 #[test]
 fn expand_doesnt_add_children_to_terminal_nodes() {
@@ -71,7 +82,7 @@ fn expand_doesnt_add_children_to_terminal_nodes() {
     game = game.play(Pass(Black)).unwrap();
     game = game.play(Pass(White)).unwrap();
     let mut node = Node::new(Pass(Black), config());
-    node.expand(&game.board());
+    node.expand(&game.board(), game.zobrist_hash_table(), &[], &game.board().settled_points());
     assert_eq!(0, node.children.len());
 }
 
@@ -81,7 +92,7 @@ fn expand_doesnt_add_children_if_threshold_not_met() {
     let game = Game::new(2, 0.5, KgsChinese);
     let mut node = Node::new(Pass(Black), config);
     node.plays = 0.0;
-    node.expand(&game.board());
+    node.expand(&game.board(), game.zobrist_hash_table(), &[], &game.board().settled_points());
     assert_eq!(0, node.children.len());
 }
 
@@ -90,7 +101,7 @@ fn expand_adds_children_if_threshold_is_met() {
     let game = Game::new(2, 0.5, KgsChinese);
     let mut node = Node::new(Pass(Black), config());
     node.plays = 2.0;
-    node.expand(&game.board());
+    node.expand(&game.board(), game.zobrist_hash_table(), &[], &game.board().settled_points());
     assert_eq!(5, node.children.len());
 }
 
@@ -100,7 +111,7 @@ fn find_leaf_and_expand_expands_the_leaves() {
     let game = Game::new(2, 0.5, KgsChinese);
     let mut root = Node::root(&game, Black, config());
     for _ in 0..5 {
-        root.find_leaf_and_expand(&game, WorkerPath::new());
+        root.find_leaf_and_expand(&game, &game.board().settled_points(), WorkerPath::new());
     }
     assert_eq!(5, root.children.len());
     assert!(root.children.iter().all({|n|
@@ -116,7 +127,7 @@ fn find_leaf_and_expand_expands_the_leaves() {
 fn find_leaf_and_expand_sets_play_on_the_root() {
     let game = Game::new(2, 0.5, KgsChinese);
     let mut root = Node::root(&game, Black, config());
-    root.find_leaf_and_expand(&game, WorkerPath::new());
+    root.find_leaf_and_expand(&game, &game.board().settled_points(), WorkerPath::new());
     assert_eq!(2.0, root.plays);
 }
 
@@ -124,7 +135,7 @@ fn find_leaf_and_expand_sets_play_on_the_root() {
 fn find_leaf_and_expand_returns_the_number_of_nodes_added() {
     let game = Game::new(2, 0.5, KgsChinese);
     let mut root = Node::root(&game, Black, config());
-    let (_, child_moves) = root.find_leaf_and_expand(&game, WorkerPath::new());
+    let (_, child_moves) = root.find_leaf_and_expand(&game, &game.board().settled_points(), WorkerPath::new());
     assert_eq!(4, child_moves.len());
 }
 
@@ -145,6 +156,24 @@ fn no_super_ko_violations_in_the_children_of_the_root() {
     assert!(root.children.iter().all(|n| n.m() != Play(White, 2, 9)));
 }
 
+#[test]
+fn expand_doesnt_add_super_ko_violations_along_the_path() {
+    let parser = Parser::from_path(Path::new("fixtures/sgf/positional-superko-setup.sgf")).unwrap();
+    let game = parser.game().unwrap();
+    let mut board = game.board();
+    let history = game.zobrist_hash_table();
+    let mut path = vec!();
+    for &m in [Play(White, 3, 9), Play(Black, 1, 9)].iter() {
+        board.play_legal_move(m);
+        path.extend(history.key_after(&m, &board));
+    }
+    let mut node = Node::new(Pass(Black), config());
+    node.expand(&board, history, &path, &board.settled_points());
+    assert!(node.children.iter().any(|n| n.m() == Play(White, 1, 1)));
+    // Play(White, 2, 9) is a super ko violation
+    assert!(node.children.iter().all(|n| n.m() != Play(White, 2, 9)));
+}
+
 describe! record_on_path {
 
     before_each {
@@ -177,6 +206,43 @@ describe! record_on_path {
         assert_eq!(1.0, root.children[0].wins);
         assert_eq!(1.0, root.children[0].children[0].wins);
     }
+
+    it "records the mean and variance of the score for each color" {
+        let child = Node::new(Pass(White), config.clone());
+        let mut root = Node::new(Pass(Black), config.clone());
+        root.children = vec!(child);
+
+        // B+74.5
+        let mut board = Board::new(9, 6.5, KgsChinese);
+        board.play(Play(Black, 1, 1)).unwrap();
+        let playout_result = PlayoutResult::new(board.score(), HashMap::new());
+        root.record_on_path(&vec!(0), &playout_result);
+        // W+6.5
+        let board = Board::new(9, 6.5, KgsChinese);
+        let playout_result = PlayoutResult::new(board.score(), HashMap::new());
+        root.record_on_path(&vec!(0), &playout_result);
+
+        assert_eq!(34.0, root.expected_score());
+        assert_eq!(-34.0, root.children[0].expected_score());
+        assert_eq!(3280.5, root.score_variance());
+        assert_eq!(3280.5, root.children[0].score_variance());
+    }
+}
+
+#[test]
+fn best_score_picks_the_highest_expected_score_among_well_searched_moves() {
+    let mut root = Node::new(Pass(Black), config());
+    let mut most_played = Node::new(Play(White, 1, 1), config());
+    most_played.plays = 100.0;
+    most_played.score_mean = 1.0;
+    let mut higher_score = Node::new(Play(White, 2, 2), config());
+    higher_score.plays = 60.0;
+    higher_score.score_mean = 5.0;
+    let mut rarely_played = Node::new(Play(White, 3, 3), config());
+    rarely_played.plays = 10.0;
+    rarely_played.score_mean = 20.0;
+    root.children = vec!(most_played, higher_score, rarely_played);
+    assert_eq!(Play(White, 2, 2), root.best_score().m());
 }
 
 #[test]
@@ -23,10 +23,16 @@ use board::Board;
 use board::Empty;
 use board::Move;
 use config::Config;
+use patterns::LargePatternMatcher;
 use patterns::SmallPatternMatcher;
+use tactics;
+use tactics::Reader;
+use tactics::Verdict;
 
 use std::sync::Arc;
 
+mod test;
+
 pub struct Prior {
     m: Move,
     plays: usize,
@@ -35,14 +41,14 @@ pub struct Prior {
 
 impl Prior {
 
-    pub fn new(board: &Board, m: &Move, small_pattern_matcher: &Arc<SmallPatternMatcher>, config: Arc<Config>) -> Prior {
+    pub fn new(board: &Board, m: &Move, small_pattern_matcher: &Arc<SmallPatternMatcher>, large_pattern_matcher: &Arc<LargePatternMatcher>, config: Arc<Config>) -> Prior {
         let mut prior = Prior {
             m: *m,
             plays: 0,
             wins: 0,
         };
         if !m.is_pass() {
-            prior.calculate(board, m, small_pattern_matcher, &config);
+            prior.calculate(board, m, small_pattern_matcher, large_pattern_matcher, &config);
         }
         prior
     }
@@ -55,7 +61,7 @@ impl Prior {
         self.wins
     }
 
-    fn calculate(&mut self, board: &Board, m: &Move, small_pattern_matcher: &Arc<SmallPatternMatcher>, config: &Arc<Config>) {
+    fn calculate(&mut self, board: &Board, m: &Move, small_pattern_matcher: &Arc<SmallPatternMatcher>, large_pattern_matcher: &Arc<LargePatternMatcher>, config: &Arc<Config>) {
         if !board.is_not_self_atari(m) {
             let value = config.priors.self_atari;
             self.record_negative_prior(value);
@@ -72,10 +78,16 @@ impl Prior {
             }
         }
         if config.priors.use_small_patterns() {
-            let count = self.matching_patterns_count(board, m, small_pattern_matcher);
-            let prior = count * config.priors.small_patterns;
+            let weight = self.matching_patterns_weight(board, m, small_pattern_matcher);
+            let prior = (weight * config.priors.small_patterns as f32).round() as usize;
             self.record_even_prior(prior);
         }
+        if config.priors.use_large_patterns() {
+            if let Some(weight) = large_pattern_matcher.weight(board, m) {
+                let prior = (weight * config.priors.large_patterns as f32).round() as usize;
+                self.record_even_prior(prior);
+            }
+        }
     }
 
     fn in_empty_area(&self, board: &Board, m: &Move) -> bool {
@@ -84,8 +96,8 @@ impl Prior {
             .all(|c| board.color(c) == Empty)
     }
 
-    fn matching_patterns_count(&self, board: &Board, m: &Move, matcher: &Arc<SmallPatternMatcher>) -> usize {
-        matcher.pattern_count(board, &m.coord())
+    fn matching_patterns_weight(&self, board: &Board, m: &Move, matcher: &Arc<SmallPatternMatcher>) -> f32 {
+        matcher.pattern_weight(board, &m.coord())
     }
 
     fn record_priors(&mut self, plays: usize, wins: usize) {
@@ -102,9 +114,9 @@ impl Prior {
     }
 }
 
-pub fn calculate(board: Board, child_moves: Vec<Move>, small_pattern_matcher: &Arc<SmallPatternMatcher>, config: &Arc<Config>) -> Vec<Prior> {
+pub fn calculate(board: &Board, child_moves: Vec<Move>, small_pattern_matcher: &Arc<SmallPatternMatcher>, large_pattern_matcher: &Arc<LargePatternMatcher>, config: &Arc<Config>) -> Vec<Prior> {
     let mut priors: Vec<Prior> = child_moves.iter()
-        .map(|m| Prior::new(&board, m, small_pattern_matcher, config.clone()))
+        .map(|m| Prior::new(board, m, small_pattern_matcher, large_pattern_matcher, config.clone()))
         .collect();
     let color = board.next_player().opposite();
     let in_danger = board.chains().iter()
@@ -129,5 +141,19 @@ pub fn calculate(board: Board, child_moves: Vec<Move>, small_pattern_matcher: &A
             }
         }
     }
+    if config.priors.use_tactics() {
+        let mut reader = Reader::new(&config.tactics);
+        for verdict in tactics::analyze(board, &mut reader) {
+            let value = match verdict {
+                Verdict::Capture { .. } => config.priors.tactics_capture,
+                Verdict::Connect { .. } | Verdict::Cut { .. } => config.priors.connection,
+                Verdict::Save { .. } => config.priors.tactics_save,
+                Verdict::Semeai { .. } => config.priors.semeai,
+            };
+            if let Some(prior) = priors.iter_mut().find(|c| c.m == verdict.m()) {
+                prior.record_even_prior(value);
+            }
+        }
+    }
     priors
 }
@@ -0,0 +1,70 @@
+/************************************************************************
+ *                                                                      *
+ * Copyright 2016 Urban Hafner                                          *
+ *                                                                      *
+ * This file is part of Iomrascálaí.                                    *
+ *                                                                      *
+ * Iomrascálaí is free software: you can redistribute it and/or modify  *
+ * it under the terms of the GNU General Public License as published by *
+ * the Free Software Foundation, either version 3 of the License, or    *
+ * (at your option) any later version.                                  *
+ *                                                                      *
+ * Iomrascálaí is distributed in the hope that it will be useful,       *
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of       *
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the        *
+ * GNU General Public License for more details.                         *
+ *                                                                      *
+ * You should have received a copy of the GNU General Public License    *
+ * along with Iomrascálaí.  If not, see <http://www.gnu.org/licenses/>. *
+ *                                                                      *
+ ************************************************************************/
+
+#![cfg(test)]
+
+use board::Board;
+use config::Config;
+use patterns::LargePatternMatcher;
+use patterns::SmallPatternMatcher;
+use sgf::Parser;
+use super::calculate;
+
+use std::path::Path;
+use std::sync::Arc;
+use test::Bencher;
+
+fn board() -> Board {
+    let parser = Parser::from_path(Path::new("fixtures/sgf/ladders.sgf")).unwrap();
+    parser.game().unwrap().board()
+}
+
+fn bench_priors(b: &mut Bencher, config: Config) {
+    let config = Arc::new(config);
+    let small_pattern_matcher = Arc::new(SmallPatternMatcher::new());
+    let large_pattern_matcher = Arc::new(LargePatternMatcher::new());
+    let board = board();
+    let moves = board.legal_moves_without_superko_check();
+    b.iter(|| calculate(&board, moves.clone(), &small_pattern_matcher, &large_pattern_matcher, &config));
+}
+
+#[test]
+fn the_tactical_reader_is_off_by_default() {
+    assert!(!Config::test_config().priors.use_tactics());
+}
+
+// Compare these two to see what the tactical reader costs on every
+// expansion of a node before turning its priors on.
+
+#[bench]
+fn priors_without_tactics(b: &mut Bencher) {
+    bench_priors(b, Config::test_config());
+}
+
+#[bench]
+fn priors_with_tactics(b: &mut Bencher) {
+    let config = Config::test_config()
+        .with_param("priors.tactics_capture", "20").unwrap()
+        .with_param("priors.tactics_save", "20").unwrap()
+        .with_param("priors.semeai", "20").unwrap()
+        .with_param("priors.connection", "10").unwrap();
+    bench_priors(b, config);
+}
@@ -22,6 +22,7 @@
 use board::Board;
 use board::Move;
 use config::Config;
+use patterns::LargePatternMatcher;
 use patterns::SmallPatternMatcher;
 use playout::Playout;
 use playout::PlayoutResult;
@@ -37,9 +38,14 @@ use std::sync::mpsc::channel;
 
 pub enum DirectMessage {
     SpinDown,
+    NewConfig {
+        config: Arc<Config>,
+        playout: Arc<Playout>,
+    },
     NewState {
         board: Board,
         id: usize,
+        settled: Arc<Vec<bool>>,
     }
 }
 
@@ -75,12 +81,18 @@ impl Path {
         &self.moves
     }
 
-    fn setup_board(&self, board: &Option<Board>) -> Board {
-        let mut b = board.clone().expect("no board for run_playout");
+    // Plays the moves of the path on the board in a way that they
+    // can be taken back with revert() instead of copying the board.
+    fn apply(&self, board: &mut Board) {
         for &m in self.moves().iter() {
-            b.play_legal_move(m);
+            board.make_legal_move(m);
+        }
+    }
+
+    fn revert(&self, board: &mut Board) {
+        for _ in self.moves().iter() {
+            board.unmake_move();
         }
-        b
     }
 }
 
@@ -108,25 +120,31 @@ pub struct Worker {
     board: Option<Board>,
     config: Arc<Config>,
     id: Option<usize>,
+    large_pattern_matcher: Arc<LargePatternMatcher>,
     playout: Arc<Playout>,
     rng: XorShiftRng,
+    scratch: Option<Board>,
     send_to_main: Sender<Response>,
     send_to_self: Option<Sender<Message>>,
+    settled: Arc<Vec<bool>>,
     small_pattern_matcher: Arc<SmallPatternMatcher>,
 }
 
 impl Worker {
 
-    pub fn new(config: &Arc<Config>, playout: &Arc<Playout>, small_pattern_matcher: &Arc<SmallPatternMatcher>, send_to_main: &Sender<Response>) -> Worker {
+    pub fn new(config: &Arc<Config>, playout: &Arc<Playout>, small_pattern_matcher: &Arc<SmallPatternMatcher>, large_pattern_matcher: &Arc<LargePatternMatcher>, send_to_main: &Sender<Response>) -> Worker {
         let rng = weak_rng();
         Worker {
             board: None,
             config: config.clone(),
             id: None,
+            large_pattern_matcher: large_pattern_matcher.clone(),
             playout: playout.clone(),
             rng: rng,
+            scratch: None,
             send_to_main: send_to_main.clone(),
             send_to_self: None,
+            settled: Arc::new(vec!()),
             small_pattern_matcher: small_pattern_matcher.clone(),
         }
     }
@@ -140,8 +158,12 @@ impl Worker {
                     check!(self.config, direct_message = r => {
                         match direct_message {
                             DirectMessage::SpinDown => { break; },
-                            DirectMessage::NewState {board, id} => {
-                                self.set_new_state(board, id);
+                            DirectMessage::NewConfig {config, playout} => {
+                                self.config = config;
+                                self.playout = playout;
+                            },
+                            DirectMessage::NewState {board, id, settled} => {
+                                self.set_new_state(board, id, settled);
                             }
                         }
                     });
@@ -163,17 +185,26 @@ impl Worker {
 
     }
 
-    fn set_new_state(&mut self, board: Board, id: usize) {
+    fn set_new_state(&mut self, board: Board, id: usize, settled: Arc<Vec<bool>>) {
         self.board = Some(board);
         self.id = Some(id);
+        self.settled = settled;
         self.respond(Answer::NewState);
     }
 
     fn run_playout(&mut self, path: Path) {
-        let mut b = path.setup_board(&self.board);
+        let mut board = self.board.take().expect("no board for run_playout");
+        path.apply(&mut board);
+        // The moves of the playout can't be taken back, so it runs on
+        // a copy that reuses the memory of the previous one.
+        let mut scratch = self.scratch.take().unwrap_or_else(|| board.clone());
+        scratch.clone_from(&board);
+        path.revert(&mut board);
+        self.board = Some(board);
         // Playout is smart enough to correctly handle the case where
         // the game is already over.
-        let playout_result = self.playout.run(&mut b, None, &mut self.rng);
+        let playout_result = self.playout.run_with_settled_points(&mut scratch, None, &self.settled, &mut self.rng);
+        self.scratch = Some(scratch);
         let answer = Answer::RunPlayout {
             path: path,
             playout_result: playout_result
@@ -181,9 +212,12 @@ impl Worker {
         self.respond(answer);
     }
 
-    fn run_prior_calculation(&self, path: Path, child_moves: Vec<Move>) {
-        let b = path.setup_board(&self.board);
-        let priors = prior::calculate(b, child_moves, &self.small_pattern_matcher, &self.config);
+    fn run_prior_calculation(&mut self, path: Path, child_moves: Vec<Move>) {
+        let mut board = self.board.take().expect("no board for run_prior_calculation");
+        path.apply(&mut board);
+        let priors = prior::calculate(&board, child_moves, &self.small_pattern_matcher, &self.large_pattern_matcher, &self.config);
+        path.revert(&mut board);
+        self.board = Some(board);
         let answer = Answer::CalculatePriors {
             path: path,
             priors: priors,
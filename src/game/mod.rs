@@ -20,13 +20,19 @@
  *                                                                      *
  ************************************************************************/
 
+use board::Black;
 use board::Board;
 use board::Color;
 use board::IllegalMove;
 use board::Move;
 use board::NoMove;
+use board::Play;
+use board::Size;
+use board::White;
 use ruleset::Ruleset;
-use self::zobrist_hash_table::ZobristHashTable;
+use ruleset::SuperKo;
+
+pub use self::zobrist_hash_table::ZobristHashTable;
 
 use std::fmt;
 use core::fmt::Display;
@@ -44,25 +50,42 @@ pub trait Info {
 #[derive(Clone)]
 pub struct Game {
     board: Board,
+    // The number of black stones played before white's first stone.
+    black_stones_before_white: u8,
     last_move: Move,
+    move_number: usize,
+    white_played: bool,
     zobrist_hash_table: ZobristHashTable,
 }
 
 impl Game {
     pub fn new(size: u8, komi: f32, ruleset: Ruleset) -> Game {
-        let new_board = Board::new(size, komi, ruleset);
+        Self::with_super_ko(Size::square(size), komi, ruleset, ruleset.super_ko())
+    }
+
+    /// Same as `new()` but the board doesn't have to be square and
+    /// it uses the given superko rule instead of the one of the
+    /// ruleset.
+    pub fn with_super_ko(size: Size, komi: f32, ruleset: Ruleset, super_ko: SuperKo) -> Game {
+        let new_board = Board::with_size(size, komi, ruleset);
 
         Game {
+            black_stones_before_white: 0,
             board: new_board,
             last_move: NoMove,
-            zobrist_hash_table: ZobristHashTable::new(size),
+            move_number: 0,
+            white_played: false,
+            zobrist_hash_table: ZobristHashTable::new(super_ko),
         }
     }
 
     pub fn with_new_state(board: Board, zobrist_hash_table: ZobristHashTable, last_move: Move) -> Game {
         Game {
+            black_stones_before_white: 0,
             board: board,
             last_move: last_move,
+            move_number: 0,
+            white_played: false,
             zobrist_hash_table: zobrist_hash_table,
        }
     }
@@ -73,7 +96,18 @@ impl Game {
         match new_board.play(m) {
             Ok(_) => {
                 let mut new_game_state = Game::with_new_state(new_board, self.zobrist_hash_table.clone(), m);
-                if !m.is_pass() && !m.is_resign() {
+                new_game_state.move_number = self.move_number + 1;
+                new_game_state.black_stones_before_white = match m {
+                    Play(Black, _, _) if !self.white_played => self.black_stones_before_white + 1,
+                    _ => self.black_stones_before_white
+                };
+                new_game_state.white_played = self.white_played || match m {
+                    Play(White, _, _) => true,
+                    _ => false
+                };
+                if m.is_pass() {
+                    new_game_state.zobrist_hash_table.record_pass(&m, &new_game_state.board);
+                } else if !m.is_resign() {
                     match new_game_state.check_and_update_super_ko(&m) {
                         Err(_) => return Err(IllegalMove::SuperKo),
                         Ok(_) => {}
@@ -97,6 +131,21 @@ impl Game {
         self.last_move
     }
 
+    /// The number of moves (including passes) played so far.
+    pub fn move_number(&self) -> usize {
+        self.move_number
+    }
+
+    /// The number of handicap stones, i.e. the black stones that were
+    /// placed before white played the first stone. A single black
+    /// stone is just the first move of an even game.
+    pub fn handicap(&self) -> u8 {
+        if self.black_stones_before_white > 1 {
+            self.black_stones_before_white
+        } else {
+            0
+        }
+    }
     pub fn next_player(&self) -> Color {
         self.board.next_player()
     }
@@ -109,7 +158,7 @@ impl Game {
         self.board.komi()
     }
 
-    pub fn size(&self) -> u8 {
+    pub fn size(&self) -> Size {
         self.board.size()
     }
 
@@ -130,12 +179,26 @@ impl Game {
     }
 
     pub fn legal_moves_without_eyes(&self) -> Vec<Move> {
-        self.board
-            .legal_moves_without_eyes()
-            .into_iter()
-            .filter(|&m| self.play(m).is_ok())
+        let moves = self.board.legal_moves_without_eyes();
+        let legal = self.check_moves(&moves);
+        moves.into_iter()
+            .zip(legal.into_iter())
+            .filter(|&(_, is_legal)| is_legal)
+            .map(|(m, _)| m)
             .collect()
     }
+
+    /// Checks for each of the moves if it can be played (including
+    /// the superko check).
+    pub fn check_moves(&self, moves: &Vec<Move>) -> Vec<bool> {
+        self.zobrist_hash_table.check_moves(&self.board, &[], moves)
+    }
+
+    /// The positions played so far. Used to carry the superko
+    /// history along the paths through the search tree.
+    pub fn zobrist_hash_table(&self) -> &ZobristHashTable {
+        &self.zobrist_hash_table
+    }
 }
 
 impl Display for Game {
@@ -146,7 +209,7 @@ impl Display for Game {
 
         // Then we print the col numbers under the board
         s.push_str(format!("{:3}", "").as_ref());
-        for col in 1..self.board.size()+1 {
+        for col in 1..self.board.size().width+1 {
             s.push_str(format!("{:<2}", col).as_ref());
         }
         s.push_str("\n");
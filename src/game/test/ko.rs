@@ -22,11 +22,17 @@
 
 #![cfg(test)]
 use board::Black;
+use board::Color;
 use board::IllegalMove;
+use board::Move;
+use board::Pass;
 use board::Play;
+use board::Size;
 use board::White;
 use game::Game;
 use ruleset::AnySizeTrompTaylor;
+use ruleset::NewZealand;
+use ruleset::SuperKo;
 use sgf::Parser;
 
 use std::path::Path;
@@ -83,3 +89,119 @@ fn not_a_super_ko2() {
         Ok(_)  => {}
     }
 }
+
+// Black fills a two point eye of white with two moves (the second
+// one is suicide) while white passes. Filling the first point again
+// doesn't capture anything, but repeats the position.
+#[test]
+fn check_moves_detects_super_ko_without_a_capture() {
+    let mut game = Game::new(9, 6.5, NewZealand);
+    let moves = [
+        Play(White, 1, 2), Play(Black, 9, 9), Play(White, 2, 2), Play(Black, 9, 8),
+        Play(White, 3, 2), Play(Black, 9, 7), Play(White, 3, 1),
+        Play(Black, 1, 1), Pass(White), Play(Black, 2, 1), Pass(White)];
+    for &m in moves.iter() {
+        game = game.play(m).unwrap();
+    }
+    assert_eq!(Err(IllegalMove::SuperKo), game.play(Play(Black, 1, 1)).map(|_| ()));
+    assert_eq!(vec!(false), game.check_moves(&vec!(Play(Black, 1, 1))));
+}
+
+#[test]
+fn check_moves_detects_super_ko() {
+    let parser = Parser::from_path(Path::new("fixtures/sgf/positional-superko.sgf")).unwrap();
+    let game   = parser.game().unwrap();
+    let moves  = vec!(Play(White, 2, 9), Play(White, 1, 1));
+    assert_eq!(vec!(false, game.play(Play(White, 1, 1)).is_ok()), game.check_moves(&moves));
+}
+
+const SUPER_KO_RULES: [SuperKo; 3] = [SuperKo::NaturalSituational, SuperKo::Positional, SuperKo::Situational];
+
+// Plays the black and white stones alternately, starting with black.
+fn setup(super_ko: SuperKo, black: Vec<(u8, u8)>, white: Vec<(u8, u8)>) -> Game {
+    let mut game = Game::with_super_ko(Size::square(9), 6.5, AnySizeTrompTaylor, super_ko);
+    for (&(bc, br), &(wc, wr)) in black.iter().zip(white.iter()) {
+        game = game.play(Play(Black, bc, br)).unwrap();
+        game = game.play(Play(White, wc, wr)).unwrap();
+    }
+    game
+}
+
+// Three kos on the rows 2, 5, and 8. In a ko marked with White black
+// can capture, in a ko marked with Black white can capture.
+fn three_kos(super_ko: SuperKo, kos: [Color; 3]) -> Game {
+    let mut black = vec!();
+    let mut white = vec!();
+    for (&ko, &row) in kos.iter().zip([2, 5, 8].iter()) {
+        black.extend(vec!((2, row), (3, row + 1), (3, row - 1)));
+        white.extend(vec!((5, row), (4, row + 1), (4, row - 1)));
+        if ko == White {
+            white.push((3, row));
+        } else {
+            black.push((4, row));
+        }
+    }
+    let mut fillers = vec!((8, 2), (8, 5), (8, 8));
+    while black.len() < white.len() {
+        black.push(fillers.remove(0));
+    }
+    setup(super_ko, black, white)
+}
+
+// Captures in the given ko (1, 2, or 3).
+fn take_ko(color: Color, ko: u8) -> Move {
+    let row = 3 * ko - 1;
+    if color == Black { Play(Black, 4, row) } else { Play(White, 3, row) }
+}
+
+// Plays all moves but the last and returns the result of the last.
+fn play_sequence(mut game: Game, moves: Vec<Move>) -> Result<Game, IllegalMove> {
+    let (last, moves) = moves.split_last().unwrap();
+    for &m in moves.iter() {
+        game = game.play(m).unwrap();
+    }
+    game.play(*last)
+}
+
+#[test]
+fn triple_ko_cycle_is_illegal_under_all_super_ko_rules() {
+    for &super_ko in SUPER_KO_RULES.iter() {
+        let game = three_kos(super_ko, [White, Black, White]);
+        let moves = vec!(
+            take_ko(Black, 1), take_ko(White, 2), take_ko(Black, 3),
+            take_ko(White, 1), take_ko(Black, 2), take_ko(White, 3));
+        assert_eq!(Some(IllegalMove::SuperKo), play_sequence(game, moves).err());
+    }
+}
+
+// The last move recreates a position that was created by white's
+// pass earlier.
+fn triple_ko_with_pass(super_ko: SuperKo) -> Result<Game, IllegalMove> {
+    let game = three_kos(super_ko, [White, White, White]);
+    let moves = vec!(
+        take_ko(Black, 1), Pass(White), take_ko(Black, 2), take_ko(White, 1),
+        take_ko(Black, 3), take_ko(White, 2), take_ko(Black, 1), take_ko(White, 3));
+    play_sequence(game, moves)
+}
+
+#[test]
+fn natural_situational_super_ko_ignores_positions_created_by_passes() {
+    assert!(triple_ko_with_pass(SuperKo::NaturalSituational).is_ok());
+    assert_eq!(Some(IllegalMove::SuperKo), triple_ko_with_pass(SuperKo::Positional).err());
+    assert_eq!(Some(IllegalMove::SuperKo), triple_ko_with_pass(SuperKo::Situational).err());
+}
+
+// Black sends two stones (A1), white captures them (C1), and black
+// returns to the original position by capturing one stone (B1), but
+// now with white to move.
+fn sending_two_returning_one(super_ko: SuperKo) -> Result<Game, IllegalMove> {
+    let game = setup(super_ko, vec!((2, 1), (4, 1), (3, 2)), vec!((1, 2), (2, 2), (9, 9)));
+    play_sequence(game, vec!(Play(Black, 1, 1), Play(White, 3, 1), Play(Black, 2, 1)))
+}
+
+#[test]
+fn sending_two_returning_one_is_only_illegal_under_positional_super_ko() {
+    assert_eq!(Some(IllegalMove::SuperKo), sending_two_returning_one(SuperKo::Positional).err());
+    assert!(sending_two_returning_one(SuperKo::Situational).is_ok());
+    assert!(sending_two_returning_one(SuperKo::NaturalSituational).is_ok());
+}
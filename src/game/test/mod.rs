@@ -61,3 +61,31 @@ fn it_should_handle_resign() {
     let res = g.play(Resign(Black));
     assert!(res.is_ok());
 }
+
+#[test]
+fn move_number_counts_all_moves() {
+    let mut g = Game::new(9, 6.5, KgsChinese);
+    g = g.play(Play(Black, 3, 3)).unwrap();
+    g = g.play(Pass(White)).unwrap();
+    assert_eq!(2, g.move_number());
+}
+
+#[test]
+fn even_games_have_no_handicap() {
+    let mut g = Game::new(9, 6.5, KgsChinese);
+    g = g.play(Play(Black, 3, 3)).unwrap();
+    g = g.play(Play(White, 7, 7)).unwrap();
+    g = g.play(Play(Black, 7, 3)).unwrap();
+    assert_eq!(0, g.handicap());
+}
+
+#[test]
+fn handicap_counts_the_black_stones_before_the_first_white_stone() {
+    let mut g = Game::new(9, 0.5, KgsChinese);
+    g = g.play(Play(Black, 3, 3)).unwrap();
+    g = g.play(Pass(White)).unwrap();
+    g = g.play(Play(Black, 7, 7)).unwrap();
+    g = g.play(Play(White, 5, 5)).unwrap();
+    g = g.play(Play(Black, 7, 3)).unwrap();
+    assert_eq!(2, g.handicap());
+}
@@ -20,72 +20,104 @@
  *                                                                      *
  ************************************************************************/
 
-use board::Black;
 use board::Board;
-use board::Coord;
-use board::Empty;
+use board::Color;
+use board::IllegalMove;
 use board::Move;
-use board::Play;
 use board::White;
+use ruleset::SuperKo;
 
 use rand::random;
 
+// The history of all positions of a game (or of a path through the
+// search tree) used to detect superko violations. The hashes of the
+// positions themselves are kept up to date by the board.
 #[derive(Clone)]
 pub struct ZobristHashTable {
-    black: Vec<u64>,
-    hashes: Vec<u64>,
-    size: u8,
-    white: Vec<u64>,
+    // The keys of all earlier positions. Depending on the superko
+    // rule they include the player to move.
+    history: Vec<u64>,
+    super_ko: SuperKo,
+    white_to_move: u64,
 }
 
 impl ZobristHashTable {
 
-    pub fn new(size: u8) -> ZobristHashTable {
-        let mut black = Vec::new();
-        let mut white = Vec::new();
-        for _ in Coord::for_board_size(size) {
-            black.push(random::<u64>());
-            white.push(random::<u64>());
-        }
+    pub fn new(super_ko: SuperKo) -> ZobristHashTable {
         ZobristHashTable {
-            black: black,
-            hashes: vec!(0),
-            size: size,
-            white: white,
+            // The empty board with black to move.
+            history: vec!(0),
+            super_ko: super_ko,
+            white_to_move: random::<u64>(),
         }
     }
 
     pub fn check_and_update_super_ko(&mut self, m: &Move, b: &Board) -> Result<(),()> {
-        let hash = self.compute_hash(m, b);
-        if self.hashes.contains(&hash) {
+        let key = self.key(b.hash(), m.color().opposite());
+        if self.history.contains(&key) {
             Err(())
         } else {
-            self.hashes.push(hash);
+            self.history.push(key);
             Ok(())
         }
     }
 
-    fn compute_hash(&self, m: &Move, b: &Board) -> u64 {
-        let mut hash = self.hashes[self.hashes.len()-1];
-        hash = self.change_hash(hash, m);
-        for coord in b.adv_stones_removed() {
-            hash = self.change_hash(hash, &Play(m.color().opposite(), coord.col, coord.row));
+    /// Records the position after a pass. Only situational superko
+    /// forbids recreating positions that were created by a pass.
+    pub fn record_pass(&mut self, m: &Move, b: &Board) {
+        if self.super_ko == SuperKo::Situational {
+            let key = self.key(b.hash(), m.color().opposite());
+            self.history.push(key);
         }
-        for coord in b.friend_stones_removed() {
-            hash = self.change_hash(hash, &Play(*m.color(), coord.col, coord.row));
+    }
+
+    /// The key of the position after the move, if it has to be
+    /// remembered. Used to carry the positions along the paths through
+    /// the search tree without copying the whole history.
+    pub fn key_after(&self, m: &Move, b: &Board) -> Option<u64> {
+        if m.is_resign() || (m.is_pass() && self.super_ko != SuperKo::Situational) {
+            None
+        } else {
+            Some(self.key(b.hash(), m.color().opposite()))
         }
-        hash
     }
 
-    fn change_hash(&self, hash: u64, m: &Move) -> u64 {
-        hash ^ self.hash_for(m)
+    // Same as check_and_update_super_ko() but doesn't record the new
+    // position. The move must already be played on the board. `path`
+    // are the keys of the positions after the game's history.
+    pub fn is_super_ko(&self, m: &Move, b: &Board, path: &[u64]) -> bool {
+        let key = self.key(b.hash(), m.color().opposite());
+        self.history.contains(&key) || path.contains(&key)
+    }
+
+    /// Checks for each of the moves if it can be played on the board
+    /// (including the superko check). `path` are the keys of the
+    /// positions reached after the game's history (see `key_after()`).
+    pub fn check_moves(&self, board: &Board, path: &[u64], moves: &Vec<Move>) -> Vec<bool> {
+        let mut board = board.clone();
+        moves.iter().map(|&m| self.check_move(&mut board, path, m).is_ok()).collect()
+    }
+
+    fn check_move(&self, board: &mut Board, path: &[u64], m: Move) -> Result<(), IllegalMove> {
+        try!(board.is_legal(m));
+        if m.is_pass() || m.is_resign() {
+            return Ok(());
+        }
+        board.make_legal_move(m);
+        let super_ko = self.is_super_ko(&m, board, path);
+        board.unmake_move();
+        if super_ko {
+            Err(IllegalMove::SuperKo)
+        } else {
+            Ok(())
+        }
     }
 
-    fn hash_for(&self, m: &Move) -> u64 {
-        match *m.color() {
-            Empty => 0,
-            Black => self.black[m.coord().to_index(self.size)],
-            White => self.white[m.coord().to_index(self.size)]
+    fn key(&self, hash: u64, to_move: Color) -> u64 {
+        match self.super_ko {
+            SuperKo::Positional => hash,
+            _ if to_move == White => hash ^ self.white_to_move,
+            _ => hash
         }
     }
 }
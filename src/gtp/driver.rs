@@ -21,6 +21,7 @@
  *                                                                      *
  ************************************************************************/
 
+use board::Size;
 use config::Config;
 use engine::Engine;
 use super::GTPInterpreter;
@@ -57,22 +58,16 @@ impl Driver {
     }
 }
 
-pub struct BenchmarkDriver;
-
-impl BenchmarkDriver {
-    pub fn new(config: Arc<Config>, engine: Engine, board_size: usize) {
-        let mut interpreter = GTPInterpreter::new(config, engine);
-        let time_settings = match board_size {
-            9 => 300,
-            13 => 600,
-            15 => 1020,
-            17 => 1440,
-            19 => 1800,
-            _ => unreachable!("board size not supported"),
-        };
-        interpreter.read(&format!("boardsize {}\n", board_size)).unwrap();
-        interpreter.read("clear_board\n").unwrap();
-        interpreter.read(&format!("time_settings {} 0 0 \n", time_settings)).unwrap();
-        interpreter.read("genmove b\n").unwrap();
+/// The main time (in seconds) of a game on the given board size. The
+/// usual time limits for the common sizes and about five seconds per
+/// point for all others.
+pub fn main_time(size: Size) -> usize {
+    match (size.width, size.height) {
+        (9, 9) => 300,
+        (13, 13) => 600,
+        (15, 15) => 1020,
+        (17, 17) => 1440,
+        (19, 19) => 1800,
+        _ => 5 * size.points(),
     }
 }
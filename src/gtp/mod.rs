@@ -22,17 +22,30 @@
  ************************************************************************/
 
 use board::Color;
+use board::Coord;
+use board::GTPError;
+use board::IllegalMove;
 use board::Move;
+use board::Size;
 use config::Config;
 use engine::Engine;
 use engine::EngineController;
 use game::Game;
 use ruleset::Ruleset;
 use sgf::parser::Parser;
+use tactics;
+use tactics::Reader;
+use tsumego::Problem;
+use tsumego::Solver;
 use timer::Timer;
+use tree_dump::DumpFormat;
+use tree_dump::TreeDump;
 use version;
 
 use regex::Regex;
+use std::cmp;
+use std::fs::File;
+use std::io::Write;
 use std::path::Path;
 use std::sync::Arc;
 use time::precise_time_ns;
@@ -56,7 +69,7 @@ impl<'a> GTPInterpreter<'a> {
     pub fn new(config: Arc<Config>, engine: Engine) -> GTPInterpreter<'a> {
         let controller = EngineController::new(config.clone(), engine);
         let komi = 6.5;
-        let boardsize = 19;
+        let boardsize = Size::square(19);
         let commands = vec![
             "boardsize",
             "clear_board",
@@ -65,7 +78,13 @@ impl<'a> GTPInterpreter<'a> {
             "genmove",
             "gogui-analyze_commands",
             "imrscl-donplayouts",
+            "imrscl-dump_tree",
+            "imrscl-expected_score",
             "imrscl-ownership",
+            "imrscl-params",
+            "imrscl-set",
+            "imrscl-tactics",
+            "imrscl-tsumego",
             "imrscl-uct_gfx",
             "kgs-genmove_cleanup",
             "known_command",
@@ -76,23 +95,26 @@ impl<'a> GTPInterpreter<'a> {
             "play",
             "protocol_version",
             "quit",
+            "rectangular_boardsize",
             "reg_genmove",
             "showboard",
             "time_left",
             "time_settings",
             "version",
             ];
-        GTPInterpreter {
+        let mut interpreter = GTPInterpreter {
             byo_stones: 0,
             byo_time: 0,
             commands: commands,
             config: config.clone(),
             controller: controller,
-            game: Game::new(boardsize, komi, config.ruleset),
+            game: Game::with_super_ko(boardsize, komi, config.ruleset, config.super_ko),
             main_time: 5,
             running: true,
             timer: Timer::new(config),
-        }
+        };
+        interpreter.use_config_for_size();
+        interpreter
     }
 
     pub fn quit(&mut self) {
@@ -107,7 +129,7 @@ impl<'a> GTPInterpreter<'a> {
         self.config.ruleset
     }
 
-    pub fn boardsize(&self) -> u8 {
+    pub fn boardsize(&self) -> Size {
         self.game.size()
     }
 
@@ -116,7 +138,7 @@ impl<'a> GTPInterpreter<'a> {
         if preprocessed.len() == 0 {
             return Err("empty command".to_string())
         };
-        let command: Vec<&str> = preprocessed.split(' ').collect();
+        let command: Vec<&str> = preprocessed.split_whitespace().collect();
         if !self.commands.contains(&command[0]) {
             return Err("unknown command".to_string());
         }
@@ -129,7 +151,13 @@ impl<'a> GTPInterpreter<'a> {
             "genmove" => self.execute_genmove(arguments),
             "gogui-analyze_commands" => self.execute_gogui_analyze_commands(arguments),
             "imrscl-donplayouts" => self.execute_imrscl_donplayouts(arguments),
+            "imrscl-dump_tree" => self.execute_imrscl_dump_tree(arguments),
+            "imrscl-expected_score" => self.execute_imrscl_expected_score(arguments),
             "imrscl-ownership" => self.execute_imrscl_ownership(arguments),
+            "imrscl-params" => self.execute_imrscl_params(arguments),
+            "imrscl-set" => self.execute_imrscl_set(arguments),
+            "imrscl-tactics" => self.execute_imrscl_tactics(arguments),
+            "imrscl-tsumego" => self.execute_imrscl_tsumego(arguments),
             "imrscl-uct_gfx" => self.execute_uct_gfx(arguments),
             "kgs-genmove_cleanup" => self.execute_kgs_genmove_cleanup(arguments),
             "known_command" => self.execute_known_command(arguments),
@@ -140,6 +168,7 @@ impl<'a> GTPInterpreter<'a> {
             "play" => self.execute_play(arguments),
             "protocol_version" => self.execute_protocol_version(arguments),
             "quit" => self.execute_quit(arguments),
+            "rectangular_boardsize" => self.execute_rectangular_boardsize(arguments),
             "reg_genmove" => self.execute_reg_genmove(arguments),
             "showboard" => self.execute_showboard(arguments),
             "time_left" => self.execute_time_left(arguments),
@@ -176,20 +205,39 @@ impl<'a> GTPInterpreter<'a> {
     fn execute_boardsize(&mut self, arguments: &[&str]) -> Result<String, String> {
         match arguments.get(0) {
             Some(comm) => match comm.parse::<u8>() {
-                Ok(size) => {
-                    self.game = Game::new(size, self.komi(), self.ruleset());
-                    Ok("".to_string())
-                },
-                Err(e) => Err(format!("{:?}", e))
+                Ok(size) => self.set_boardsize(size, size),
+                Err(_) => Err("syntax error".to_string())
             },
             None => Err("missing argument".to_string())
         }
     }
 
+    fn execute_rectangular_boardsize(&mut self, arguments: &[&str]) -> Result<String, String> {
+        if arguments.len() < 2 {
+            return Err("missing argument".to_string());
+        }
+        match (arguments[0].parse::<u8>(), arguments[1].parse::<u8>()) {
+            (Ok(width), Ok(height)) => self.set_boardsize(width, height),
+            _ => Err("syntax error".to_string())
+        }
+    }
+
+    fn set_boardsize(&mut self, width: u8, height: u8) -> Result<String, String> {
+        match Size::new(width, height) {
+            Ok(size) => {
+                self.game = Game::with_super_ko(size, self.komi(), self.ruleset(), self.config.super_ko);
+                self.use_config_for_size();
+                Ok("".to_string())
+            },
+            Err(_) => Err("unacceptable size".to_string())
+        }
+    }
+
     fn execute_clear_board(&mut self, _: &[&str]) -> Result<String, String> {
         let size = self.boardsize();
         let komi = self.komi();
-        self.game = Game::new(size, komi, self.ruleset());
+        self.game = Game::with_super_ko(size, komi, self.ruleset(), self.config.super_ko);
+        self.use_config_for_size();
         self.timer.setup(self.main_time, self.byo_time, self.byo_stones);
         self.controller.reset(size, komi);
         Ok("".to_string())
@@ -199,11 +247,11 @@ impl<'a> GTPInterpreter<'a> {
         match arguments.get(0) {
             Some(comm) =>
                 match comm.parse::<f32>() {
-                    Ok(komi) => {
+                    Ok(komi) if komi.is_finite() => {
                         self.game.set_komi(komi);
                         Ok("".to_string())
                     },
-                    Err(e) => Err(format!("{:?}", e))
+                    _ => Err("syntax error".to_string())
                 },
             None => Err("missing argument".to_string())
         }
@@ -212,18 +260,19 @@ impl<'a> GTPInterpreter<'a> {
     fn execute_reg_genmove(&mut self, arguments: &[&str]) -> Result<String, String> {
         match arguments.get(0) {
             Some(c) => {
+                let color = try!(Self::parse_color(c));
                 let started_at = precise_time_ns();
                 self.timer.start(&self.game);
-                let color = Color::from_gtp(c);
                 let (m, playouts) = self.controller.genmove(color, &self.game, &self.timer);
-                let response = match self.game.play(m) {
-                    Ok(_) => {
-                        self.timer.reset();
-                        Ok(m.to_gtp())
+                let response = match m.to_gtp() {
+                    Ok(vertex) => match self.game.play(m) {
+                        Ok(_) => {
+                            self.timer.reset();
+                            Ok(vertex)
+                        },
+                        Err(e) => Err(Self::error_response(GTPError::IllegalMove(e)))
                     },
-                    Err(e) => {
-                        Err(format!("Illegal move {:?} ({:?})", m, e))
-                    }
+                    Err(e) => Err(format!("{}", e))
                 };
                 Self::measure_playout_speed(started_at, playouts, &self.config);
                 response
@@ -235,19 +284,20 @@ impl<'a> GTPInterpreter<'a> {
     fn execute_genmove(&mut self, arguments: &[&str]) -> Result<String, String> {
         match arguments.get(0) {
             Some(comm) => {
+                let color = try!(Self::parse_color(comm));
                 let started_at = precise_time_ns();
                 self.timer.start(&self.game);
-                let color = Color::from_gtp(comm);
                 let (m, playouts) = self.controller.genmove(color, &self.game, &self.timer);
-                let response = match self.game.play(m) {
-                    Ok(g) => {
-                        self.game = g;
-                        self.timer.stop();
-                        Ok(m.to_gtp())
+                let response = match m.to_gtp() {
+                    Ok(vertex) => match self.game.play(m) {
+                        Ok(g) => {
+                            self.game = g;
+                            self.timer.stop();
+                            Ok(vertex)
+                        },
+                        Err(e) => Err(Self::error_response(GTPError::IllegalMove(e)))
                     },
-                    Err(e) => {
-                        Err(format!("Illegal move {:?} ({:?})", m, e))
-                    }
+                    Err(e) => Err(format!("{}", e))
                 };
                 Self::measure_playout_speed(started_at, playouts, &self.config);
                 response
@@ -259,20 +309,21 @@ impl<'a> GTPInterpreter<'a> {
     fn execute_kgs_genmove_cleanup(&mut self, arguments: &[&str]) -> Result<String, String> {
         match arguments.get(0) {
             Some(comm) => {
+                let color = try!(Self::parse_color(comm));
                 self.game.reset_game_over();
                 let started_at = precise_time_ns();
                 self.timer.start(&self.game);
-                let color = Color::from_gtp(comm);
                 let (m, playouts) = self.controller.genmove_cleanup(color, &self.game, &self.timer);
-                let response = match self.game.play(m) {
-                    Ok(g) => {
-                        self.game = g;
-                        self.timer.stop();
-                        Ok(m.to_gtp())
+                let response = match m.to_gtp() {
+                    Ok(vertex) => match self.game.play(m) {
+                        Ok(g) => {
+                            self.game = g;
+                            self.timer.stop();
+                            Ok(vertex)
+                        },
+                        Err(e) => Err(Self::error_response(GTPError::IllegalMove(e)))
                     },
-                    Err(e) => {
-                        Err(format!("Illegal move {:?} ({:?})", m, e))
-                    }
+                    Err(e) => Err(format!("{}", e))
                 };
                 Self::measure_playout_speed(started_at, playouts, &self.config);
                 response
@@ -289,28 +340,131 @@ impl<'a> GTPInterpreter<'a> {
                         self.controller.donplayouts(&self.game, playouts);
                         Ok("".to_string())
                     },
-                    Err(e) => Err(format!("{:?}", e))
+                    Err(_) => Err("syntax error".to_string())
                 }
             }
             None => Err("missing argument".to_string()),
         }
     }
 
+    // Writes the current search tree to the file (as DOT if it ends
+    // with .dot or .gv, otherwise as JSON). The optional depth and
+    // minimum number of playouts default to the ones of the config.
+    fn execute_imrscl_dump_tree(&mut self, arguments: &[&str]) -> Result<String, String> {
+        let filename = match arguments.get(0) {
+            Some(filename) => filename,
+            None => return Err("missing argument".to_string())
+        };
+        let mut numbers = vec!(self.config.tree_dump.max_depth(), self.config.tree_dump.min_playouts());
+        for (i, argument) in arguments[1..].iter().take(2).enumerate() {
+            numbers[i] = try!(argument.parse().map_err(|_| "syntax error".to_string()));
+        }
+        let format = DumpFormat::from_filename(filename).unwrap_or(self.config.tree_dump.format());
+        let dump = TreeDump::new(format, numbers[0], numbers[1]);
+        try!(File::create(filename)
+             .and_then(|mut file| file.write_all(self.controller.dump_tree(&dump).as_bytes()))
+             .map_err(|e| format!("can't write {}: {}", filename, e)));
+        Ok("".to_string())
+    }
+
+    fn execute_imrscl_expected_score(&mut self, _: &[&str]) -> Result<String, String> {
+        Ok(self.controller.expected_score())
+    }
+
     fn execute_imrscl_ownership(&mut self, _: &[&str]) -> Result<String, String> {
         let stats = self.controller.ownership_statistics();
         Ok(stats)
     }
 
+    // Without arguments it lists the parameters in the format of the
+    // GoGui parameter dialog, which then calls it with the name and
+    // the new value of a parameter.
+    fn execute_imrscl_params(&mut self, arguments: &[&str]) -> Result<String, String> {
+        if arguments.is_empty() {
+            let lines: Vec<String> = self.config.params().iter()
+                .map(|&(ref name, ref value)| format!("[string] {} {}", name, value))
+                .collect();
+            Ok(lines.join("\n"))
+        } else {
+            self.execute_imrscl_set(arguments)
+        }
+    }
+
+    fn execute_imrscl_set(&mut self, arguments: &[&str]) -> Result<String, String> {
+        match (arguments.get(0), arguments.get(1)) {
+            (Some(name), Some(value)) => {
+                let config = try!(self.config.with_param(name, value));
+                self.set_config(config);
+                Ok("".to_string())
+            },
+            _ => Err("missing argument(s)".to_string())
+        }
+    }
+
+    fn set_config(&mut self, config: Config) {
+        let config = Arc::new(config);
+        self.config = config.clone();
+        self.controller.set_config(config.clone());
+        self.timer.set_config(config);
+    }
+
+    // Switches to the settings for the size of the current game (see
+    // `Config::for_size()`).
+    fn use_config_for_size(&mut self) {
+        if let Some(config) = self.config.for_size(self.game.size()) {
+            info!(self.config, "Using the config for size {}", self.game.size());
+            self.set_config(config);
+        }
+    }
+
+    fn execute_imrscl_tactics(&mut self, _: &[&str]) -> Result<String, String> {
+        let mut reader = Reader::new(&self.config.tactics);
+        let verdicts = tactics::analyze(&self.game.board(), &mut reader);
+        let lines: Vec<String> = verdicts.iter().map(|v| v.to_string()).collect();
+        Ok(lines.join("\n"))
+    }
+
+    // The first argument is the target stone, the others are the
+    // points of the region, either single vertices or rectangles like
+    // A1:C3.
+    fn execute_imrscl_tsumego(&mut self, arguments: &[&str]) -> Result<String, String> {
+        let target = match arguments.get(0) {
+            Some(vertex) => try!(Coord::from_gtp(vertex).map_err(Self::error_response)),
+            None => return Err("missing argument".to_string())
+        };
+        let mut region = vec!();
+        for argument in arguments[1..].iter() {
+            let mut corners = argument.split(':');
+            let from = try!(Coord::from_gtp(corners.next().unwrap()).map_err(Self::error_response));
+            let to = match corners.next() {
+                Some(vertex) => try!(Coord::from_gtp(vertex).map_err(Self::error_response)),
+                None => from
+            };
+            for col in cmp::min(from.col, to.col)..cmp::max(from.col, to.col)+1 {
+                for row in cmp::min(from.row, to.row)..cmp::max(from.row, to.row)+1 {
+                    region.push(Coord::new(col, row));
+                }
+            }
+        }
+        let board = self.game.board();
+        if region.iter().any(|c| !c.is_inside(board.size())) {
+            return Err("syntax error".to_string());
+        }
+        let problem = try!(Problem::new(board, target, region));
+        let solution = Solver::new(&self.config.tsumego).solve(&problem);
+        Ok(solution.to_string())
+    }
+
     fn execute_play(&mut self, arguments: &[&str]) -> Result<String, String> {
         match arguments.get(1) {
             Some(second) => {
-                let m = Move::from_gtp(arguments[0], second);
+                let m = try!(Move::from_gtp(arguments[0], second).map_err(Self::error_response));
                 match self.game.play(m) {
                     Ok(g) => {
                         self.game = g;
                         Ok("".to_string())
                     },
-                    Err(e) => Err(format!("Illegal move {:?} ({:?})", m, e))
+                    Err(e) => Err(Self::error_response(GTPError::IllegalMove(e)))
                 }
             },
             None => Err("missing argument".to_string())
@@ -345,15 +499,15 @@ impl<'a> GTPInterpreter<'a> {
         match arguments.get(2) {
             Some(third) => {
             	//command[1] and command[2] should be there
-                match (arguments[0].parse::<i64>(), arguments[1].parse::<i64>(), third.parse::<i32>()) {
-                    (Ok(main), Ok(byo), Ok(stones)) => {
+                match (Self::parse_seconds(arguments[0]), Self::parse_seconds(arguments[1]), third.parse::<i32>()) {
+                    (Some(main), Some(byo), Ok(stones)) if stones >= 0 => {
                         self.main_time = main;
                         self.byo_time = byo;
                         self.byo_stones = stones;
                         self.timer.setup(main, byo, stones);
                         Ok("".to_string())
                     }
-                    _ => Err("error parsing time_settings".to_string())
+                    _ => Err("syntax error".to_string())
                 }
             },
             None => Err("missing argument(s)".to_string())
@@ -368,12 +522,13 @@ impl<'a> GTPInterpreter<'a> {
     fn execute_time_left(&mut self, arguments: &[&str]) -> Result<String, String> {
         match arguments.get(2) {
             Some(third) => {
-                match (arguments[1].parse::<i64>(), third.parse::<i32>()) {
-                    (Ok(time), Ok(stones)) => {
+                try!(Self::parse_color(arguments[0]));
+                match (Self::parse_seconds(arguments[1]), third.parse::<i32>()) {
+                    (Some(time), Ok(stones)) if stones >= 0 => {
                         self.timer.update(time, stones);
                         Ok("".to_string())
                     },
-                    _ => Err("error parsing time_left".to_string())
+                    _ => Err("syntax error".to_string())
                 }
             },
             None => Err("missing argument(s)".to_string())
@@ -391,6 +546,7 @@ impl<'a> GTPInterpreter<'a> {
                         match game {
                             Ok(g) => {
                                 self.game = g;
+                                self.use_config_for_size();
                                 Ok("".to_string())
                             },
                             Err(_) => Err("cannot load file".to_string())
@@ -408,11 +564,38 @@ impl<'a> GTPInterpreter<'a> {
             "dboard/Ownership/imrscl-ownership",
             "plist/Final Status List Dead/final_status_list dead",
             "plist/Final Status List Alive/final_status_list alive",
-            "gfx/Uct Gfx/imrscl-uct_gfx"
+            "string/Tactics/imrscl-tactics",
+            "gfx/Uct Gfx/imrscl-uct_gfx",
+            "string/Expected Score/imrscl-expected_score",
+            "param/Parameters/imrscl-params"
                 ];
         Ok(analyze_commands[1..].iter().fold(analyze_commands[0].to_string(), |acc, &el| format!("{}\n{}", acc, el)))
     }
 
+    fn parse_color(gtp_color: &str) -> Result<Color, String> {
+        Color::from_gtp(gtp_color).map_err(Self::error_response)
+    }
+
+    // Time in seconds that can be converted to milliseconds without
+    // overflowing.
+    fn parse_seconds(s: &str) -> Option<i64> {
+        match s.parse::<i64>() {
+            Ok(seconds) if seconds >= 0 && seconds.checked_mul(1000).is_some() => Some(seconds),
+            _ => None
+        }
+    }
+
+    // The conventional GTP error messages. The details are left out
+    // as they're of no use to the controller.
+    fn error_response(error: GTPError) -> String {
+        match error {
+            GTPError::IllegalMove(IllegalMove::PlayOutOfBoard) => "invalid vertex".to_string(),
+            GTPError::IllegalMove(_) => "illegal move".to_string(),
+            GTPError::InvalidColor(_) => "invalid color".to_string(),
+            _ => "syntax error".to_string(),
+        }
+    }
+
     fn measure_playout_speed(started_at: u64, playouts: usize, config: &Arc<Config>) {
         let finished_at = precise_time_ns();
         let duration_ns = finished_at - started_at;
@@ -420,7 +603,7 @@ impl<'a> GTPInterpreter<'a> {
         let pps = (playouts as f64) / duration_s;
         let threads = config.threads;
         let ptps = pps / (threads as f64);
-        config.log(format!("{}pps ({}pps per thread)", pps.round() as usize, ptps.round() as usize));
+        info!(config, "{}pps ({}pps per thread)", pps.round() as usize, ptps.round() as usize);
     }
 
     fn preprocess(&self, input: &str) -> String {
@@ -23,14 +23,27 @@
 
 #![cfg(test)]
 
+pub use book::Book;
 pub use config::Config;
 pub use engine::Engine;
+pub use patterns::LargePatternMatcher;
 pub use patterns::SmallPatternMatcher;
+pub use board::Size;
 pub use ruleset::CGOS;
 pub use ruleset::KgsChinese;
 pub use super::GTPInterpreter;
+pub use tree_dump::DumpFormat;
+pub use tree_dump::TreeDump;
 
 pub use hamcrest::prelude::*;
+pub use rand::Rng;
+pub use rand::SeedableRng;
+pub use rand::XorShiftRng;
+pub use std::env::temp_dir;
+pub use std::panic;
+pub use std::panic::AssertUnwindSafe;
+pub use std::fs::File;
+pub use std::io::Read;
 pub use std::sync::Arc;
 
 pub fn err(s: &'static str) -> Result<String, String> {
@@ -50,7 +63,9 @@ describe! interpreter {
             c.ruleset = CGOS;
             let config = Arc::new(c);
             let small_pattern_matcher = Arc::new(SmallPatternMatcher::new());
-            let engine = Engine::new(config.clone(), small_pattern_matcher);
+            let large_pattern_matcher = Arc::new(LargePatternMatcher::new());
+            let book = Arc::new(Book::new(Size::square(19)));
+            let engine = Engine::new(config.clone(), small_pattern_matcher, large_pattern_matcher, book);
             let mut interpreter = GTPInterpreter::new(config.clone(), engine);
         }
 
@@ -80,6 +95,16 @@ describe! interpreter {
                 assert_that!(response, is(equal_to(err("missing argument(s)"))));
             }
 
+            it "invalid color" {
+                let response = interpreter.read("time_left x 30 0");
+                assert_that!(response, is(equal_to(err("invalid color"))));
+            }
+
+            it "negative time" {
+                let response = interpreter.read("time_left b -30 0");
+                assert_that!(response, is(equal_to(err("syntax error"))));
+            }
+
             it "sets the main time" {
                 let response = interpreter.read("time_left b 30 0");
                 assert_that!(response, is(equal_to(ok(""))));
@@ -126,6 +151,28 @@ describe! interpreter {
                 assert_that!(interpreter.game.board().vacant_point_count(), is(equal_to(360)));
             }
 
+            it "invalid color" {
+                let response = interpreter.read("play x a1\n");
+                assert_that!(response, is(equal_to(err("invalid color"))));
+            }
+
+            it "invalid vertex" {
+                let response = interpreter.read("play b i1\n");
+                assert_that!(response, is(equal_to(err("syntax error"))));
+            }
+
+            it "occupied vertex" {
+                interpreter.read("play b a1\n").unwrap();
+                let response = interpreter.read("play w a1\n");
+                assert_that!(response, is(equal_to(err("illegal move"))));
+            }
+
+            it "vertex outside of the board" {
+                interpreter.read("boardsize 9\n").unwrap();
+                let response = interpreter.read("play b k10\n");
+                assert_that!(response, is(equal_to(err("invalid vertex"))));
+            }
+
         }
 
         describe! genmove {
@@ -140,6 +187,11 @@ describe! interpreter {
                 assert!(response.is_ok());
             }
 
+            it "invalid color" {
+                let response = interpreter.read("genmove red\n");
+                assert_that!(response, is(equal_to(err("invalid color"))));
+            }
+
         }
 
         describe! kgs {
@@ -175,6 +227,11 @@ describe! interpreter {
                 assert_that!(response, is(equal_to(err("missing argument"))));
             }
 
+            it "rejects komi that isn't a number" {
+                let response = interpreter.read("komi NaN\n");
+                assert_that!(response, is(equal_to(err("syntax error"))));
+            }
+
             it "sets the komi" {
                 let response = interpreter.read("komi 10\n");
                 assert_that!(response, is(equal_to(ok(""))));
@@ -191,10 +248,31 @@ describe! interpreter {
             }
 
             it "sets the correct size" {
-                assert_that!(interpreter.game.size(), is(equal_to(19)));
+                assert_that!(interpreter.game.size(), is(equal_to(Size::square(19))));
                 let response = interpreter.read("boardsize 9\n");
                 assert_that!(response, is(equal_to(ok(""))));
-                assert_that!(interpreter.game.size(), is(equal_to(9)));
+                assert_that!(interpreter.game.size(), is(equal_to(Size::square(9))));
+            }
+
+            it "accepts sizes up to 25" {
+                let response = interpreter.read("boardsize 25\n");
+                assert_that!(response, is(equal_to(ok(""))));
+                assert_that!(interpreter.game.size(), is(equal_to(Size::square(25))));
+                let response = interpreter.read("play b z25\n");
+                assert_that!(response, is(equal_to(ok(""))));
+            }
+
+            it "rejects sizes that aren't supported" {
+                let response = interpreter.read("boardsize 26\n");
+                assert_that!(response, is(equal_to(err("unacceptable size"))));
+                let response = interpreter.read("boardsize 0\n");
+                assert_that!(response, is(equal_to(err("unacceptable size"))));
+                assert_that!(interpreter.game.size(), is(equal_to(Size::square(19))));
+            }
+
+            it "rejects sizes that aren't numbers" {
+                let response = interpreter.read("boardsize nine\n");
+                assert_that!(response, is(equal_to(err("syntax error"))));
             }
 
             it "boardsize resets the board" {
@@ -205,6 +283,32 @@ describe! interpreter {
 
         }
 
+        describe! rectangular_boardsize {
+
+            it "needs two arguments" {
+                let response = interpreter.read("rectangular_boardsize 9\n");
+                assert_that!(response, is(equal_to(err("missing argument"))));
+            }
+
+            it "sets the width and the height" {
+                let response = interpreter.read("rectangular_boardsize 19 9\n");
+                assert_that!(response, is(equal_to(ok(""))));
+                assert_that!(interpreter.game.size(), is(equal_to(Size::new(19, 9).unwrap())));
+                let response = interpreter.read("play b t9\n");
+                assert_that!(response, is(equal_to(ok(""))));
+                let response = interpreter.read("play w a10\n");
+                assert_that!(response, is(equal_to(err("invalid vertex"))));
+            }
+
+            it "rejects sizes that aren't supported" {
+                let response = interpreter.read("rectangular_boardsize 9 26\n");
+                assert_that!(response, is(equal_to(err("unacceptable size"))));
+                let response = interpreter.read("rectangular_boardsize 9 x\n");
+                assert_that!(response, is(equal_to(err("syntax error"))));
+            }
+
+        }
+
         describe! known_command {
 
             it "one argument" {
@@ -228,7 +332,7 @@ describe! interpreter {
 
             it "no newline at end" {
                 let response = interpreter.read("list_commands\n");
-                let expected = "boardsize\nclear_board\nfinal_score\nfinal_status_list\ngenmove\ngogui-analyze_commands\nimrscl-donplayouts\nimrscl-ownership\nimrscl-uct_gfx\nkgs-genmove_cleanup\nknown_command\nkomi\nlist_commands\nloadsgf\nname\nplay\nprotocol_version\nquit\nreg_genmove\nshowboard\ntime_left\ntime_settings\nversion";
+                let expected = "boardsize\nclear_board\nfinal_score\nfinal_status_list\ngenmove\ngogui-analyze_commands\nimrscl-donplayouts\nimrscl-dump_tree\nimrscl-expected_score\nimrscl-ownership\nimrscl-params\nimrscl-set\nimrscl-tactics\nimrscl-tsumego\nimrscl-uct_gfx\nkgs-genmove_cleanup\nknown_command\nkomi\nlist_commands\nloadsgf\nname\nplay\nprotocol_version\nquit\nrectangular_boardsize\nreg_genmove\nshowboard\ntime_left\ntime_settings\nversion";
                 assert_that!(response, is(equal_to(ok(expected))));
             }
 
@@ -342,6 +446,41 @@ describe! interpreter {
             }
         }
 
+        describe! fuzzing {
+
+            it "never panics on random command lines" {
+                // Commands that start a search (including the
+                // tsumego solver) are left out as they would make the
+                // test take much too long.
+                let commands = ["boardsize", "clear_board", "gogui-analyze_commands",
+                                "imrscl-expected_score", "imrscl-ownership", "imrscl-params", "imrscl-set", "imrscl-tactics", "imrscl-uct_gfx",
+                                "known_command", "komi", "list_commands", "loadsgf", "name", "play",
+                                "protocol_version", "rectangular_boardsize", "showboard", "time_left",
+                                "time_settings", "version", "unknown", ""];
+                let arguments = ["b", "W", "white", "x", "", "a1", "T19", "t20", "i5",
+                                 "z1", "pass", "PASS", "resign", "0", "1", "2", "9",
+                                 "19", "-1", "255", "256", "99999999999999999999",
+                                 "1.5", "-0.5", "inf", "NaN", "\u{e4}", "#", "\t",
+                                 "fixtures/sgf/twomoves.sgf"];
+                let mut rng = XorShiftRng::from_seed([1, 2, 3, 4]);
+                interpreter.read("boardsize 5\n").unwrap();
+                for _ in 0..5000 {
+                    let mut line = commands[rng.gen::<usize>() % commands.len()].to_string();
+                    for _ in 0..rng.gen::<usize>() % 4 {
+                        line.push(' ');
+                        line.push_str(arguments[rng.gen::<usize>() % arguments.len()]);
+                    }
+                    if rng.gen::<usize>() % 10 == 0 {
+                        let random: String = rng.gen_ascii_chars().take(5).collect();
+                        line.push_str(&random);
+                    }
+                    let result = panic::catch_unwind(AssertUnwindSafe(|| { let _ = interpreter.read(&line); }));
+                    assert!(result.is_ok(), "panicked on {:?}", line);
+                }
+            }
+
+        }
+
         describe! final_status_list {
 
             before_each {
@@ -405,7 +544,7 @@ describe! interpreter {
             describe! analyze_commands {
 
                 it "returns the supported analyze commands" {
-                    let expected = "dboard/Ownership/imrscl-ownership\nplist/Final Status List Dead/final_status_list dead\nplist/Final Status List Alive/final_status_list alive\ngfx/Uct Gfx/imrscl-uct_gfx";
+                    let expected = "dboard/Ownership/imrscl-ownership\nplist/Final Status List Dead/final_status_list dead\nplist/Final Status List Alive/final_status_list alive\nstring/Tactics/imrscl-tactics\ngfx/Uct Gfx/imrscl-uct_gfx\nstring/Expected Score/imrscl-expected_score\nparam/Parameters/imrscl-params";
                     let response = interpreter.read("gogui-analyze_commands\n");
                     assert_that!(response, is(equal_to(ok(expected))));
                 }
@@ -428,6 +567,181 @@ describe! interpreter {
                 }
             }
 
+            describe! expected_score {
+
+                it "returns nothing before a search" {
+                    let response = interpreter.read("imrscl-expected_score\n");
+                    assert_that!(response, is(equal_to(ok(""))));
+                }
+
+                it "returns vertex, score, deviation, and playouts for every searched move" {
+                    interpreter.read("boardsize 9\n").unwrap();
+                    interpreter.read("clear_board\n").unwrap();
+                    interpreter.read("imrscl-donplayouts 100\n").unwrap();
+                    let response = interpreter.read("imrscl-expected_score\n").unwrap();
+                    assert!(response.len() > 0);
+                    for line in response.lines() {
+                        let fields: Vec<&str> = line.split_whitespace().collect();
+                        assert_eq!(4, fields.len());
+                        assert!(fields[1].parse::<f32>().is_ok());
+                        assert!(fields[2].parse::<f32>().unwrap() >= 0.0);
+                        assert!(fields[3].parse::<usize>().unwrap() > 0);
+                    }
+                }
+            }
+
+            describe! dump_tree {
+
+                it "writes the search tree as json or dot" {
+                    interpreter.read("boardsize 9\n").unwrap();
+                    interpreter.read("clear_board\n").unwrap();
+                    interpreter.read("imrscl-donplayouts 100\n").unwrap();
+                    let json = temp_dir().join("iomrascalai-tree.json");
+                    let response = interpreter.read(&format!("imrscl-dump_tree {} 1 1\n", json.display()));
+                    assert_that!(response, is(equal_to(ok(""))));
+                    let mut contents = String::new();
+                    File::open(&json).unwrap().read_to_string(&mut contents).unwrap();
+                    assert!(contents.starts_with("{\"move\":null,"));
+                    let dot = temp_dir().join("iomrascalai-tree.dot");
+                    interpreter.read(&format!("imrscl-dump_tree {}\n", dot.display())).unwrap();
+                    let mut contents = String::new();
+                    File::open(&dot).unwrap().read_to_string(&mut contents).unwrap();
+                    assert!(contents.starts_with("digraph tree {"));
+                }
+
+                it "uses the configured format if the extension doesn't determine it" {
+                    let mut c = Config::test_config();
+                    c.tree_dump = TreeDump::new(DumpFormat::Dot, 3, 10);
+                    let dot_config = Arc::new(c);
+                    let book = Arc::new(Book::new(Size::square(19)));
+                    let engine = Engine::new(dot_config.clone(), Arc::new(SmallPatternMatcher::new()), Arc::new(LargePatternMatcher::new()), book);
+                    let mut dot_interpreter = GTPInterpreter::new(dot_config, engine);
+                    let txt = temp_dir().join("iomrascalai-tree.txt");
+                    let command = format!("imrscl-dump_tree {}\n", txt.display());
+                    for (interpreter, start) in vec!((&mut interpreter, "{\"move\":null,"), (&mut dot_interpreter, "digraph tree {")) {
+                        interpreter.read("boardsize 9\n").unwrap();
+                        interpreter.read("clear_board\n").unwrap();
+                        interpreter.read("imrscl-donplayouts 100\n").unwrap();
+                        interpreter.read(&command).unwrap();
+                        let mut contents = String::new();
+                        File::open(&txt).unwrap().read_to_string(&mut contents).unwrap();
+                        assert!(contents.starts_with(start));
+                    }
+                }
+
+                it "fails without a file name or with an invalid depth" {
+                    assert_that!(interpreter.read("imrscl-dump_tree\n"), is(equal_to(err("missing argument"))));
+                    assert_that!(interpreter.read("imrscl-dump_tree tree.json x\n"), is(equal_to(err("syntax error"))));
+                }
+            }
+
+            describe! params {
+
+                it "lists the parameters with their values" {
+                    let response = interpreter.read("imrscl-params\n").unwrap();
+                    assert!(response.lines().any(|l| l == "[string] tree.rave_equiv 20.0"));
+                    assert!(response.lines().all(|l| l.starts_with("[string] ")));
+                }
+
+                it "doesn't list the parameters only used on startup" {
+                    let response = interpreter.read("imrscl-params\n").unwrap();
+                    assert!(!response.contains("patterns.small"));
+                }
+
+                it "sets a parameter when called with a name and value" {
+                    interpreter.read("imrscl-params tree.rave_equiv 25\n").unwrap();
+                    let response = interpreter.read("imrscl-params\n").unwrap();
+                    assert!(response.lines().any(|l| l == "[string] tree.rave_equiv 25.0"));
+                }
+            }
+
+            describe! set {
+
+                it "changes the parameter" {
+                    let response = interpreter.read("imrscl-set tree.rave_equiv 25\n");
+                    assert_that!(response, is(equal_to(ok(""))));
+                    let response = interpreter.read("imrscl-params\n").unwrap();
+                    assert!(response.lines().any(|l| l == "[string] tree.rave_equiv 25.0"));
+                }
+
+                it "requires a name and a value" {
+                    let response = interpreter.read("imrscl-set tree.rave_equiv\n");
+                    assert_that!(response, is(equal_to(err("missing argument(s)"))));
+                }
+
+                it "rejects unknown parameters" {
+                    let response = interpreter.read("imrscl-set tree.rave_equv 25\n");
+                    assert_that!(response, is(equal_to(err("unknown parameter tree.rave_equv"))));
+                }
+
+                it "rejects invalid values" {
+                    let response = interpreter.read("imrscl-set scoring.ownership_cutoff 3\n");
+                    assert_that!(response, is(equal_to(err("scoring.ownership_cutoff: 3 is not between 0 and 1"))));
+                    let response = interpreter.read("imrscl-set tree.expand_after many\n");
+                    assert_that!(response, is(equal_to(err("expected integer but found 'many'"))));
+                }
+
+                it "still plays after a parameter change" {
+                    interpreter.read("boardsize 9\n").unwrap();
+                    interpreter.read("clear_board\n").unwrap();
+                    interpreter.read("imrscl-set priors.neutral_plays 20\n").unwrap();
+                    let response = interpreter.read("imrscl-donplayouts 100\n");
+                    assert_that!(response, is(equal_to(ok(""))));
+                }
+            }
+
+            describe! tactics {
+
+                it "returns nothing on an empty board" {
+                    interpreter.read("boardsize 9\n").unwrap();
+                    interpreter.read("clear_board\n").unwrap();
+                    let response = interpreter.read("imrscl-tactics\n");
+                    assert_that!(response, is(equal_to(ok(""))));
+                }
+
+                it "finds a move that saves a chain" {
+                    interpreter.read("boardsize 9\n").unwrap();
+                    interpreter.read("clear_board\n").unwrap();
+                    interpreter.read("play b e5\n").unwrap();
+                    interpreter.read("play w e6\n").unwrap();
+                    interpreter.read("play b e3\n").unwrap();
+                    interpreter.read("play w d5\n").unwrap();
+                    interpreter.read("play b j9\n").unwrap();
+                    interpreter.read("play w f5\n").unwrap();
+                    let response = interpreter.read("imrscl-tactics\n").unwrap();
+                    assert!(response.lines().any(|l| l == "save E5 at E4"));
+                }
+            }
+
+            describe! tsumego {
+
+                it "solves a problem in the given region" {
+                    interpreter.read("boardsize 9\n").unwrap();
+                    interpreter.read("clear_board\n").unwrap();
+                    let moves = ["b a3", "w a2", "b b3", "w b2", "b c3", "w c2", "b d3",
+                                 "w d2", "b e3", "w d1", "b e2", "w pass", "b e1", "w pass"];
+                    for m in moves.iter() {
+                        interpreter.read(&format!("play {}\n", m)).unwrap();
+                    }
+                    let response = interpreter.read("imrscl-tsumego a2 a1:c1\n").unwrap();
+                    assert!(response.starts_with("B kills at B1"));
+                }
+
+                it "rejects a target that isn't a stone" {
+                    interpreter.read("boardsize 9\n").unwrap();
+                    interpreter.read("clear_board\n").unwrap();
+                    let response = interpreter.read("imrscl-tsumego a2 a1:c1\n");
+                    assert_that!(response, is(equal_to(err("target is not a stone"))));
+                }
+
+                it "rejects a malformed region" {
+                    interpreter.read("boardsize 9\n").unwrap();
+                    interpreter.read("play b a2\n").unwrap();
+                    let response = interpreter.read("imrscl-tsumego a2 a1:z1\n");
+                    assert_that!(response, is(equal_to(err("syntax error"))));
+                }
+            }
+
         }
 
     }
@@ -439,7 +753,9 @@ describe! interpreter {
             c.ruleset = KgsChinese;
             let config = Arc::new(c);
             let small_pattern_matcher = Arc::new(SmallPatternMatcher::new());
-            let engine = Engine::new(config.clone(), small_pattern_matcher);
+            let large_pattern_matcher = Arc::new(LargePatternMatcher::new());
+            let book = Arc::new(Book::new(Size::square(19)));
+            let engine = Engine::new(config.clone(), small_pattern_matcher, large_pattern_matcher, book);
             let mut interpreter = GTPInterpreter::new(config.clone(), engine);
         }
 
@@ -0,0 +1,139 @@
+/************************************************************************
+ *                                                                      *
+ * Copyright 2016 Urban Hafner                                          *
+ *                                                                      *
+ * This file is part of Iomrascálaí.                                    *
+ *                                                                      *
+ * Iomrascálaí is free software: you can redistribute it and/or modify  *
+ * it under the terms of the GNU General Public License as published by *
+ * the Free Software Foundation, either version 3 of the License, or    *
+ * (at your option) any later version.                                  *
+ *                                                                      *
+ * Iomrascálaí is distributed in the hope that it will be useful,       *
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of       *
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the        *
+ * GNU General Public License for more details.                         *
+ *                                                                      *
+ * You should have received a copy of the GNU General Public License    *
+ * along with Iomrascálaí.  If not, see <http://www.gnu.org/licenses/>. *
+ *                                                                      *
+ ************************************************************************/
+
+use board::Black;
+use board::Color;
+use config::Config;
+use game::Game;
+
+use std::cmp;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+
+mod test;
+
+/// How the virtual komi used during the search is chosen.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum KomiMode {
+    /// The virtual komi changes after each move depending on the win
+    /// rate of the previous search.
+    Adaptive,
+    /// In handicap games the komi compensates for the handicap
+    /// stones at the start and decreases linearly with the move
+    /// number.
+    Linear,
+    /// The real komi is used.
+    Off,
+}
+
+impl FromStr for KomiMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<KomiMode, Self::Err> {
+        match s {
+            "adaptive" => Ok(KomiMode::Adaptive),
+            "linear"   => Ok(KomiMode::Linear),
+            "off"      => Ok(KomiMode::Off),
+            _          => Err(format!("Unknown dynamic komi mode '{}'", s)),
+        }
+    }
+}
+
+impl fmt::Display for KomiMode {
+
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match *self {
+            KomiMode::Adaptive => "adaptive",
+            KomiMode::Linear => "linear",
+            KomiMode::Off => "off",
+        };
+        s.fmt(f)
+    }
+}
+
+// When one side is far ahead (as in handicap games) almost every
+// playout is won (or lost) and the win rate can't tell good moves
+// from bad ones anymore. Dynamic komi changes the komi the playouts
+// are scored with to keep the win rate in a range where it's
+// informative. The real komi is still used to score the game.
+pub struct DynamicKomi {
+    // The komi the engine gives away to the opponent in adaptive
+    // mode. It never gets negative as the engine would otherwise
+    // never resign a lost game.
+    adaptive_offset: f32,
+    config: Arc<Config>,
+}
+
+impl DynamicKomi {
+
+    pub fn new(config: Arc<Config>) -> DynamicKomi {
+        DynamicKomi {
+            adaptive_offset: 0.0,
+            config: config,
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.adaptive_offset = 0.0;
+    }
+
+    pub fn set_config(&mut self, config: Arc<Config>) {
+        self.config = config;
+    }
+
+    /// The komi to use for the search of the next move of the given
+    /// player.
+    pub fn komi(&self, game: &Game, color: Color) -> f32 {
+        let config = &self.config.dynamic_komi;
+        match config.mode {
+            KomiMode::Adaptive => {
+                // Giving away komi means a larger komi when playing
+                // black and a smaller one when playing white.
+                if color == Black {
+                    game.komi() + self.adaptive_offset
+                } else {
+                    game.komi() - self.adaptive_offset
+                }
+            },
+            KomiMode::Linear => {
+                let handicap = game.handicap() as f32 * config.handicap_value;
+                let left = config.moves.saturating_sub(game.move_number()) as f32;
+                game.komi() + handicap * left / cmp::max(config.moves, 1) as f32
+            },
+            KomiMode::Off => game.komi(),
+        }
+    }
+
+    /// Adapts the komi to the win rate of the last search. Only used
+    /// in adaptive mode.
+    pub fn update(&mut self, win_ratio: f32) {
+        let config = &self.config.dynamic_komi;
+        if config.mode != KomiMode::Adaptive {
+            return;
+        }
+        if win_ratio > config.green_zone {
+            self.adaptive_offset += config.step;
+        } else if win_ratio < config.red_zone {
+            self.adaptive_offset = (self.adaptive_offset - config.step).max(0.0);
+        }
+    }
+}
@@ -0,0 +1,119 @@
+/************************************************************************
+ *                                                                      *
+ * Copyright 2016 Urban Hafner                                          *
+ *                                                                      *
+ * This file is part of Iomrascálaí.                                    *
+ *                                                                      *
+ * Iomrascálaí is free software: you can redistribute it and/or modify  *
+ * it under the terms of the GNU General Public License as published by *
+ * the Free Software Foundation, either version 3 of the License, or    *
+ * (at your option) any later version.                                  *
+ *                                                                      *
+ * Iomrascálaí is distributed in the hope that it will be useful,       *
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of       *
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the        *
+ * GNU General Public License for more details.                         *
+ *                                                                      *
+ * You should have received a copy of the GNU General Public License    *
+ * along with Iomrascálaí.  If not, see <http://www.gnu.org/licenses/>. *
+ *                                                                      *
+ ************************************************************************/
+
+#![cfg(test)]
+
+use board::Black;
+use board::Pass;
+use board::Play;
+use board::White;
+use config::Config;
+use game::Game;
+use ruleset::KgsChinese;
+use super::DynamicKomi;
+use super::KomiMode;
+
+use std::sync::Arc;
+
+fn dynamic_komi(mode: KomiMode) -> DynamicKomi {
+    let mut config = Config::test_config();
+    config.dynamic_komi.mode = mode;
+    config.dynamic_komi.handicap_value = 7.0;
+    config.dynamic_komi.moves = 100;
+    config.dynamic_komi.green_zone = 0.8;
+    config.dynamic_komi.red_zone = 0.4;
+    config.dynamic_komi.step = 1.0;
+    DynamicKomi::new(Arc::new(config))
+}
+
+fn two_stone_handicap() -> Game {
+    let mut game = Game::new(19, 0.5, KgsChinese);
+    game = game.play(Play(Black, 4, 4)).unwrap();
+    game = game.play(Pass(White)).unwrap();
+    game.play(Play(Black, 16, 16)).unwrap()
+}
+
+#[test]
+fn mode_can_be_parsed() {
+    assert_eq!(Ok(KomiMode::Adaptive), "adaptive".parse());
+    assert_eq!(Ok(KomiMode::Linear), "linear".parse());
+    assert_eq!(Ok(KomiMode::Off), "off".parse());
+    assert!("dynamic".parse::<KomiMode>().is_err());
+}
+
+#[test]
+fn off_uses_the_real_komi() {
+    let komi = dynamic_komi(KomiMode::Off);
+    assert_eq!(0.5, komi.komi(&two_stone_handicap(), White));
+}
+
+#[test]
+fn linear_compensates_for_the_handicap_stones() {
+    let komi = dynamic_komi(KomiMode::Linear);
+    let game = two_stone_handicap();
+    // Three moves of 100 have been played.
+    assert_eq!(0.5 + 14.0 * 97.0 / 100.0, komi.komi(&game, White));
+    assert_eq!(0.5 + 14.0 * 97.0 / 100.0, komi.komi(&game, Black));
+}
+
+#[test]
+fn linear_uses_the_real_komi_in_even_games() {
+    let komi = dynamic_komi(KomiMode::Linear);
+    let game = Game::new(19, 6.5, KgsChinese).play(Play(Black, 4, 4)).unwrap();
+    assert_eq!(6.5, komi.komi(&game, White));
+}
+
+#[test]
+fn linear_uses_the_real_komi_after_the_configured_moves() {
+    let komi = dynamic_komi(KomiMode::Linear);
+    let mut game = two_stone_handicap();
+    for _ in 0..50 {
+        game = game.play(Pass(White)).unwrap();
+        game = game.play(Pass(Black)).unwrap();
+        game.reset_game_over();
+    }
+    assert_eq!(0.5, komi.komi(&game, White));
+}
+
+#[test]
+fn adaptive_gives_away_komi_when_winning() {
+    let mut komi = dynamic_komi(KomiMode::Adaptive);
+    let game = Game::new(19, 6.5, KgsChinese);
+    komi.update(0.9);
+    komi.update(0.9);
+    assert_eq!(8.5, komi.komi(&game, Black));
+    assert_eq!(4.5, komi.komi(&game, White));
+    komi.update(0.6);
+    assert_eq!(8.5, komi.komi(&game, Black));
+    komi.update(0.1);
+    assert_eq!(7.5, komi.komi(&game, Black));
+}
+
+#[test]
+fn adaptive_never_takes_extra_komi() {
+    let mut komi = dynamic_komi(KomiMode::Adaptive);
+    let game = Game::new(19, 6.5, KgsChinese);
+    komi.update(0.1);
+    assert_eq!(6.5, komi.komi(&game, Black));
+    komi.update(0.9);
+    komi.reset();
+    assert_eq!(6.5, komi.komi(&game, White));
+}
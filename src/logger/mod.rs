@@ -0,0 +1,321 @@
+/************************************************************************
+ *                                                                      *
+ * Copyright 2016 Urban Hafner                                          *
+ *                                                                      *
+ * This file is part of Iomrascálaí.                                    *
+ *                                                                      *
+ * Iomrascálaí is free software: you can redistribute it and/or modify  *
+ * it under the terms of the GNU General Public License as published by *
+ * the Free Software Foundation, either version 3 of the License, or    *
+ * (at your option) any later version.                                  *
+ *                                                                      *
+ * Iomrascálaí is distributed in the hope that it will be useful,       *
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of       *
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the        *
+ * GNU General Public License for more details.                         *
+ *                                                                      *
+ * You should have received a copy of the GNU General Public License    *
+ * along with Iomrascálaí.  If not, see <http://www.gnu.org/licenses/>. *
+ *                                                                      *
+ ************************************************************************/
+
+use std::fmt;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::io::stderr;
+use std::str::FromStr;
+use std::sync::Mutex;
+use time;
+
+/// Logs a message with optional fields if the level is enabled for
+/// the current module, e.g.
+///
+/// `log!(config, Level::Info, "genmove", "move" => "C3", "playouts" => 1000)`
+macro_rules! log {
+    ($config:expr, $level:expr, $message:expr $(, $name:expr => $value:expr)*) => {{
+        let logger = &$config.logger;
+        if logger.enabled($level, module_path!()) {
+            logger.log($level, module_path!(), &$message, &[$(($name, ::logger::Field::from($value))),*]);
+        }
+    }};
+}
+
+macro_rules! error {
+    ($config:expr, $($arg:tt)+) => { log!($config, ::logger::Level::Error, format!($($arg)+)) };
+}
+
+macro_rules! warn {
+    ($config:expr, $($arg:tt)+) => { log!($config, ::logger::Level::Warn, format!($($arg)+)) };
+}
+
+macro_rules! info {
+    ($config:expr, $($arg:tt)+) => { log!($config, ::logger::Level::Info, format!($($arg)+)) };
+}
+
+macro_rules! debug {
+    ($config:expr, $($arg:tt)+) => { log!($config, ::logger::Level::Debug, format!($($arg)+)) };
+}
+
+macro_rules! trace {
+    ($config:expr, $($arg:tt)+) => { log!($config, ::logger::Level::Trace, format!($($arg)+)) };
+}
+
+// After the macros so that the tests can use them.
+mod test;
+
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub enum Level {
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl FromStr for Level {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Level, String> {
+        match s {
+            "error" => Ok(Level::Error),
+            "warn" => Ok(Level::Warn),
+            "info" => Ok(Level::Info),
+            "debug" => Ok(Level::Debug),
+            "trace" => Ok(Level::Trace),
+            _ => Err(format!("Unknown log level '{}'", s)),
+        }
+    }
+}
+
+impl fmt::Display for Level {
+
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match *self {
+            Level::Error => "error",
+            Level::Warn => "warn",
+            Level::Info => "info",
+            Level::Debug => "debug",
+            Level::Trace => "trace",
+        };
+        s.fmt(f)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Format {
+    /// One line of text per message.
+    Text,
+    /// One JSON object per line with the fields as keys.
+    Json,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Format, String> {
+        match s {
+            "text" => Ok(Format::Text),
+            "json" => Ok(Format::Json),
+            _ => Err(format!("Unknown log format '{}'", s)),
+        }
+    }
+}
+
+/// The value of a field of a log message.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Field {
+    Float(f64),
+    Integer(i64),
+    Text(String),
+}
+
+impl Field {
+
+    fn to_json(&self) -> String {
+        match *self {
+            Field::Float(f) if f.is_finite() => format!("{}", f),
+            Field::Float(_) => "null".to_string(),
+            Field::Integer(i) => format!("{}", i),
+            Field::Text(ref s) => json_string(s),
+        }
+    }
+}
+
+impl fmt::Display for Field {
+
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Field::Float(v) => v.fmt(f),
+            Field::Integer(v) => v.fmt(f),
+            Field::Text(ref v) => v.fmt(f),
+        }
+    }
+}
+
+impl From<f32> for Field {
+    fn from(v: f32) -> Field { Field::Float(v as f64) }
+}
+
+impl From<f64> for Field {
+    fn from(v: f64) -> Field { Field::Float(v) }
+}
+
+impl From<i64> for Field {
+    fn from(v: i64) -> Field { Field::Integer(v) }
+}
+
+impl From<usize> for Field {
+    fn from(v: usize) -> Field { Field::Integer(v as i64) }
+}
+
+impl<'a> From<&'a str> for Field {
+    fn from(v: &'a str) -> Field { Field::Text(v.to_string()) }
+}
+
+impl From<String> for Field {
+    fn from(v: String) -> Field { Field::Text(v) }
+}
+
+/// Writes log messages with a timestamp, level, and module to stderr
+/// or a file. Which messages are written is decided by a filter like
+/// `info,engine=debug,timer=trace`: a default level followed by the
+/// levels of individual modules (and the modules inside them). The
+/// modules are named without the crate, e.g. `engine::node`.
+pub struct Logger {
+    filters: Vec<(String, Level)>,
+    format: Format,
+    level: Option<Level>,
+    sink: Mutex<Box<Write + Send>>,
+}
+
+impl Logger {
+
+    /// A logger that doesn't write anything.
+    pub fn off() -> Logger {
+        Logger {
+            filters: vec!(),
+            format: Format::Text,
+            level: None,
+            sink: Mutex::new(Box::new(stderr())),
+        }
+    }
+
+    /// A logger that writes text to stderr.
+    pub fn stderr(filter: &str) -> Result<Logger, String> {
+        Self::new(filter, Format::Text, Box::new(stderr()))
+    }
+
+    /// A logger that appends to the file.
+    pub fn file(filter: &str, format: Format, filename: &str) -> Result<Logger, String> {
+        let file = try!(OpenOptions::new().create(true).append(true).open(filename)
+                        .map_err(|e| format!("can't open log file {}: {}", filename, e)));
+        Self::new(filter, format, Box::new(file))
+    }
+
+    pub fn new(filter: &str, format: Format, sink: Box<Write + Send>) -> Result<Logger, String> {
+        let mut level = Some(Level::Info);
+        let mut filters = vec!();
+        for part in filter.split(',').map(|p| p.trim()).filter(|p| !p.is_empty()) {
+            let mut pair = part.splitn(2, '=');
+            match (pair.next(), pair.next()) {
+                (Some("off"), None) => level = None,
+                (Some(l), None) => level = Some(try!(l.parse())),
+                (Some(module), Some(l)) => filters.push((module.to_string(), try!(l.parse()))),
+                _ => {}
+            }
+        }
+        Ok(Logger {
+            filters: filters,
+            format: format,
+            level: level,
+            sink: Mutex::new(sink),
+        })
+    }
+
+    /// Whether messages of the level are written for the module (as
+    /// returned by `module_path!()`). The most specific filter wins.
+    pub fn enabled(&self, level: Level, module_path: &str) -> bool {
+        let module = Self::module(module_path);
+        let filter = self.filters.iter()
+            .filter(|&&(ref name, _)| module == *name || module.starts_with(&format!("{}::", name)))
+            .max_by_key(|&&(ref name, _)| name.len());
+        match filter {
+            Some(&(_, max)) => level <= max,
+            None => self.level.map_or(false, |max| level <= max),
+        }
+    }
+
+    pub fn log(&self, level: Level, module_path: &str, message: &str, fields: &[(&str, Field)]) {
+        let line = self.format(&time::now_utc(), level, Self::module(module_path), message, fields);
+        if let Ok(mut sink) = self.sink.lock() {
+            // There's nowhere left to report a failed write to.
+            let _ = sink.write_all(line.as_bytes()).and_then(|_| sink.flush());
+        }
+    }
+
+    fn format(&self, now: &time::Tm, level: Level, module: &str, message: &str, fields: &[(&str, Field)]) -> String {
+        let timestamp = format!("{}.{:03}Z", now.strftime("%Y-%m-%dT%H:%M:%S").unwrap(), now.tm_nsec / 1000000);
+        match self.format {
+            Format::Text => {
+                let mut line = format!("{} {:5} {}: {}", timestamp, level.to_string().to_uppercase(), module, message);
+                for &(name, ref value) in fields {
+                    line.push_str(&format!(" {}={}", name, value));
+                }
+                line.push('\n');
+                line
+            },
+            Format::Json => {
+                let mut line = format!(
+                    "{{\"time\":{},\"level\":{},\"module\":{},\"message\":{}",
+                    json_string(&timestamp),
+                    json_string(&level.to_string()),
+                    json_string(module),
+                    json_string(message));
+                for &(name, ref value) in fields {
+                    line.push_str(&format!(",{}:{}", json_string(name), value.to_json()));
+                }
+                line.push_str("}\n");
+                line
+            }
+        }
+    }
+
+    // "iomrascalai::engine::node" -> "engine::node"
+    fn module(module_path: &str) -> &str {
+        match module_path.find("::") {
+            Some(index) => &module_path[index + 2..],
+            None => "main",
+        }
+    }
+}
+
+impl fmt::Debug for Logger {
+
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Logger {{ filters: {:?}, format: {:?}, level: {:?} }}", self.filters, self.format, self.level)
+    }
+}
+
+impl PartialEq for Logger {
+
+    fn eq(&self, other: &Logger) -> bool {
+        self.filters == other.filters && self.format == other.format && self.level == other.level
+    }
+}
+
+fn json_string(s: &str) -> String {
+    let mut json = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if (c as u32) < 0x20 => json.push_str(&format!("\\u{:04x}", c as u32)),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
@@ -0,0 +1,128 @@
+/************************************************************************
+ *                                                                      *
+ * Copyright 2016 Urban Hafner                                          *
+ *                                                                      *
+ * This file is part of Iomrascálaí.                                    *
+ *                                                                      *
+ * Iomrascálaí is free software: you can redistribute it and/or modify  *
+ * it under the terms of the GNU General Public License as published by *
+ * the Free Software Foundation, either version 3 of the License, or    *
+ * (at your option) any later version.                                  *
+ *                                                                      *
+ * Iomrascálaí is distributed in the hope that it will be useful,       *
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of       *
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the        *
+ * GNU General Public License for more details.                         *
+ *                                                                      *
+ * You should have received a copy of the GNU General Public License    *
+ * along with Iomrascálaí.  If not, see <http://www.gnu.org/licenses/>. *
+ *                                                                      *
+ ************************************************************************/
+
+#![cfg(test)]
+
+use super::Field;
+use super::Format;
+use super::Level;
+use super::Logger;
+
+use std::io;
+use std::io::Write;
+use std::sync::Arc;
+use std::sync::Mutex;
+use time;
+use time::Timespec;
+
+#[derive(Clone)]
+struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+impl Buffer {
+
+    fn contents(&self) -> String {
+        String::from_utf8(self.0.lock().unwrap().clone()).unwrap()
+    }
+}
+
+impl Write for Buffer {
+
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+struct Holder {
+    logger: Logger,
+}
+
+fn logger(filter: &str, format: Format) -> (Logger, Buffer) {
+    let buffer = Buffer(Arc::new(Mutex::new(vec!())));
+    (Logger::new(filter, format, Box::new(buffer.clone())).unwrap(), buffer)
+}
+
+fn epoch() -> time::Tm {
+    time::at_utc(Timespec::new(0, 5000000))
+}
+
+#[test]
+fn the_default_level_is_info() {
+    let (logger, _) = logger("", Format::Text);
+    assert!(logger.enabled(Level::Info, "iomrascalai::engine"));
+    assert!(!logger.enabled(Level::Debug, "iomrascalai::engine"));
+}
+
+#[test]
+fn the_most_specific_module_filter_wins() {
+    let (logger, _) = logger("warn,engine=debug,engine::node=trace", Format::Text);
+    assert!(!logger.enabled(Level::Info, "iomrascalai::timer"));
+    assert!(logger.enabled(Level::Debug, "iomrascalai::engine::worker"));
+    assert!(!logger.enabled(Level::Trace, "iomrascalai::engine::worker"));
+    assert!(logger.enabled(Level::Trace, "iomrascalai::engine::node"));
+    assert!(!logger.enabled(Level::Info, "iomrascalai::engineering"));
+}
+
+#[test]
+fn off_disables_everything_but_the_module_filters() {
+    let (logger, _) = logger("off,timer=info", Format::Text);
+    assert!(!logger.enabled(Level::Error, "iomrascalai::engine"));
+    assert!(logger.enabled(Level::Info, "iomrascalai::timer"));
+    assert!(!Logger::off().enabled(Level::Error, "iomrascalai"));
+}
+
+#[test]
+fn invalid_levels_are_rejected() {
+    assert!(Logger::new("loud", Format::Text, Box::new(io::sink())).is_err());
+    assert!(Logger::new("engine=loud", Format::Text, Box::new(io::sink())).is_err());
+}
+
+#[test]
+fn text_lines_have_a_timestamp_level_and_module() {
+    let (logger, _) = logger("", Format::Text);
+    let fields = [("playouts", Field::from(1000usize)), ("move", Field::from("C3"))];
+    assert_eq!(
+        "1970-01-01T00:00:00.005Z INFO  engine: genmove playouts=1000 move=C3\n",
+        logger.format(&epoch(), Level::Info, "engine", "genmove", &fields));
+}
+
+#[test]
+fn json_lines_have_the_fields_as_keys() {
+    let (logger, _) = logger("", Format::Json);
+    let fields = [("win_rate", Field::from(0.5)), ("move", Field::from("C3"))];
+    assert_eq!(
+        "{\"time\":\"1970-01-01T00:00:00.005Z\",\"level\":\"warn\",\"module\":\"gtp\",\"message\":\"say \\\"hi\\\"\\n\",\"win_rate\":0.5,\"move\":\"C3\"}\n",
+        logger.format(&epoch(), Level::Warn, "gtp", "say \"hi\"\n", &fields));
+}
+
+#[test]
+fn the_macros_only_write_enabled_messages() {
+    let (logger, buffer) = logger("info", Format::Json);
+    let holder = Holder { logger: logger };
+    debug!(holder, "not written");
+    log!(holder, Level::Info, "genmove", "playouts" => 10usize);
+    let contents = buffer.contents();
+    assert_eq!(1, contents.lines().count());
+    assert!(contents.contains("\"module\":\"logger::test\",\"message\":\"genmove\",\"playouts\":10}"));
+}
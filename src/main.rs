@@ -42,40 +42,85 @@ extern crate toml;
 // Use everything in config publicly to force the generation of
 // documentation.
 pub use config::*;
+use benchmark::Benchmark;
+use board::Size;
+use book::Book;
 use engine::Engine;
-use gtp::driver::BenchmarkDriver;
+use game::Game;
 use gtp::driver::Driver;
+use gtp::driver::main_time;
+use gtp::GTPInterpreter;
+use logger::Format;
+use logger::Logger;
+use patterns::LargePatternMatcher;
 use patterns::SmallPatternMatcher;
+use regress::Outcome;
+use regress::Suite;
+use referee::ExternalEngine;
+use referee::Player;
+use referee::Referee;
+use referee::Results;
 use ruleset::Ruleset;
+use sgf::Parser;
+use tree_dump::TreeDump;
+use tsumego::Problem;
+use tsumego::Solver;
+use tuner::Spec;
+use tuner::Tuner;
 
+use getopts::Matches;
 use getopts::Options;
+use rand::weak_rng;
 use std::sync::Arc;
 use std::env::args;
+use std::fs::File;
+use std::fs::create_dir_all;
+use std::fs::read_dir;
+use std::io::Write;
+use std::io::stderr;
+use std::path::Path;
 use std::process::exit;
 
+// Declared first so that its macros can be used in all other modules.
+#[macro_use]
+mod logger;
+mod benchmark;
 mod board;
+mod book;
 mod config;
 mod engine;
 mod fixtures;
 mod game;
 mod gtp;
+mod komi;
 mod ownership;
 mod patterns;
 mod playout;
+mod referee;
+mod regress;
 mod ruleset;
 mod score;
 mod sgf;
+mod tactics;
 mod timer;
+mod tree_dump;
+mod tsumego;
+mod tuner;
 mod uct_gfx;
 mod version;
 
 fn main() {
     let mut opts = Options::new();
     let default_ruleset = Ruleset::KgsChinese;
-    opts.optflag("d", "dump", "Dump default config to stdout");
+    opts.optflagopt("d", "dump", "Dump default config to stdout (or the config resolved for board size SIZE, e.g. 9 or 19x13)", "SIZE");
+    opts.optflag("", "dump-patterns", "Dump the default 3x3 patterns to stdout");
     opts.optflag("g", "gfx", "Ouput GoGui live graphics");
+    opts.optflag("", "check-config", "Check the config file, the --opponent config file, and the rules, superko, log, and tree dump options and exit");
     opts.optflag("h", "help", "Print this help menu");
     opts.optflag("l", "log", "Print logging information to STDERR");
+    opts.optopt("", "log-level", "Log the messages up to LEVEL, optionally per module (e.g. \"info,engine=debug\"; implies --log)", "LEVEL");
+    opts.optopt("", "log-file", "Append the log to FILE instead of printing it to STDERR (implies --log)", "FILE");
+    opts.optopt("", "log-format", "Format of the log lines (defaults to text)", "text|json");
     opts.optflag("v", "version", "Print the version number");
     opts.optopt("c", "config", "Config file", "FILE");
     opts.optopt(
@@ -84,9 +129,33 @@ fn main() {
         "Number of worker threads (overrides value set in the config file)",
         "INTEGER"
     );
-    let r_expl = format!("cgos|chinese|tromp-taylor (defaults to {})", default_ruleset);
+    let r_expl = format!("cgos|chinese|new-zealand|tromp-taylor (defaults to {})", default_ruleset);
     opts.optopt("r", "rules", "Pick ruleset", &r_expl);
-    opts.optopt("b", "benchmark", "Run benchmark on provided board size", "INTEGER");
+    opts.optopt("", "superko", "Pick superko rule (defaults to the one of the ruleset)", "positional|situational|natural-situational");
+    opts.optopt("b", "benchmark", "Run the benchmark on the board size (e.g. 19 or 19x13) and print the results as JSON", "SIZE");
+    opts.optopt("", "repeat", "Number of times the --benchmark measurements are repeated (defaults to 1)", "N");
+    opts.optopt("", "benchmark-playouts", "Number of playouts of the searches in --benchmark (defaults to 10000)", "N");
+    opts.optopt("", "build-book", "Build an opening book from --book-sgf and --book-searches and write it to FILE", "FILE");
+    opts.optmulti("", "book-sgf", "Add the games of the SGF files in DIR to the opening book (can be given multiple times)", "DIR");
+    opts.optopt("", "book-searches", "Add the moves of GAMES games played with long searches to the opening book", "GAMES");
+    opts.optopt("", "book-size", "Board size of the opening book (defaults to 19)", "SIZE");
+    opts.optopt("", "selfplay", "Play GAMES games between the config file (A) and the --opponent config file (B) and print the results", "GAMES");
+    opts.optopt("", "opponent", "Config file of B in --selfplay and --tune (defaults to the default config)", "FILE");
+    opts.optopt("", "match", "Play GAMES games between the config file (A) and the --engine GTP program (B) and print the results", "GAMES");
+    opts.optopt("", "engine", "Command line of B in --match (e.g. \"gnugo --mode gtp --level 0\")", "COMMAND");
+    opts.optopt("", "tune", "Tune the parameters in the SPEC file with SPSA in games against the --opponent config file", "SPEC");
+    opts.optopt("", "checkpoint", "Progress of --tune, which continues from it if it exists (defaults to SPEC.checkpoint)", "FILE");
+    opts.optopt("", "tuned", "Config file with the values found by --tune (defaults to tuned.toml)", "FILE");
+    opts.optopt("", "size", "Board size of the --selfplay, --match, and --tune games (defaults to 9)", "SIZE");
+    opts.optopt("", "main-time", "Main time of each player in the --selfplay, --match, and --tune games (defaults to the usual time for the board size)", "SECONDS");
+    opts.optopt("", "sgf-dir", "Save the --selfplay and --match games as SGF files and a summary of the results in DIR", "DIR");
+    opts.optmulti("", "regress", "Run the GTP regression tests in the .tst FILE (can be given multiple times)", "FILE");
+    opts.optopt("", "regress-results", "Write the results of --regress as tab separated values to FILE", "FILE");
+    opts.optopt("", "dump-tree", "Write the search tree before each generated move to a file in DIR (e.g. move-012-b.json)", "DIR");
+    opts.optopt("", "dump-format", "Format of the trees of --dump-tree and of imrscl-dump_tree if the file name doesn't end in .json, .dot, or .gv (defaults to json)", "json|dot");
+    opts.optopt("", "dump-depth", "Number of moves below the root included in the dumped trees (defaults to 3)", "N");
+    opts.optopt("", "dump-min-playouts", "Minimum number of playouts of the nodes included in the dumped trees (defaults to 10)", "N");
+    opts.optmulti("", "tsumego", "Solve the life-and-death problem in the SGF file (can be given multiple times)", "FILE");
     let args : Vec<String> = args().collect();
 
     let (_, tail) = args.split_first().unwrap();
@@ -98,6 +167,11 @@ fn main() {
         }
     };
 
+    if let Err(error) = check_modes(&matches) {
+        println!("{}", error);
+        exit(1);
+    }
+
     if matches.opt_present("h") {
         let brief = format!("Usage: {} [options]", args[0]);
         println!("{}", opts.usage(brief.as_ref()));
@@ -107,10 +181,14 @@ fn main() {
         println!("Iomrascálaí {}", version::version());
         exit(0);
     }
-    if matches.opt_present("d") {
+    if matches.opt_present("d") && matches.opt_str("d").is_none() {
         println!("{}", Config::toml());
         exit(0);
     }
+    if matches.opt_present("dump-patterns") {
+        println!("{}", SmallPatternMatcher::defaults());
+        exit(0);
+    }
     let log = matches.opt_present("l");
     let gfx = matches.opt_present("g");
     let ruleset = match matches.opt_str("r") {
@@ -135,35 +213,414 @@ fn main() {
     };
 
     let config_file_opt = matches.opt_str("c");
-    let config = match config_file_opt {
+    let loaded = match config_file_opt {
         Some(filename) => {
             Config::from_file(filename, log, gfx, ruleset, threads)
         },
         None => {
-            Config::default(log, gfx, ruleset, threads)
+            Config::from_toml(String::new(), log, gfx, ruleset, threads)
         }
     };
+    let mut config = match loaded {
+        Ok(config) => config,
+        Err(error) => {
+            println!("{}", error);
+            exit(1);
+        }
+    };
+    if let Some(s) = matches.opt_str("d") {
+        match s.parse() {
+            Ok(size) => match config.for_size(size) {
+                Some(resolved) => println!("{}", resolved.to_toml()),
+                None => println!("{}", config.to_toml()),
+            },
+            Err(error) => {
+                println!("{}", error);
+                exit(1);
+            }
+        }
+        exit(0);
+    }
 
+    if let Some(s) = matches.opt_str("superko") {
+        match s.parse() {
+            Ok(super_ko) => config.super_ko = super_ko,
+            Err(error) => {
+                println!("{}", error);
+                exit(1);
+            }
+        }
+    }
+    match logger(&matches) {
+        Ok(Some(logger)) => {
+            config.logger = Arc::new(logger);
+        },
+        Ok(None) => {},
+        Err(error) => {
+            println!("{}", error);
+            exit(1);
+        }
+    }
+    if let Err(error) = set_tree_dump(&mut config, &matches) {
+        println!("{}", error);
+        exit(1);
+    }
+    let opponent = if matches.opt_present("selfplay") || matches.opt_present("tune") || matches.opt_present("check-config") {
+        let loaded = match matches.opt_str("opponent") {
+            Some(filename) => Config::from_file(filename, log, gfx, ruleset, threads),
+            None => Config::from_toml(String::new(), log, gfx, ruleset, threads),
+        };
+        match loaded {
+            Ok(mut opponent) => {
+                opponent.super_ko = config.super_ko;
+                opponent.logger = config.logger.clone();
+                Some(Arc::new(opponent))
+            },
+            Err(error) => {
+                println!("{}", error);
+                exit(1);
+            }
+        }
+    } else {
+        None
+    };
+    if matches.opt_present("check-config") {
+        println!("Configuration OK");
+        exit(0);
+    }
     let config = Arc::new(config);
-    // Instantiate only one matcher as it does a lot of computation
-    // during setup.
-    let small_pattern_matcher = Arc::new(SmallPatternMatcher::new());
+    if matches.opt_present("tsumego") {
+        solve_tsumego(&config, matches.opt_strs("tsumego"));
+        exit(0);
+    }
+    let mut engine = match load_engine(&config) {
+        Ok(engine) => engine,
+        Err(error) => {
+            println!("{}", error);
+            exit(1);
+        }
+    };
 
-    let engine = Engine::new(
-        config.clone(),
-        small_pattern_matcher,
-    );
+    if let Some(filename) = matches.opt_str("build-book") {
+        match build_book(&config, &mut engine, &matches) {
+            Ok(book) => match book.save(&filename) {
+                Ok(_) => println!("{} positions written to {}", book.len(), filename),
+                Err(error) => {
+                    println!("{}", error);
+                    exit(1);
+                }
+            },
+            Err(error) => {
+                println!("{}", error);
+                exit(1);
+            }
+        }
+        exit(0);
+    }
+
+    if matches.opt_present("regress") {
+        match regress(config, engine, &matches) {
+            Ok(true) => exit(0),
+            Ok(false) => exit(1),
+            Err(error) => {
+                println!("{}", error);
+                exit(1);
+            }
+        }
+    }
+
+    if matches.opt_present("tune") {
+        if let Err(error) = tune(config, engine, opponent.unwrap(), &matches) {
+            println!("{}", error);
+            exit(1);
+        }
+        exit(0);
+    }
+
+    if matches.opt_present("selfplay") || matches.opt_present("match") {
+        match play_match(config, engine, opponent, &matches) {
+            Ok(results) => println!("{}", results),
+            Err(error) => {
+                println!("{}", error);
+                exit(1);
+            }
+        }
+        exit(0);
+    }
 
-    config.log(format!("Current configuration: {:#?}", config));
+    debug!(config, "Current configuration: {:#?}", config);
 
-    match matches.opt_str("b") {
-        Some(bs) => match bs.parse() {
-            Ok(board_size) => BenchmarkDriver::new(config, engine, board_size),
+    if matches.opt_present("b") {
+        match benchmark(config, engine, &matches) {
+            Ok(json) => println!("{}", json),
             Err(error) => {
                 println!("{}", error);
                 exit(1);
             }
+        }
+        exit(0);
+    }
+
+    Driver::new(config, engine)
+}
+
+// Sets up the dumps of the search tree from the --dump-* options.
+// The options that each run their own mode and exit. Only one of them
+// can be given at a time.
+const MODES: [&'static str; 8] = ["check-config", "tsumego", "build-book", "regress", "tune", "selfplay", "match", "benchmark"];
+
+fn check_modes(matches: &Matches) -> Result<(), String> {
+    let given: Vec<&str> = MODES.iter().cloned().filter(|mode| matches.opt_present(mode)).collect();
+    if given.len() > 1 {
+        Err(format!("--{} and --{} can't be combined", given[0], given[1]))
+    } else {
+        Ok(())
+    }
+}
+
+fn set_tree_dump(config: &mut Config, matches: &Matches) -> Result<(), String> {
+    let format = match matches.opt_str("dump-format") {
+        Some(s) => try!(s.parse()),
+        None => config.tree_dump.format(),
+    };
+    let max_depth = match matches.opt_str("dump-depth") {
+        Some(s) => try!(s.parse().map_err(|_| format!("invalid --dump-depth: {}", s))),
+        None => config.tree_dump.max_depth(),
+    };
+    let min_playouts = match matches.opt_str("dump-min-playouts") {
+        Some(s) => try!(s.parse().map_err(|_| format!("invalid --dump-min-playouts: {}", s))),
+        None => config.tree_dump.min_playouts(),
+    };
+    config.tree_dump = TreeDump::new(format, max_depth, min_playouts);
+    if let Some(dir) = matches.opt_str("dump-tree") {
+        try!(create_dir_all(&dir).map_err(|e| format!("can't create {}: {}", dir, e)));
+        config.dump_dir = Some(dir);
+    }
+    Ok(())
+}
+
+// Builds the logger of the --log-level, --log-file, and --log-format
+// options (or returns None if none of them is given).
+fn logger(matches: &Matches) -> Result<Option<Logger>, String> {
+    if !matches.opt_present("log-level") && !matches.opt_present("log-file") && !matches.opt_present("log-format") {
+        return Ok(None);
+    }
+    let filter = matches.opt_str("log-level").unwrap_or("info".to_string());
+    let format = match matches.opt_str("log-format") {
+        Some(s) => try!(s.parse()),
+        None => Format::Text,
+    };
+    let logger = match matches.opt_str("log-file") {
+        Some(filename) => try!(Logger::file(&filter, format, &filename)),
+        None => try!(Logger::new(&filter, format, Box::new(stderr()))),
+    };
+    Ok(Some(logger))
+}
+
+fn benchmark(config: Arc<Config>, engine: Engine, matches: &Matches) -> Result<String, String> {
+    let size = try!(matches.opt_str("b").unwrap().parse());
+    let repetitions = match matches.opt_str("repeat") {
+        Some(s) => try!(s.parse().map_err(|_| format!("invalid number of repetitions '{}'", s))),
+        None => 1,
+    };
+    let playouts = match matches.opt_str("benchmark-playouts") {
+        Some(s) => try!(s.parse().map_err(|_| format!("invalid number of playouts '{}'", s))),
+        None => 10000,
+    };
+    Ok(Benchmark::new(config, engine, size, playouts).run(repetitions))
+}
+
+fn load_engine(config: &Arc<Config>) -> Result<Engine, String> {
+    // The matchers do a lot of computation during setup, so only one
+    // of each is instantiated per engine.
+    let small_pattern_matcher = if config.patterns.small.is_empty() {
+        SmallPatternMatcher::new()
+    } else {
+        try!(SmallPatternMatcher::from_file(&config.patterns.small))
+    };
+    let large_pattern_matcher = if config.patterns.large.is_empty() {
+        LargePatternMatcher::new()
+    } else {
+        try!(LargePatternMatcher::from_file(&config.patterns.large))
+    };
+    let book = if config.book.file.is_empty() {
+        Book::new(Size::square(19))
+    } else {
+        try!(Book::from_file(&config.book.file))
+    };
+    Ok(Engine::new(
+        config.clone(),
+        Arc::new(small_pattern_matcher),
+        Arc::new(large_pattern_matcher),
+        Arc::new(book),
+    ))
+}
+
+// A referee for games between the engine (A) and either the opponent
+// config or the external engine (B).
+fn referee(config: Arc<Config>, engine: Engine, opponent: Option<Arc<Config>>, matches: &Matches) -> Result<Referee<'static>, String> {
+    let size = match matches.opt_str("size") {
+        Some(s) => try!(s.parse()),
+        None => Size::square(9),
+    };
+    let time = match matches.opt_str("main-time") {
+        Some(s) => try!(s.parse().map_err(|_| format!("invalid main time '{}'", s))),
+        None => main_time(size),
+    };
+    let b: Box<Player> = match opponent {
+        Some(opponent) => {
+            let opponent_engine = try!(load_engine(&opponent));
+            Box::new(GTPInterpreter::new(opponent, opponent_engine))
         },
-        None => Driver::new(config, engine)
+        None => {
+            let command = try!(matches.opt_str("engine").ok_or("--match needs an --engine".to_string()));
+            Box::new(try!(ExternalEngine::new(&command)))
+        }
+    };
+    let a = Box::new(GTPInterpreter::new(config.clone(), engine));
+    Ok(Referee::new(a, b, config.ruleset, config.super_ko, size, time))
+}
+
+fn tune(config: Arc<Config>, engine: Engine, opponent: Arc<Config>, matches: &Matches) -> Result<(), String> {
+    let spec_file = matches.opt_str("tune").unwrap();
+    let spec = try!(Spec::from_file(&spec_file));
+    let checkpoint = matches.opt_str("checkpoint").unwrap_or(format!("{}.checkpoint", spec_file));
+    let tuned = matches.opt_str("tuned").unwrap_or("tuned.toml".to_string());
+    let games = spec.games();
+    let mut tuner = try!(Tuner::new(spec, &config));
+    if Path::new(&checkpoint).exists() {
+        try!(tuner.load_checkpoint(&checkpoint));
+        println!("Continuing after iteration {} from {}", tuner.iteration(), checkpoint);
+    }
+    let mut referee = try!(referee(config, engine, Some(opponent), matches));
+    let mut rng = weak_rng();
+    while !tuner.is_done() {
+        try!(tuner.step(&mut rng, |params| {
+            // The parameters are changed at runtime (just like with
+            // GTP) so that the engine doesn't have to be set up again.
+            for &(ref name, ref value) in params {
+                try!(referee.a().send(&format!("imrscl-set {} {}", name, value)));
+            }
+            let mut results = Results::new();
+            for game in 0..games {
+                results.add(&try!(referee.play(game % 2 == 0)));
+            }
+            Ok(results.win_rate())
+        }));
+        try!(tuner.save_checkpoint(&checkpoint));
+        try!(tuner.save_overlay(&tuned));
+        let values: Vec<String> = tuner.params().iter()
+            .map(|&(ref name, ref value)| format!("{} = {}", name, value))
+            .collect();
+        println!("Iteration {}: {}", tuner.iteration(), values.join(", "));
+    }
+    println!("Tuned values written to {}", tuned);
+    Ok(())
+}
+
+fn play_match(config: Arc<Config>, engine: Engine, opponent: Option<Arc<Config>>, matches: &Matches) -> Result<Results, String> {
+    let s = matches.opt_str("selfplay").or(matches.opt_str("match")).unwrap();
+    let games: usize = try!(s.parse().map_err(|_| format!("invalid number of games '{}'", s)));
+    let sgf_dir = matches.opt_str("sgf-dir");
+    if let Some(ref dir) = sgf_dir {
+        try!(create_dir_all(dir).map_err(|e| format!("can't create {}: {}", dir, e)));
+    }
+    let mut referee = try!(referee(config, engine, opponent, matches));
+    let mut results = Results::new();
+    let mut summary = String::new();
+    for game in 0..games {
+        // Alternate the colors so that the komi doesn't favour one
+        // of the players.
+        let record = try!(referee.play(game % 2 == 0));
+        if let Some(ref dir) = sgf_dir {
+            let path = Path::new(dir).join(format!("game-{:04}.sgf", game + 1));
+            try!(record.save(&path.to_string_lossy()));
+        }
+        results.add(&record);
+        let line = format!("Game {}: {}", game + 1, record);
+        println!("{}", line);
+        summary.push_str(&line);
+        summary.push('\n');
+    }
+    if let Some(ref dir) = sgf_dir {
+        let path = Path::new(dir).join("results.txt");
+        summary.push_str(&format!("{}\n", results));
+        try!(File::create(&path)
+             .and_then(|mut file| file.write_all(summary.as_bytes()))
+             .map_err(|e| format!("can't write {}: {}", path.display(), e)));
+    }
+    Ok(results)
+}
+
+// Returns false if any of the tests failed.
+fn regress(config: Arc<Config>, engine: Engine, matches: &Matches) -> Result<bool, String> {
+    let mut interpreter = GTPInterpreter::new(config, engine);
+    let mut results = vec!();
+    for file in matches.opt_strs("regress") {
+        let suite = try!(Suite::from_file(&file));
+        for result in suite.run(&mut interpreter) {
+            println!("{}", result);
+            results.push(result);
+        }
+    }
+    println!("{}", regress::summary(&results));
+    if let Some(filename) = matches.opt_str("regress-results") {
+        let lines: Vec<String> = results.iter().map(|r| r.to_tsv()).collect();
+        try!(File::create(&filename)
+             .and_then(|mut file| file.write_all(format!("{}\n", lines.join("\n")).as_bytes()))
+             .map_err(|e| format!("can't write {}: {}", filename, e)));
+    }
+    Ok(results.iter().all(|r| r.outcome != Outcome::Fail))
+}
+
+fn build_book(config: &Config, engine: &mut Engine, matches: &Matches) -> Result<Book, String> {
+    let size = match matches.opt_str("book-size") {
+        Some(s) => try!(s.parse()),
+        None => Size::square(19),
+    };
+    let mut book = Book::new(size);
+    let max_depth = config.book.max_depth;
+    for dir in matches.opt_strs("book-sgf") {
+        let entries = try!(read_dir(&dir).map_err(|e| format!("can't read {}: {}", dir, e)));
+        let mut games = 0;
+        for entry in entries {
+            let path = try!(entry.map_err(|e| e.to_string())).path();
+            if path.extension().map_or(true, |ext| ext != "sgf") {
+                continue;
+            }
+            match Parser::from_path(&path) {
+                Ok(parser) => if book.add_game(&parser, max_depth) { games += 1; },
+                Err(error) => warn!(config, "{}: {}", path.display(), error),
+            }
+        }
+        info!(config, "{} games added from {}", games, dir);
+    }
+    if let Some(s) = matches.opt_str("book-searches") {
+        let searches: usize = try!(s.parse().map_err(|_| format!("invalid number of searches '{}'", s)));
+        for _ in 0..searches {
+            let mut game = Game::with_super_ko(size, 6.5, config.ruleset, config.super_ko);
+            for _ in 0..max_depth {
+                let m = engine.search_best_move(&game, config.book.playouts);
+                if m.is_pass() {
+                    break;
+                }
+                book.add(&game, m);
+                game = try!(game.play(m).map_err(|e| format!("{:?}", e)));
+            }
+        }
+    }
+    Ok(book)
+}
+
+fn solve_tsumego(config: &Config, files: Vec<String>) {
+    let solver = Solver::new(&config.tsumego);
+    for file in files {
+        let problem = Parser::from_path(Path::new(&file))
+            .map_err(|e| e.to_string())
+            .and_then(|parser| Problem::from_sgf(&parser));
+        match problem {
+            Ok(problem) => println!("{}: {}", file, solver.solve(&problem)),
+            Err(error) => println!("{}: {}", file, error),
+        }
     }
 }
@@ -24,6 +24,7 @@ use board::Black;
 use board::Color;
 use board::Coord;
 use board::Empty;
+use board::Size;
 use board::White;
 use config::Config;
 use score::Score;
@@ -41,15 +42,15 @@ pub struct OwnershipStatistics {
     config: Arc<Config>,
     empty: Vec<usize>,
     komi: f32,
-    size: u8,
+    size: Size,
     white: Vec<usize>,
 }
 
 impl OwnershipStatistics {
 
-    pub fn new(config: Arc<Config>, size: u8, komi: f32) -> OwnershipStatistics {
+    pub fn new(config: Arc<Config>, size: Size, komi: f32) -> OwnershipStatistics {
         let prior = config.scoring.ownership_prior;
-        let len = size as usize * size as usize;
+        let len = size.points();
         OwnershipStatistics {
             black: vec![0; len],
             config: config,
@@ -138,8 +139,8 @@ impl Display for OwnershipStatistics {
 
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let mut s = String::new();
-        for row in (1u8..self.size+1).rev() {
-            for col in 1u8..self.size+1 {
+        for row in (1u8..self.size.height+1).rev() {
+            for col in 1u8..self.size.width+1 {
                 let coord = Coord::new(col, row);
                 s.push_str(&format!("{} ", self.value_for_coord(coord)));
             }
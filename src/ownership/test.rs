@@ -22,6 +22,7 @@
 #![cfg(test)]
 
 pub use board::Board;
+pub use board::Size;
 pub use config::Config;
 pub use ruleset::KgsChinese;
 pub use super::OwnershipStatistics;
@@ -42,7 +43,7 @@ describe! ownership {
 
             before_each {
                 let config = Arc::new(Config::test_config());
-                let stats = OwnershipStatistics::new(config, 3, 6.5);
+                let stats = OwnershipStatistics::new(config, Size::square(3), 6.5);
             }
 
             it "returns 0 by default" {
@@ -58,7 +59,7 @@ describe! ownership {
 #[bench]
 fn new(b: &mut Bencher) {
     let config = Arc::new(Config::test_config());
-    b.iter(|| OwnershipStatistics::new(config.clone(), 19, 6.5))
+    b.iter(|| OwnershipStatistics::new(config.clone(), Size::square(19), 6.5))
 
 }
 
@@ -67,7 +68,7 @@ fn merge(b: &mut Bencher) {
     let size = 19;
     let komi = 6.5;
     let config = Arc::new(Config::test_config());
-    let mut ownership = OwnershipStatistics::new(config.clone(), size, komi);
+    let mut ownership = OwnershipStatistics::new(config.clone(), Size::square(size), komi);
     let score = Board::new(size, komi, KgsChinese).score();
     b.iter(|| ownership.merge(&score))
 }
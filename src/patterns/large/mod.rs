@@ -0,0 +1,245 @@
+/************************************************************************
+ *                                                                      *
+ * Copyright 2016 Urban Hafner                                          *
+ *                                                                      *
+ * This file is part of Iomrascálaí.                                    *
+ *                                                                      *
+ * Iomrascálaí is free software: you can redistribute it and/or modify  *
+ * it under the terms of the GNU General Public License as published by *
+ * the Free Software Foundation, either version 3 of the License, or    *
+ * (at your option) any later version.                                  *
+ *                                                                      *
+ * Iomrascálaí is distributed in the hope that it will be useful,       *
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of       *
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the        *
+ * GNU General Public License for more details.                         *
+ *                                                                      *
+ * You should have received a copy of the GNU General Public License    *
+ * along with Iomrascálaí.  If not, see <http://www.gnu.org/licenses/>. *
+ *                                                                      *
+ ************************************************************************/
+
+use board::Board;
+use board::Color;
+use board::Coord;
+use board::Empty;
+use board::Move;
+
+use rand::Rng;
+use rand::SeedableRng;
+use rand::XorShiftRng;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::prelude::*;
+
+mod test;
+
+pub const MIN_RADIUS: usize = 2;
+pub const MAX_RADIUS: usize = 9;
+
+// Fixed seed so that the Zobrist keys (and therefore the hashes of
+// the patterns) are the same every time the program runs.
+const SEED: [u32; 4] = [0x193a6754, 0xa8a7d469, 0x97830e05, 0x113ba7bb];
+
+// The four states a point inside of a pattern can be in. The colors
+// are relative to the player that is about to move.
+const EMPTY: usize = 0;
+const OWN: usize = 1;
+const OPPONENT: usize = 2;
+const OFF_BOARD: usize = 3;
+
+// Large patterns (as used by Pachi, Fuego, etc.) are diamond shaped,
+// i.e. all points within a given Manhattan distance (the radius) of
+// the point that is about to be played. The points are ordered in a
+// spiral: First by the distance to the center and then clockwise
+// starting north of the center. A pattern of radius `d` contains
+// `2d(d+1)` points.
+pub struct Matcher {
+    keys: Vec<[u64; 4]>,
+    max_radius: usize,
+    offsets: Vec<(isize, isize)>,
+    patterns: HashMap<u64, f32>,
+}
+
+impl Matcher {
+
+    /// Returns a matcher without any patterns.
+    pub fn new() -> Matcher {
+        let mut rng = XorShiftRng::from_seed(SEED);
+        let offsets = Self::spiral(MAX_RADIUS);
+        let keys = offsets.iter()
+            .map(|_| [rng.gen(), rng.gen(), rng.gen(), rng.gen()])
+            .collect();
+        Matcher {
+            keys: keys,
+            max_radius: 0,
+            offsets: offsets,
+            patterns: HashMap::new(),
+        }
+    }
+
+    /// Reads the pattern dictionary at `filename`. See `from_str()`
+    /// for the format of the file.
+    pub fn from_file(filename: &str) -> Result<Matcher, String> {
+        let mut contents = String::new();
+        match File::open(filename) {
+            Ok(mut file) => match file.read_to_string(&mut contents) {
+                Ok(_) => Self::from_str(&contents),
+                Err(e) => Err(format!("Can't read {}: {}", filename, e))
+            },
+            Err(e) => Err(format!("Can't open {}: {}", filename, e))
+        }
+    }
+
+    /// Parses a pattern dictionary. Each line contains a weight (a
+    /// non-negative float) followed by the pattern. The pattern lists
+    /// the points of the diamond in spiral order using `X` for a
+    /// stone of the player to move, `O` for a stone of the opponent,
+    /// `.` for an empty intersection and `#` for a point off the
+    /// board. The length of the pattern determines its radius. Empty
+    /// lines and lines starting with `#` are ignored.
+    ///
+    /// All eight symmetries of a pattern are added to the dictionary.
+    pub fn from_str(s: &str) -> Result<Matcher, String> {
+        let mut matcher = Matcher::new();
+        for (index, line) in s.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            match matcher.parse_line(line) {
+                Ok((weight, radius, points)) => matcher.add(weight, radius, &points),
+                Err(e) => return Err(format!("line {}: {}", index + 1, e))
+            }
+        }
+        Ok(matcher)
+    }
+
+    pub fn len(&self) -> usize {
+        self.patterns.len()
+    }
+
+    /// Returns the weight of the largest pattern that matches the
+    /// move or `None` if no pattern matches at all.
+    pub fn weight(&self, board: &Board, m: &Move) -> Option<f32> {
+        if self.max_radius == 0 {
+            return None;
+        }
+        let coord = m.coord();
+        let color = *m.color();
+        let mut hash = 0;
+        let mut index = 0;
+        let mut weight = None;
+        for radius in 1..self.max_radius+1 {
+            let ring_end = Self::point_count(radius);
+            while index < ring_end {
+                let state = self.state_at(board, &coord, color, self.offsets[index]);
+                hash ^= self.keys[index][state];
+                index += 1;
+            }
+            if radius >= MIN_RADIUS {
+                if let Some(&w) = self.patterns.get(&hash) {
+                    weight = Some(w);
+                }
+            }
+        }
+        weight
+    }
+
+    fn state_at(&self, board: &Board, coord: &Coord, color: Color, (dx, dy): (isize, isize)) -> usize {
+        let col = coord.col as isize + dx;
+        let row = coord.row as isize + dy;
+        let size = board.size();
+        if col < 1 || row < 1 || col > size.width as isize || row > size.height as isize {
+            return OFF_BOARD;
+        }
+        let c = board.color(&Coord::new(col as u8, row as u8));
+        if c == Empty {
+            EMPTY
+        } else if c == color {
+            OWN
+        } else {
+            OPPONENT
+        }
+    }
+
+    fn parse_line(&self, line: &str) -> Result<(f32, usize, Vec<usize>), String> {
+        let mut parts = line.split_whitespace();
+        let weight = match parts.next().map(|w| w.parse::<f32>()) {
+            Some(Ok(w)) if w >= 0.0 => w,
+            _ => return Err(format!("expected a non-negative weight in {:?}", line))
+        };
+        let pattern = match parts.next() {
+            Some(p) => p,
+            None => return Err(format!("missing pattern in {:?}", line))
+        };
+        if parts.next().is_some() {
+            return Err(format!("unexpected data after pattern in {:?}", line));
+        }
+        let radius = match (MIN_RADIUS..MAX_RADIUS+1).find(|&r| Self::point_count(r) == pattern.len()) {
+            Some(r) => r,
+            None => return Err(format!("pattern of length {} doesn't have a valid radius", pattern.len()))
+        };
+        let mut points = Vec::with_capacity(pattern.len());
+        for c in pattern.chars() {
+            let state = match c {
+                '.' => EMPTY,
+                'X' => OWN,
+                'O' => OPPONENT,
+                '#' => OFF_BOARD,
+                _ => return Err(format!("unknown point {:?} in pattern", c))
+            };
+            points.push(state);
+        }
+        Ok((weight, radius, points))
+    }
+
+    fn add(&mut self, weight: f32, radius: usize, points: &Vec<usize>) {
+        for symmetry in 0..8 {
+            let mut hash = 0;
+            for (index, &state) in points.iter().enumerate() {
+                let offset = Self::transform(self.offsets[index], symmetry);
+                let transformed = self.offset_index(offset);
+                hash ^= self.keys[transformed][state];
+            }
+            self.patterns.insert(hash, weight);
+        }
+        if radius > self.max_radius {
+            self.max_radius = radius;
+        }
+    }
+
+    fn offset_index(&self, offset: (isize, isize)) -> usize {
+        self.offsets.iter().position(|&o| o == offset).unwrap()
+    }
+
+    fn transform((x, y): (isize, isize), symmetry: usize) -> (isize, isize) {
+        match symmetry {
+            0 => ( x,  y),
+            1 => (-y,  x),
+            2 => (-x, -y),
+            3 => ( y, -x),
+            4 => (-x,  y),
+            5 => ( x, -y),
+            6 => ( y,  x),
+            _ => (-y, -x),
+        }
+    }
+
+    /// The number of points in a pattern of the given radius.
+    pub fn point_count(radius: usize) -> usize {
+        2 * radius * (radius + 1)
+    }
+
+    fn spiral(max_radius: usize) -> Vec<(isize, isize)> {
+        let mut offsets = Vec::with_capacity(Self::point_count(max_radius));
+        for d in 1..max_radius as isize + 1 {
+            for i in 0..d { offsets.push(( i,      d - i)); }
+            for i in 0..d { offsets.push(( d - i, -i)); }
+            for i in 0..d { offsets.push((-i,     -(d - i))); }
+            for i in 0..d { offsets.push((-(d - i), i)); }
+        }
+        offsets
+    }
+
+}
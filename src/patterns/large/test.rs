@@ -0,0 +1,107 @@
+/************************************************************************
+ *                                                                      *
+ * Copyright 2016 Urban Hafner                                          *
+ *                                                                      *
+ * This file is part of Iomrascálaí.                                    *
+ *                                                                      *
+ * Iomrascálaí is free software: you can redistribute it and/or modify  *
+ * it under the terms of the GNU General Public License as published by *
+ * the Free Software Foundation, either version 3 of the License, or    *
+ * (at your option) any later version.                                  *
+ *                                                                      *
+ * Iomrascálaí is distributed in the hope that it will be useful,       *
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of       *
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the        *
+ * GNU General Public License for more details.                         *
+ *                                                                      *
+ * You should have received a copy of the GNU General Public License    *
+ * along with Iomrascálaí.  If not, see <http://www.gnu.org/licenses/>. *
+ *                                                                      *
+ ************************************************************************/
+
+#![cfg(test)]
+
+use board::Black;
+use board::Board;
+use board::Play;
+use board::White;
+use fixtures::load_board;
+use ruleset::KgsChinese;
+use super::Matcher;
+
+#[test]
+fn point_count() {
+    assert_eq!(12, Matcher::point_count(2));
+    assert_eq!(180, Matcher::point_count(9));
+}
+
+#[test]
+fn the_spiral_contains_every_point_once() {
+    let offsets = Matcher::spiral(9);
+    assert_eq!(180, offsets.len());
+    for (i, a) in offsets.iter().enumerate() {
+        assert!(offsets[i+1..].iter().all(|b| a != b));
+    }
+}
+
+#[test]
+fn an_empty_matcher_never_matches() {
+    let board = Board::new(9, 6.5, KgsChinese);
+    assert_eq!(None, Matcher::new().weight(&board, &Play(Black, 5, 5)));
+}
+
+#[test]
+fn loads_the_dictionary_from_a_file() {
+    let matcher = Matcher::from_file("fixtures/patterns/large.txt").unwrap();
+    let board = Board::new(19, 6.5, KgsChinese);
+    assert_eq!(Some(0.5), matcher.weight(&board, &Play(Black, 1, 1)));
+    assert_eq!(None, matcher.weight(&board, &Play(Black, 10, 10)));
+}
+
+#[test]
+fn matches_all_symmetries() {
+    let matcher = Matcher::from_str("0.5 ..##...#####").unwrap();
+    let board = Board::new(19, 6.5, KgsChinese);
+    assert_eq!(Some(0.5), matcher.weight(&board, &Play(Black, 1, 1)));
+    assert_eq!(Some(0.5), matcher.weight(&board, &Play(Black, 19, 1)));
+    assert_eq!(Some(0.5), matcher.weight(&board, &Play(Black, 1, 19)));
+    assert_eq!(Some(0.5), matcher.weight(&board, &Play(White, 19, 19)));
+    assert_eq!(None, matcher.weight(&board, &Play(Black, 2, 1)));
+}
+
+#[test]
+fn colors_are_relative_to_the_player_to_move() {
+    let matcher = Matcher::from_str("1.0 ...X........").unwrap();
+    let black = load_board("3x3/one-black-w");
+    let white = load_board("3x3/one-white-w");
+    assert_eq!(Some(1.0), matcher.weight(&black, &Play(Black, 5, 5)));
+    assert_eq!(None, matcher.weight(&black, &Play(White, 5, 5)));
+    assert_eq!(Some(1.0), matcher.weight(&white, &Play(White, 5, 5)));
+    assert_eq!(Some(1.0), matcher.weight(&load_board("3x3/one-black-e"), &Play(Black, 5, 5)));
+}
+
+#[test]
+fn the_largest_matching_pattern_wins() {
+    let dictionary = format!("0.1 {}\n0.9 {}", ".".repeat(12), ".".repeat(24));
+    let matcher = Matcher::from_str(&dictionary).unwrap();
+    let board = Board::new(19, 6.5, KgsChinese);
+    assert_eq!(Some(0.9), matcher.weight(&board, &Play(Black, 10, 10)));
+    // Radius three reaches the edge of the board on the third line
+    assert_eq!(Some(0.1), matcher.weight(&board, &Play(Black, 3, 10)));
+}
+
+#[test]
+fn reports_the_line_of_an_invalid_pattern() {
+    let result = Matcher::from_str("# comment\n\n0.5 ..##...####");
+    assert_eq!(Err("line 3: pattern of length 11 doesn't have a valid radius".to_string()), result.map(|m| m.len()));
+}
+
+#[test]
+fn rejects_unknown_points() {
+    assert!(Matcher::from_str("0.5 ..##...####?").is_err());
+}
+
+#[test]
+fn rejects_negative_weights() {
+    assert!(Matcher::from_str("-0.5 ..##...#####").is_err());
+}
@@ -20,110 +20,138 @@
  ************************************************************************/
 
 pub use self::pattern::Pattern;
+#[cfg(not(feature = "tree-walk-patterns"))]
+use board::Black;
 use board::Board;
+#[cfg(not(feature = "tree-walk-patterns"))]
+use board::Color;
 use board::Coord;
+#[cfg(not(feature = "tree-walk-patterns"))]
+use board::Empty;
+#[cfg(not(feature = "tree-walk-patterns"))]
+use board::White;
 use self::tree::Tree;
 
+use std::fs::File;
+use std::io::prelude::*;
+
+mod parser;
 mod pattern;
 mod point;
 mod test;
 mod tree;
 
+// There's one entry in the lookup tables for every possible value
+// of `Board::pattern_code()`.
+#[cfg(not(feature = "tree-walk-patterns"))]
+const CODES: usize = 1 << 16;
+
 pub struct Matcher {
-    tree: Tree
+    #[cfg(not(feature = "tree-walk-patterns"))]
+    counts: Vec<usize>,
+    #[cfg(feature = "tree-walk-patterns")]
+    tree: Tree,
+    #[cfg(not(feature = "tree-walk-patterns"))]
+    weights: Vec<f32>,
 }
 
 impl Matcher {
 
+    /// Uses the patterns defined in `defaults.txt`.
     pub fn new() -> Matcher {
-        Self::with_patterns(Self::expand_patterns(Self::patterns()))
+        match Self::from_str(Self::defaults()) {
+            Ok(matcher) => matcher,
+            Err(e) => panic!("Invalid default patterns: {}", e)
+        }
+    }
+
+    /// Reads the patterns from a file. See `defaults.txt` for a
+    /// description of the format.
+    pub fn from_file(filename: &str) -> Result<Matcher, String> {
+        let mut contents = String::new();
+        match File::open(filename) {
+            Ok(mut file) => match file.read_to_string(&mut contents) {
+                Ok(_) => Self::from_str(&contents).map_err(|e| format!("{}:\n{}", filename, e)),
+                Err(e) => Err(format!("Can't read {}: {}", filename, e))
+            },
+            Err(e) => Err(format!("Can't open {}: {}", filename, e))
+        }
+    }
+
+    pub fn from_str(s: &str) -> Result<Matcher, String> {
+        let patterns = try!(parser::parse(s));
+        Ok(Self::with_patterns(Self::expand_patterns(patterns)))
+    }
+
+    /// Returns the patterns used by default in the same format that
+    /// `from_file()` accepts.
+    pub fn defaults() -> &'static str {
+        include_str!("defaults.txt")
+    }
+
+    // Walking the tree for every query is slow. Therefore we walk it
+    // once for every possible 3x3 neighbourhood and store the results
+    // in lookup tables indexed by the pattern code the board
+    // maintains for every point.
+    #[cfg(not(feature = "tree-walk-patterns"))]
+    fn with_patterns(patterns: Vec<Pattern>) -> Matcher {
+        let tree = Tree::from_patterns(patterns);
+        let mut counts = Vec::with_capacity(CODES);
+        let mut weights = Vec::with_capacity(CODES);
+        for code in 0..CODES {
+            let colors = Self::decode(code as u16);
+            counts.push(tree.count_for(&colors));
+            weights.push(tree.weight_for(&colors));
+        }
+        Matcher {
+            counts: counts,
+            weights: weights,
+        }
     }
 
+    // Without the pattern codes (see the `tree-walk-patterns`
+    // feature) every query walks the tree.
+    #[cfg(feature = "tree-walk-patterns")]
     fn with_patterns(patterns: Vec<Pattern>) -> Matcher {
-        Matcher { tree: Tree::from_patterns(patterns) }
+        Matcher {
+            tree: Tree::from_patterns(patterns),
+        }
     }
 
+    #[cfg(not(feature = "tree-walk-patterns"))]
+    fn decode(code: u16) -> Vec<Option<Color>> {
+        (0..8).map(|i| {
+            match (code >> (2 * i)) & 3 {
+                0 => Some(Empty),
+                1 => Some(Black),
+                2 => Some(White),
+                _ => None,
+            }
+        }).collect()
+    }
+
+    #[cfg(not(feature = "tree-walk-patterns"))]
+    pub fn pattern_count(&self, board: &Board, coord: &Coord) -> usize {
+        self.counts[board.pattern_code(coord) as usize]
+    }
+
+    #[cfg(feature = "tree-walk-patterns")]
     pub fn pattern_count(&self, board: &Board, coord: &Coord) -> usize {
         self.tree.pattern_count(board, coord)
     }
 
-    fn expand_patterns(patterns: Vec<Pattern>) -> Vec<Pattern> {
-        patterns.iter().flat_map(|pattern| pattern.expand()).collect()
+    #[cfg(not(feature = "tree-walk-patterns"))]
+    pub fn pattern_weight(&self, board: &Board, coord: &Coord) -> f32 {
+        self.weights[board.pattern_code(coord) as usize]
+    }
+
+    #[cfg(feature = "tree-walk-patterns")]
+    pub fn pattern_weight(&self, board: &Board, coord: &Coord) -> f32 {
+        self.tree.weight_for(&board.neighbours8_checked(*coord))
     }
 
-    // Patterns liften from michi.py
-    // * X, O are colors
-    // * . is an empty intersection
-    // * x, o are the opposites of X,O (i.e. other color or empty)
-    // * SPACE is off board
-    // * ? is any color, empty intersection, or off board
-    fn patterns() -> Vec<Pattern> {
-        vec!(
-            // hane pattern - enclosing hane
-            Pattern::new([
-                ['X', 'O', 'X'],
-                ['.', '.', '.'],
-                ['?', '?', '?']]),
-            // hane pattern - non-cutting hane
-            Pattern::new([
-                ['X', 'O', '.'],
-                ['.', '.', '.'],
-                ['?', '.', '?']]),
-            // hane pattern - magari
-            Pattern::new([
-                ['X', 'O', '?'],
-                ['X', '.', '.'],
-                ['x', '.', '?']]),
-            // generic pattern - katatsuke or diagonal attachment; similar to magari
-            Pattern::new([
-                ['.', 'O', '.'],
-                ['X', '.', '.'],
-                ['.', '.', '.']]),
-            // cut1 pattern (kiri] - unprotected cut
-            Pattern::new([
-                ['X', 'O', '?'],
-                ['O', '.', 'o'],
-                ['?', 'o', '?']]),
-            // cut1 pattern (kiri] - peeped cut
-            Pattern::new([
-                ['X', 'O', '?'],
-                ['O', '.', 'X'],
-                ['?', '?', '?']]),
-            // cut2 pattern (de]
-            Pattern::new([
-                ['?', 'X', '?'],
-                ['O', '.', 'O'],
-                ['o', 'o', 'o']]),
-            // cut keima
-            Pattern::new([
-                ['O', 'X', '?'],
-                ['o', '.', 'O'],
-                ['?', '?', '?']]),
-            // side pattern - chase
-            Pattern::new([
-                ['X', '.', '?'],
-                ['O', '.', '?'],
-                [' ', ' ', '?']]),
-            // side pattern - block side cut
-            Pattern::new([
-                ['O', 'X', '?'],
-                ['X', '.', 'O'],
-                [' ', ' ', ' ']]),
-            // side pattern - block side connection
-            Pattern::new([
-                ['?', 'X', '?'],
-                ['x', '.', 'O'],
-                [' ', ' ', ' ']]),
-            // side pattern - sagari
-            Pattern::new([
-                ['?', 'X', 'O'],
-                ['x', '.', 'x'],
-                [' ', ' ', ' ']]),
-            // side pattern - cut
-            Pattern::new([
-                ['?', 'O', 'X'],
-                ['X', '.', 'O'],
-                [' ', ' ', ' ']]))
+    fn expand_patterns(patterns: Vec<Pattern>) -> Vec<Pattern> {
+        patterns.iter().flat_map(|pattern| pattern.expand()).collect()
     }
 
 }
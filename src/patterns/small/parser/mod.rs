@@ -0,0 +1,141 @@
+/************************************************************************
+ *                                                                      *
+ * Copyright 2016 Urban Hafner                                          *
+ *                                                                      *
+ * This file is part of Iomrascálaí.                                    *
+ *                                                                      *
+ * Iomrascálaí is free software: you can redistribute it and/or modify  *
+ * it under the terms of the GNU General Public License as published by *
+ * the Free Software Foundation, either version 3 of the License, or    *
+ * (at your option) any later version.                                  *
+ *                                                                      *
+ * Iomrascálaí is distributed in the hope that it will be useful,       *
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of       *
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the        *
+ * GNU General Public License for more details.                         *
+ *                                                                      *
+ * You should have received a copy of the GNU General Public License    *
+ * along with Iomrascálaí.  If not, see <http://www.gnu.org/licenses/>. *
+ *                                                                      *
+ ************************************************************************/
+
+use super::Pattern;
+
+mod test;
+
+const POINTS: &'static str = "XOxo.? ";
+
+struct Header {
+    line: usize,
+    name: Option<String>,
+    weight: f32,
+}
+
+// Parses the text format of the 3x3 patterns (see defaults.txt for
+// an example). All problems found in the file are reported, each one
+// prefixed with the line number it was found on.
+pub fn parse(s: &str) -> Result<Vec<Pattern>, String> {
+    let mut patterns = vec!();
+    let mut errors = vec!();
+    let mut header: Option<Header> = None;
+    let mut rows: Vec<[char; 3]> = vec!();
+    let mut first_row = 0;
+    for (index, raw_line) in s.lines().enumerate() {
+        let line_number = index + 1;
+        let line = raw_line.trim();
+        if line.starts_with('|') {
+            if rows.is_empty() {
+                first_row = line_number;
+            }
+            match parse_row(line) {
+                Ok(row) => rows.push(row),
+                Err(e) => {
+                    errors.push(format!("line {}: {}", line_number, e));
+                    // Keep counting the rows so that the following
+                    // patterns are still read correctly.
+                    rows.push(['?', '.', '?']);
+                }
+            }
+            if rows.len() == 3 {
+                match build(&rows, header.take()) {
+                    Ok(pattern) => patterns.push(pattern),
+                    Err(e) => errors.push(format!("line {}: {}", first_row, e)),
+                }
+                rows.clear();
+            }
+            continue;
+        }
+        if !rows.is_empty() {
+            errors.push(format!("line {}: incomplete pattern, expected 3 rows but found {}", first_row, rows.len()));
+            rows.clear();
+        }
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if let Some(h) = header.take() {
+            errors.push(format!("line {}: pattern header without any rows", h.line));
+        }
+        match parse_header(line, line_number) {
+            Ok(h) => header = Some(h),
+            Err(e) => errors.push(format!("line {}: {}", line_number, e)),
+        }
+    }
+    if !rows.is_empty() {
+        errors.push(format!("line {}: incomplete pattern, expected 3 rows but found {}", first_row, rows.len()));
+    }
+    if let Some(h) = header {
+        errors.push(format!("line {}: pattern header without any rows", h.line));
+    }
+    if errors.is_empty() {
+        Ok(patterns)
+    } else {
+        Err(errors.join("\n"))
+    }
+}
+
+fn parse_row(line: &str) -> Result<[char; 3], String> {
+    if line.len() < 2 || !line.ends_with('|') {
+        return Err(format!("row {:?} must be enclosed in |", line));
+    }
+    let points: Vec<char> = line[1..line.len()-1].chars().collect();
+    if points.len() != 3 {
+        return Err(format!("row {:?} must contain exactly 3 points", line));
+    }
+    match points.iter().find(|c| !POINTS.contains(**c)) {
+        Some(c) => Err(format!("unknown point {:?}", c)),
+        None => Ok([points[0], points[1], points[2]])
+    }
+}
+
+fn parse_header(line: &str, line_number: usize) -> Result<Header, String> {
+    let parts: Vec<&str> = line.split_whitespace().collect();
+    if parts[0] != "pattern" {
+        return Err(format!("expected a pattern header or row but found {:?}", line));
+    }
+    let weight = match parts.get(2) {
+        Some(w) => match w.parse::<f32>() {
+            Ok(weight) if weight >= 0.0 => weight,
+            _ => return Err(format!("weight {:?} isn't a non-negative number", w))
+        },
+        None => 1.0
+    };
+    if parts.len() > 3 {
+        return Err(format!("unexpected data after the weight in {:?}", line));
+    }
+    Ok(Header {
+        line: line_number,
+        name: parts.get(1).map(|n| n.to_string()),
+        weight: weight,
+    })
+}
+
+fn build(rows: &Vec<[char; 3]>, header: Option<Header>) -> Result<Pattern, String> {
+    if rows[1][1] != '.' {
+        return Err(format!("the center point must be empty ('.') but is {:?}", rows[1][1]));
+    }
+    let points = [rows[0], rows[1], rows[2]];
+    Ok(match header {
+        Some(h) => Pattern::with_info(points, h.name, h.weight),
+        None => Pattern::new(points)
+    })
+}
@@ -0,0 +1,103 @@
+/************************************************************************
+ *                                                                      *
+ * Copyright 2016 Urban Hafner                                          *
+ *                                                                      *
+ * This file is part of Iomrascálaí.                                    *
+ *                                                                      *
+ * Iomrascálaí is free software: you can redistribute it and/or modify  *
+ * it under the terms of the GNU General Public License as published by *
+ * the Free Software Foundation, either version 3 of the License, or    *
+ * (at your option) any later version.                                  *
+ *                                                                      *
+ * Iomrascálaí is distributed in the hope that it will be useful,       *
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of       *
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the        *
+ * GNU General Public License for more details.                         *
+ *                                                                      *
+ * You should have received a copy of the GNU General Public License    *
+ * along with Iomrascálaí.  If not, see <http://www.gnu.org/licenses/>. *
+ *                                                                      *
+ ************************************************************************/
+
+#![cfg(test)]
+
+use super::parse;
+use super::super::Pattern;
+
+#[test]
+fn parses_a_pattern_without_a_header() {
+    let patterns = parse("|XO.|\n|x.?|\n|   |\n").unwrap();
+    let expected = Pattern::new([
+        ['X', 'O', '.'],
+        ['x', '.', '?'],
+        [' ', ' ', ' ']]);
+    assert_eq!(vec!(expected), patterns);
+}
+
+#[test]
+fn parses_the_name_and_the_weight() {
+    let patterns = parse("pattern hane 2.5\n|XOX|\n|...|\n|???|").unwrap();
+    assert_eq!(1, patterns.len());
+    assert_eq!(Some("hane"), patterns[0].name());
+    assert_eq!(2.5, patterns[0].weight());
+}
+
+#[test]
+fn the_weight_defaults_to_one() {
+    let patterns = parse("pattern hane\n|XOX|\n|...|\n|???|").unwrap();
+    assert_eq!(1.0, patterns[0].weight());
+}
+
+#[test]
+fn ignores_comments_and_whitespace_around_rows() {
+    let patterns = parse("# comment\n\n  |XOX|  \n\t|...|\n|???|\n\n# another\n").unwrap();
+    assert_eq!(1, patterns.len());
+}
+
+#[test]
+fn parses_the_default_patterns() {
+    let patterns = parse(include_str!("../defaults.txt")).unwrap();
+    assert_eq!(13, patterns.len());
+}
+
+#[test]
+fn reports_unknown_points() {
+    let error = parse("|XOZ|\n|...|\n|???|").unwrap_err();
+    assert_eq!("line 1: unknown point 'Z'", error);
+}
+
+#[test]
+fn reports_rows_with_the_wrong_length() {
+    let error = parse("|XO|\n|...|\n|???|").unwrap_err();
+    assert_eq!("line 1: row \"|XO|\" must contain exactly 3 points", error);
+}
+
+#[test]
+fn reports_an_occupied_center() {
+    let error = parse("\n|XOX|\n|.X.|\n|???|").unwrap_err();
+    assert_eq!("line 2: the center point must be empty ('.') but is 'X'", error);
+}
+
+#[test]
+fn reports_incomplete_patterns() {
+    let error = parse("|XOX|\n|...|\n\n|XOX|").unwrap_err();
+    assert_eq!("line 1: incomplete pattern, expected 3 rows but found 2\nline 4: incomplete pattern, expected 3 rows but found 1", error);
+}
+
+#[test]
+fn reports_invalid_weights() {
+    let error = parse("pattern hane heavy\n|XOX|\n|...|\n|???|").unwrap_err();
+    assert_eq!("line 1: weight \"heavy\" isn't a non-negative number", error);
+}
+
+#[test]
+fn reports_headers_without_rows() {
+    let error = parse("pattern one\npattern two\n|XOX|\n|...|\n|???|").unwrap_err();
+    assert_eq!("line 1: pattern header without any rows", error);
+}
+
+#[test]
+fn reports_garbage() {
+    let error = parse("XOX\n").unwrap_err();
+    assert_eq!("line 1: expected a pattern header or row but found \"XOX\"", error);
+}
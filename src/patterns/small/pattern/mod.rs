@@ -24,14 +24,20 @@ use super::point::Point;
 
 mod test;
 
-#[derive(Clone, Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct Pattern {
-    points: [Point; 8]
+    name: Option<String>,
+    points: [Point; 8],
+    weight: f32,
 }
 
 impl Pattern {
 
     pub fn new(vec: [[char; 3]; 3]) -> Pattern {
+        Self::with_info(vec, None, 1.0)
+    }
+
+    pub fn with_info(vec: [[char; 3]; 3], name: Option<String>, weight: f32) -> Pattern {
         let points = [
             Point::from_char(vec[0][0]),  // NW
             Point::from_char(vec[0][1]),  // N
@@ -41,7 +47,7 @@ impl Pattern {
             Point::from_char(vec[2][1]),  // S
             Point::from_char(vec[2][0]),  // SW
             Point::from_char(vec[1][0])]; // W
-        Pattern { points: points }
+        Pattern { name: name, points: points, weight: weight }
     }
 
     pub fn raw(vec: [[Point; 3]; 3]) -> Pattern {
@@ -54,7 +60,15 @@ impl Pattern {
             vec[2][1],  // S
             vec[2][0],  // SW
             vec[1][0]]; // W
-        Pattern { points: points }
+        Pattern { name: None, points: points, weight: 1.0 }
+    }
+
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_ref().map(|n| n.as_str())
+    }
+
+    pub fn weight(&self) -> f32 {
+        self.weight
     }
 
     pub fn expand(&self) -> Vec<Pattern> {
@@ -100,11 +114,11 @@ impl Pattern {
         for i in 0..self.points.len() {
             swapped[i] = self.points[i].swap();
         }
-        Pattern { points: swapped }
+        Pattern { points: swapped, ..self.clone() }
     }
 
     fn rotated90(&self) -> Pattern {
-        Pattern::raw([
+        self.with_points([
             [self.at(2,0), self.at(1,0), self.at(0,0)],
             [self.at(2,1), self.at(1,1), self.at(0,1)],
             [self.at(2,2), self.at(1,2), self.at(0,2)]])
@@ -119,12 +133,21 @@ impl Pattern {
     }
 
     fn mirrored(&self) -> Pattern {
-        Pattern::raw([
+        self.with_points([
             [self.at(2,0), self.at(2,1), self.at(2,2)],
             [self.at(1,0), self.at(1,1), self.at(1,2)],
             [self.at(0,0), self.at(0,1), self.at(0,2)]])
     }
 
+    // Same as `raw()` but keeps the name and the weight.
+    fn with_points(&self, vec: [[Point; 3]; 3]) -> Pattern {
+        Pattern {
+            name: self.name.clone(),
+            weight: self.weight,
+            ..Pattern::raw(vec)
+        }
+    }
+
     fn at(&self, row: usize, col: usize) -> Point {
         self.as_point_array()[row][col]
     }
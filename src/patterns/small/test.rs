@@ -23,6 +23,7 @@
 
 pub use hamcrest::prelude::*;
 pub use std::path::Path;
+pub use test::Bencher;
 
 pub use board::Board;
 pub use board::Coord;
@@ -30,6 +31,8 @@ pub use sgf::Parser;
 
 pub use super::Matcher;
 pub use super::Pattern;
+pub use super::parser::parse;
+pub use super::tree::Tree;
 
 describe! expand_patterns {
 
@@ -134,3 +137,63 @@ describe! pattern_count {
         assert_that!(matcher.pattern_count(board, off_center), is(equal_to(0)));
     }
 }
+
+describe! from_file {
+
+    it "reads the patterns from the file" {
+        let matcher = Matcher::from_file("fixtures/patterns/small.txt").unwrap();
+        let board = &board_from_sgf("3x3/one-black-w.sgf");
+        // Both patterns match twice: once directly (or swapped) and
+        // once mirrored.
+        assert_that!(matcher.pattern_count(board, &Coord::new(5, 5)), is(equal_to(4)));
+        assert_that!(matcher.pattern_weight(board, &Coord::new(5, 5)), is(equal_to(7.0)));
+        assert_that!(matcher.pattern_weight(board, &Coord::new(4, 4)), is(equal_to(0.0)));
+    }
+
+    it "reports missing files" {
+        assert!(Matcher::from_file("fixtures/patterns/missing.txt").is_err());
+    }
+
+    it "prefixes the errors with the file name" {
+        let error = Matcher::from_file("fixtures/patterns/large.txt").err().unwrap();
+        assert!(error.starts_with("fixtures/patterns/large.txt:\nline 5:"));
+    }
+
+}
+
+describe! new {
+
+    it "uses the default patterns" {
+        let matcher = Matcher::new();
+        let board = &board_from_sgf("empty.sgf");
+        assert_that!(matcher.pattern_count(board, &Coord::new(10, 10)), is(equal_to(0)));
+    }
+
+}
+
+// Compare these two to see the speedup of looking up the
+// incrementally updated pattern codes over walking the tree. Run
+// `cargo bench playout` with and without the `tree-walk-patterns`
+// feature to see the effect on the playouts, including the cost of
+// keeping the codes up to date.
+
+#[bench]
+fn pattern_count_lookup(b: &mut Bencher) {
+    let matcher = Matcher::new();
+    let board = board_from_sgf("ladders.sgf");
+    let coords = Coord::for_board_size(board.size());
+    b.iter(|| {
+        coords.iter().map(|c| matcher.pattern_count(&board, c)).sum::<usize>()
+    });
+}
+
+#[bench]
+fn pattern_count_tree_walk(b: &mut Bencher) {
+    let patterns = parse(Matcher::defaults()).unwrap();
+    let tree = Tree::from_patterns(Matcher::expand_patterns(patterns));
+    let board = board_from_sgf("ladders.sgf");
+    let coords = Coord::for_board_size(board.size());
+    b.iter(|| {
+        coords.iter().map(|c| tree.pattern_count(&board, c)).sum::<usize>()
+    });
+}
@@ -21,8 +21,10 @@
 
 pub use super::Pattern;
 use board::Black;
+#[cfg(any(test, feature = "tree-walk-patterns"))]
 use board::Board;
 use board::Color;
+#[cfg(any(test, feature = "tree-walk-patterns"))]
 use board::Coord;
 use board::Empty;
 use board::White;
@@ -35,6 +37,7 @@ mod test;
 #[derive(PartialEq)]
 pub struct Tree {
     count: usize,
+    weight: f32,
     black: Option<Box<Tree>>,
     white: Option<Box<Tree>>,
     empty: Option<Box<Tree>>,
@@ -46,6 +49,7 @@ impl Tree {
     pub fn empty() -> Tree {
         Tree {
             count: 0,
+            weight: 0.0,
             black: None,
             white: None,
             empty: None,
@@ -60,14 +64,44 @@ impl Tree {
         }
     }
 
+    // Only used without the pattern codes of the board (see the
+    // `tree-walk-patterns` feature) and to compare with them.
+    #[cfg(any(test, feature = "tree-walk-patterns"))]
     pub fn pattern_count(&self, board: &Board, coord: &Coord) -> usize {
         let colors = board.neighbours8_checked(*coord);
         self.walk(colors, 0, &self)
     }
 
+    /// Number of patterns that match the colors of the eight
+    /// neighbours (`None` is off board).
+    #[cfg(not(feature = "tree-walk-patterns"))]
+    pub fn count_for(&self, colors: &Vec<Option<Color>>) -> usize {
+        match self.find(colors, 0, &self) {
+            Some(leaf) => leaf.count,
+            None => 0
+        }
+    }
+
+    /// Sum of the weights of all patterns that match the colors of
+    /// the eight neighbours (`None` is off board).
+    pub fn weight_for(&self, colors: &Vec<Option<Color>>) -> f32 {
+        match self.find(colors, 0, &self) {
+            Some(leaf) => leaf.weight,
+            None => 0.0
+        }
+    }
+
+    #[cfg(any(test, feature = "tree-walk-patterns"))]
     fn walk(&self, colors: Vec<Option<Color>>, i: usize, subtree: &Tree) -> usize {
+        match self.find(&colors, i, subtree) {
+            Some(leaf) => leaf.count,
+            None => 0
+        }
+    }
+
+    fn find<'a>(&self, colors: &Vec<Option<Color>>, i: usize, subtree: &'a Tree) -> Option<&'a Tree> {
         if colors.len() == i {
-            return subtree.count;
+            return Some(subtree);
         }
         let child = match colors[i] {
             Some(color) => {
@@ -80,8 +114,8 @@ impl Tree {
             None => &subtree.off_board
         };
         match child {
-            &Some(ref c) => self.walk(colors, i + 1, c),
-            &None => 0
+            &Some(ref c) => self.find(colors, i + 1, c),
+            &None => None
         }
     }
 
@@ -104,6 +138,7 @@ impl Tree {
                 level + 1);
             let node = Tree {
                 count: count,
+                weight: patterns.iter().map(|p| p.weight()).sum(),
                 black: bn,
                 white: wn,
                 empty: en,
@@ -37,51 +37,60 @@ pub fn black_tree() -> Tree {
     Tree {
         // root
         count: 1,
+        weight: 1.0,
         empty: Some(Box::new(Tree {
             // ply 1
             count: 1,
+            weight: 1.0,
             black: None,
             white: None,
             off_board: None,
             empty: Some(Box::new(Tree {
                 // ply 2
                 count: 1,
+                weight: 1.0,
                 black: None,
                 white: None,
                 off_board: None,
                 empty: Some(Box::new(Tree {
                     // ply 3
                     count: 1,
+                    weight: 1.0,
                     black: None,
                     white: None,
                     off_board: None,
                     empty: Some(Box::new(Tree {
                         // ply 4
                         count: 1,
+                        weight: 1.0,
                         black: None,
                         white: None,
                         off_board: None,
                         empty: Some(Box::new(Tree {
                             // ply 5
                             count: 1,
+                            weight: 1.0,
                             black: None,
                             white: None,
                             off_board: None,
                             empty: Some(Box::new(Tree {
                                 // ply 6
                                 count: 1,
+                                weight: 1.0,
                                 black: None,
                                 white: None,
                                 off_board: None,
                                 empty: Some(Box::new(Tree {
                                     // ply 7
                                     count: 1,
+                                    weight: 1.0,
                                     empty: None,
                                     white: None,
                                     off_board: None,
                                     black: Some(Box::new(Tree {
                                         // ply 8
                                         count: 1,
+                                        weight: 1.0,
                                         black: None,
                                         white: None,
                                         off_board: None,
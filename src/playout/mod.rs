@@ -24,9 +24,11 @@
 use board::Board;
 use board::Color;
 use board::Coord;
+use board::Empty;
 use board::Move;
 use board::Pass;
 use board::Play;
+use board::Size;
 use config::Config;
 use patterns::SmallPatternMatcher;
 use score::Score;
@@ -56,6 +58,15 @@ impl Playout {
     }
 
     pub fn run(&self, board: &mut Board, initial_move: Option<&Move>, rng: &mut XorShiftRng) -> PlayoutResult {
+        let settled = board.settled_points();
+        self.run_with_settled_points(board, initial_move, &settled, rng)
+    }
+
+    /// Same as `run()` but with the settled points (see
+    /// `Board::settled_points()`) calculated beforehand, e.g. once
+    /// for all the playouts of a search. Pass-alive territory stays
+    /// settled as long as nobody plays inside it.
+    pub fn run_with_settled_points(&self, board: &mut Board, initial_move: Option<&Move>, settled: &Vec<bool>, rng: &mut XorShiftRng) -> PlayoutResult {
         let mut played_moves = Vec::new();
         let mut amaf = HashMap::new();
         initial_move.map(|&m| {
@@ -66,28 +77,45 @@ impl Playout {
             }
         });
 
+        // A repeated position means that the playout is stuck in a
+        // ko cycle that superko would forbid. The playout ends there
+        // and the position gets scored as it is. Any move can repeat
+        // a position, but a cycle back to the same position has to
+        // contain a move that removes stones (every other move adds
+        // one). Remembering only the positions after those moves
+        // catches a cycle on its second round at the latest, and
+        // `max_moves` bounds whatever slips through.
+        let mut positions = Vec::new();
         let max_moves = self.max_moves(board.size());
         while !board.is_game_over() && played_moves.len() < max_moves {
             let heuristic_set = self.heuristic_set(&played_moves, board, rng);
-            let m = self.select_move(board, heuristic_set, rng);
+            let m = self.select_move(board, heuristic_set, settled, rng);
             board.play_legal_move(m);
             played_moves.push(m);
             if !m.is_pass() && !amaf.contains_key(&m.coord()) {
                 amaf.insert(m.coord(), *m.color());
             }
+            if !m.is_pass() && (board.adv_stones_removed().len() > 0 || board.color(&m.coord()) == Empty) {
+                let hash = board.hash();
+                if positions.contains(&hash) {
+                    break;
+                }
+                positions.push(hash);
+            }
         }
         PlayoutResult::new(board.score(), amaf)
     }
 
     //don't self atari strings that will make an eye after dying, which is strings of 7+
-    fn is_playable(&self, board: &Board, m: &Move) -> bool {
-        !board.is_eye(&m.coord(), *m.color()) &&
+    fn is_playable(&self, board: &Board, m: &Move, settled: &Vec<bool>) -> bool {
+        !Board::is_settled(settled, board.size(), &m.coord()) &&
+            !board.is_eye(&m.coord(), *m.color()) &&
             (board.is_not_self_atari(m) ||
              board.new_chain_length_less_than(*m, ATARI_CUTOFF)) //suicide for smaller groups is ok
     }
 
-    fn max_moves(&self, size: u8) -> usize {
-        size as usize * size as usize * 3
+    fn max_moves(&self, size: Size) -> usize {
+        size.points() * 3
     }
 
     fn heuristic_set(&self, played_moves: &Vec<Move>, board: &Board, rng: &mut XorShiftRng) -> Vec<Coord> {
@@ -112,7 +140,7 @@ impl Playout {
         coords
     }
 
-    fn select_move(&self, board: &Board, heuristic_set: Vec<Coord>, rng: &mut XorShiftRng) -> Move {
+    fn select_move(&self, board: &Board, heuristic_set: Vec<Coord>, settled: &Vec<bool>, rng: &mut XorShiftRng) -> Move {
         let color = board.next_player();
 
         if self.check_for_atari(rng) {
@@ -122,12 +150,12 @@ impl Playout {
             }
         }
         if self.use_patterns(rng) {
-            let possible_move = self.small_pattern_move(color, &heuristic_set, board);
+            let possible_move = self.small_pattern_move(color, &heuristic_set, board, settled);
             if possible_move.is_some() {
                 return possible_move.unwrap();
             }
         }
-        self.random_move(color, board, rng)
+        self.random_move(color, board, settled, rng)
     }
 
     // If own group of more than one stone has one liberty, check if it can be captured
@@ -154,13 +182,14 @@ impl Playout {
         }
     }
 
-    fn small_pattern_move(&self, color: Color, coords: &Vec<Coord>, board: &Board) -> Option<Move> {
+    fn small_pattern_move(&self, color: Color, coords: &Vec<Coord>, board: &Board, settled: &Vec<bool>) -> Option<Move> {
         // This works as coords is randomly ordered, so taking the
         // first we find is OK.
         coords.iter()
             .map(|c| Play(color, c.col, c.row))
             .find(|&m| {
-                board.is_legal(m).is_ok() && self.small_pattern_matches(board, &m)
+                !Board::is_settled(settled, board.size(), &m.coord()) &&
+                    board.is_legal(m).is_ok() && self.small_pattern_matches(board, &m)
             })
     }
 
@@ -168,12 +197,12 @@ impl Playout {
         self.small_pattern_matcher.pattern_count(board, &m.coord()) > 0
     }
 
-    fn random_move(&self, color: Color, board: &Board, rng: &mut XorShiftRng) -> Move {
+    fn random_move(&self, color: Color, board: &Board, settled: &Vec<bool>, rng: &mut XorShiftRng) -> Move {
         let vacant = board.vacant();
         let playable_move = vacant
             .iter()
             .map(|c| Play(color, c.col, c.row))
-            .position(|m| board.is_legal(m).is_ok() && self.is_playable(board, &m));
+            .position(|m| board.is_legal(m).is_ok() && self.is_playable(board, &m, settled));
         if let Some(first) = playable_move {
             let mut include_pass = 0;
             loop {
@@ -184,7 +213,7 @@ impl Playout {
                 }
                 let c = vacant[r];
                 let m = Play(color, c.col, c.row);
-                if board.is_legal(m).is_ok() && self.is_playable(board, &m) {
+                if board.is_legal(m).is_ok() && self.is_playable(board, &m, settled) {
                     if include_pass == 0 && !board.is_not_self_atari(&m) {
                         include_pass = 1; //try to pass in a seki sometimes
                     } else {
@@ -241,6 +270,11 @@ impl PlayoutResult {
         &self.score
     }
 
+    /// See `Score::set_real_komi()`.
+    pub fn set_real_komi(&mut self, komi: f32) {
+        self.score.set_real_komi(komi);
+    }
+
     pub fn amaf(&self) -> &HashMap<Coord,Color> {
         &self.amaf
     }
@@ -48,6 +48,13 @@ fn max_moves() {
     assert_eq!(1083, playout().max_moves(19));
 }
 
+#[test]
+fn runs_before_the_settled_points_are_known() {
+    let mut board = Board::new(9, 6.5, KgsChinese);
+    let mut rng = weak_rng();
+    playout().run_with_settled_points(&mut board, None, &vec!(), &mut rng);
+}
+
 #[bench]
 fn playout_09x09(b: &mut Bencher) {
     let board = Board::new(9, 6.5, KgsChinese);
@@ -0,0 +1,183 @@
+/************************************************************************
+ *                                                                      *
+ * Copyright 2016 Urban Hafner                                          *
+ *                                                                      *
+ * This file is part of Iomrascálaí.                                    *
+ *                                                                      *
+ * Iomrascálaí is free software: you can redistribute it and/or modify  *
+ * it under the terms of the GNU General Public License as published by *
+ * the Free Software Foundation, either version 3 of the License, or    *
+ * (at your option) any later version.                                  *
+ *                                                                      *
+ * Iomrascálaí is distributed in the hope that it will be useful,       *
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of       *
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the        *
+ * GNU General Public License for more details.                         *
+ *                                                                      *
+ * You should have received a copy of the GNU General Public License    *
+ * along with Iomrascálaí.  If not, see <http://www.gnu.org/licenses/>. *
+ *                                                                      *
+ ************************************************************************/
+
+use super::Player;
+
+use std::io::BufRead;
+use std::io::BufReader;
+use std::io::Write;
+use std::process::Child;
+use std::process::ChildStdin;
+use std::process::Command;
+use std::process::Stdio;
+use std::sync::mpsc::Receiver;
+use std::sync::mpsc::RecvTimeoutError;
+use std::sync::mpsc::channel;
+use std::thread;
+use std::time::Duration;
+
+/// A GTP engine that runs as a separate process (e.g. GNU Go). The
+/// commands are numbered so that a late answer to a command that
+/// timed out can't be mistaken for the answer to the next one.
+pub struct ExternalEngine {
+    child: Child,
+    command: String,
+    id: usize,
+    responses: Receiver<String>,
+    stdin: ChildStdin,
+}
+
+impl ExternalEngine {
+
+    /// Starts the engine. The command line is split at whitespace,
+    /// e.g. `gnugo --mode gtp --level 0`.
+    pub fn new(command: &str) -> Result<ExternalEngine, String> {
+        let mut parts = command.split_whitespace();
+        let program = try!(parts.next().ok_or("empty engine command".to_string()));
+        let mut child = try!(Command::new(program)
+            .args(&parts.collect::<Vec<&str>>())
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|e| format!("can't start '{}': {}", command, e)));
+        let stdin = child.stdin.take().unwrap();
+        let stdout = child.stdout.take().unwrap();
+        let (sender, responses) = channel();
+        // A GTP response ends with an empty line. The thread ends
+        // when the engine exits, which closes the channel.
+        thread::spawn(move || {
+            let mut response = String::new();
+            for line in BufReader::new(stdout).lines() {
+                let line = match line {
+                    Ok(line) => line.replace("\r", ""),
+                    Err(_) => break,
+                };
+                if line.trim().is_empty() {
+                    if !response.is_empty() {
+                        if sender.send(response.clone()).is_err() {
+                            break;
+                        }
+                        response.clear();
+                    }
+                } else {
+                    if !response.is_empty() {
+                        response.push('\n');
+                    }
+                    response.push_str(&line);
+                }
+            }
+        });
+        Ok(ExternalEngine {
+            child: child,
+            command: command.to_string(),
+            id: 0,
+            responses: responses,
+            stdin: stdin,
+        })
+    }
+
+    // Splits "=12 C3" into the id 12 and the result Ok("C3").
+    fn parse(response: &str) -> (Option<usize>, Result<String, String>) {
+        let success = response.starts_with('=');
+        let rest = &response[1..];
+        let digits = rest.chars().take_while(|c| c.is_digit(10)).count();
+        let id = rest[..digits].parse().ok();
+        let text = rest[digits..].trim().to_string();
+        if success {
+            (id, Ok(text))
+        } else {
+            (id, Err(text))
+        }
+    }
+
+    fn receive(&mut self, timeout: Option<Duration>) -> Option<Result<String, String>> {
+        loop {
+            let response = match timeout {
+                Some(timeout) => match self.responses.recv_timeout(timeout) {
+                    Ok(response) => response,
+                    Err(RecvTimeoutError::Timeout) => return None,
+                    Err(RecvTimeoutError::Disconnected) => return Some(self.exited()),
+                },
+                None => match self.responses.recv() {
+                    Ok(response) => response,
+                    Err(_) => return Some(self.exited()),
+                },
+            };
+            if !response.starts_with('=') && !response.starts_with('?') {
+                continue;
+            }
+            // Answers to commands that timed out earlier are skipped.
+            // Engines that don't echo the ids can't be told apart.
+            match Self::parse(&response) {
+                (Some(id), _) if id != self.id => continue,
+                (_, result) => return Some(result),
+            }
+        }
+    }
+
+    fn exited(&self) -> Result<String, String> {
+        Err(format!("'{}' exited", self.command))
+    }
+
+    fn write(&mut self, command: &str) -> Result<(), String> {
+        self.id += 1;
+        let line = format!("{} {}\n", self.id, command);
+        self.stdin.write_all(line.as_bytes())
+            .and_then(|_| self.stdin.flush())
+            .map_err(|e| format!("can't send '{}' to '{}': {}", command, self.command, e))
+    }
+
+}
+
+impl Player for ExternalEngine {
+
+    fn name(&mut self) -> String {
+        let name = self.send("name").unwrap_or(self.command.clone());
+        match self.send("version") {
+            Ok(ref version) if !version.is_empty() => format!("{} {}", name, version),
+            _ => name,
+        }
+    }
+
+    fn send(&mut self, command: &str) -> Result<String, String> {
+        try!(self.write(command));
+        self.receive(None).unwrap()
+    }
+
+    fn send_with_timeout(&mut self, command: &str, timeout: Duration) -> Option<Result<String, String>> {
+        if let Err(error) = self.write(command) {
+            return Some(Err(error));
+        }
+        self.receive(Some(timeout))
+    }
+}
+
+impl Drop for ExternalEngine {
+
+    fn drop(&mut self) {
+        if self.write("quit").is_ok() {
+            self.receive(Some(Duration::from_secs(1)));
+        }
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
@@ -0,0 +1,410 @@
+/************************************************************************
+ *                                                                      *
+ * Copyright 2016 Urban Hafner                                          *
+ *                                                                      *
+ * This file is part of Iomrascálaí.                                    *
+ *                                                                      *
+ * Iomrascálaí is free software: you can redistribute it and/or modify  *
+ * it under the terms of the GNU General Public License as published by *
+ * the Free Software Foundation, either version 3 of the License, or    *
+ * (at your option) any later version.                                  *
+ *                                                                      *
+ * Iomrascálaí is distributed in the hope that it will be useful,       *
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of       *
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the        *
+ * GNU General Public License for more details.                         *
+ *                                                                      *
+ * You should have received a copy of the GNU General Public License    *
+ * along with Iomrascálaí.  If not, see <http://www.gnu.org/licenses/>. *
+ *                                                                      *
+ ************************************************************************/
+
+pub use self::external::ExternalEngine;
+use board::Black;
+use board::Color;
+use board::Coord;
+use board::Empty;
+use board::Move;
+use board::Size;
+use board::White;
+use game::Game;
+use gtp::GTPInterpreter;
+use ruleset::Ruleset;
+use ruleset::SuperKo;
+use version;
+
+use std::fmt;
+use std::fs::File;
+use std::io::prelude::*;
+use std::time::Duration;
+use std::time::Instant;
+
+mod external;
+mod test;
+
+// Games that are still going on after this many moves per point of
+// the board are stopped and scored as they are.
+const MAX_MOVES_PER_POINT: usize = 3;
+// Time a player may use on top of the main time before the game is
+// lost on time (starting up and answering GTP commands isn't free).
+const TIME_MARGIN: u64 = 5;
+
+/// A program that plays in a match and is controlled with GTP
+/// commands.
+pub trait Player {
+
+    /// The name used in the SGF files.
+    fn name(&mut self) -> String;
+
+    /// Sends a GTP command (without a trailing newline) and returns
+    /// the response without the leading `=` or `?`.
+    fn send(&mut self, command: &str) -> Result<String, String>;
+
+    /// Like `send`, but waits at most `timeout` for the response and
+    /// returns `None` if there was none. Players that can't be
+    /// interrupted just answer late and are checked afterwards.
+    fn send_with_timeout(&mut self, command: &str, _: Duration) -> Option<Result<String, String>> {
+        Some(self.send(command))
+    }
+}
+
+impl<'a> Player for GTPInterpreter<'a> {
+
+    fn name(&mut self) -> String {
+        format!("Iomrascalai {}", version::version())
+    }
+
+    fn send(&mut self, command: &str) -> Result<String, String> {
+        self.read(&format!("{}\n", command))
+    }
+}
+
+/// Plays games between two players (A and B) and keeps track of the
+/// game itself. The moves are checked with our own `Game`, so a
+/// player that plays an illegal move, takes too long, or resigns
+/// loses the game, and finished games are scored by the referee.
+pub struct Referee<'a> {
+    a: Box<Player + 'a>,
+    b: Box<Player + 'a>,
+    komi: f32,
+    main_time: usize,
+    margin: Duration,
+    ruleset: Ruleset,
+    size: Size,
+    super_ko: SuperKo,
+}
+
+impl<'a> Referee<'a> {
+
+    pub fn new(a: Box<Player + 'a>, b: Box<Player + 'a>, ruleset: Ruleset, super_ko: SuperKo, size: Size, main_time: usize) -> Referee<'a> {
+        Referee {
+            a: a,
+            b: b,
+            komi: 6.5,
+            main_time: main_time,
+            margin: Duration::from_secs(TIME_MARGIN),
+            ruleset: ruleset,
+            size: size,
+            super_ko: super_ko,
+        }
+    }
+
+    /// Player A, e.g. to change its parameters between games.
+    pub fn a(&mut self) -> &mut Box<Player + 'a> {
+        &mut self.a
+    }
+
+    /// Plays one game until both players pass, one of them loses by
+    /// resignation, time, or forfeit, or the game gets too long.
+    pub fn play(&mut self, a_plays_black: bool) -> Result<GameRecord, String> {
+        try!(self.setup());
+        let limit = Duration::from_secs(self.main_time as u64) + self.margin;
+        let max_moves = MAX_MOVES_PER_POINT * self.size.points();
+        let mut game = Game::with_super_ko(self.size, self.komi, self.ruleset, self.super_ko);
+        let mut moves = vec!();
+        let mut passes = 0;
+        let mut result = None;
+        // Time used by black and white.
+        let mut used = [Duration::from_secs(0), Duration::from_secs(0)];
+        while passes < 2 && moves.len() < max_moves {
+            let color = game.next_player();
+            let gtp_color = if color == Black { "b" } else { "w" };
+            let index = if color == Black { 0 } else { 1 };
+            let a_to_move = (color == Black) == a_plays_black;
+            let started = Instant::now();
+            let response = {
+                let player = if a_to_move { &mut self.a } else { &mut self.b };
+                player.send_with_timeout(&format!("genmove {}", gtp_color), limit - used[index])
+            };
+            used[index] += started.elapsed();
+            let vertex = match response {
+                Some(Ok(vertex)) => vertex,
+                Some(Err(_)) => {
+                    result = Some(Self::outcome(color.opposite(), "F"));
+                    break;
+                }
+                None => {
+                    result = Some(Self::outcome(color.opposite(), "T"));
+                    break;
+                }
+            };
+            if used[index] > limit {
+                result = Some(Self::outcome(color.opposite(), "T"));
+                break;
+            }
+            let m = match Move::from_gtp(gtp_color, &vertex) {
+                Ok(m) => m,
+                Err(_) => {
+                    result = Some(Self::outcome(color.opposite(), "F"));
+                    break;
+                }
+            };
+            if m.is_resign() {
+                result = Some(Self::outcome(color.opposite(), "R"));
+                break;
+            }
+            game = match game.play(m) {
+                Ok(g) => g,
+                Err(_) => {
+                    result = Some(Self::outcome(color.opposite(), "F"));
+                    break;
+                }
+            };
+            {
+                let opponent = if a_to_move { &mut self.b } else { &mut self.a };
+                try!(opponent.send(&format!("play {} {}", gtp_color, vertex))
+                     .map_err(|e| format!("the opponent rejected {} {}: {}", gtp_color, vertex, e)));
+            }
+            passes = if m.is_pass() { passes + 1 } else { 0 };
+            moves.push(m);
+        }
+        let result = match result {
+            Some(result) => result,
+            None => self.score(&game),
+        };
+        let (black, white) = if a_plays_black {
+            (self.a.name(), self.b.name())
+        } else {
+            (self.b.name(), self.a.name())
+        };
+        Ok(GameRecord {
+            a_plays_black: a_plays_black,
+            black: black,
+            komi: self.komi,
+            moves: moves,
+            result: result,
+            ruleset: self.ruleset,
+            size: self.size,
+            white: white,
+        })
+    }
+
+    fn setup(&mut self) -> Result<(), String> {
+        let boardsize = if self.size.is_square() {
+            format!("boardsize {}", self.size.width)
+        } else {
+            format!("rectangular_boardsize {} {}", self.size.width, self.size.height)
+        };
+        let komi = format!("komi {}", self.komi);
+        let time_settings = format!("time_settings {} 0 0", self.main_time);
+        for player in vec!(&mut self.a, &mut self.b) {
+            try!(player.send(&boardsize));
+            try!(player.send("clear_board"));
+            try!(player.send(&komi));
+            try!(player.send(&time_settings));
+        }
+        Ok(())
+    }
+
+    // Only the stones that both players consider dead are removed
+    // before the board is scored. Players that don't know
+    // `final_status_list` don't consider any stones dead.
+    fn score(&mut self, game: &Game) -> String {
+        let dead_a = Self::dead_stones(&mut self.a);
+        let dead_b = Self::dead_stones(&mut self.b);
+        let mut board = game.board();
+        for coord in dead_a.iter().filter(|c| dead_b.contains(c)) {
+            board.remove_dead_stone(coord);
+        }
+        format!("{}", board.score())
+    }
+
+    fn dead_stones(player: &mut Box<Player + 'a>) -> Vec<Coord> {
+        match player.send("final_status_list dead") {
+            Ok(list) => list.split_whitespace()
+                .filter_map(|vertex| Coord::from_gtp(vertex).ok())
+                .collect(),
+            Err(_) => vec!(),
+        }
+    }
+
+    fn outcome(winner: Color, reason: &str) -> String {
+        let color = if winner == Black { "B" } else { "W" };
+        format!("{}+{}", color, reason)
+    }
+
+}
+
+/// A finished game between A and B.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GameRecord {
+    a_plays_black: bool,
+    black: String,
+    komi: f32,
+    moves: Vec<Move>,
+    result: String,
+    ruleset: Ruleset,
+    size: Size,
+    white: String,
+}
+
+impl GameRecord {
+
+    pub fn a_plays_black(&self) -> bool {
+        self.a_plays_black
+    }
+
+    /// The color of the winner or `Empty` for a draw.
+    pub fn winner(&self) -> Color {
+        match self.result.chars().next() {
+            Some('B') => Black,
+            Some('W') => White,
+            _ => Empty,
+        }
+    }
+
+    pub fn save(&self, filename: &str) -> Result<(), String> {
+        File::create(filename)
+            .and_then(|mut file| file.write_all(self.to_sgf().as_bytes()))
+            .map_err(|e| format!("can't write game {}: {}", filename, e))
+    }
+
+    pub fn to_sgf(&self) -> String {
+        let mut sgf = format!(
+            "(;GM[1]FF[4]AP[Iomrascalai:{}]SZ[{}]KM[{}]RU[{}]PB[{}]PW[{}]RE[{}]",
+            version::version(),
+            self.size.to_sgf(),
+            self.komi,
+            self.ruleset,
+            self.black,
+            self.white,
+            self.result);
+        for m in &self.moves {
+            let color = if *m.color() == Black { "B" } else { "W" };
+            let vertex = if m.is_pass() { String::new() } else { m.coord().to_sgf(self.size) };
+            sgf.push_str(&format!("\n;{}[{}]", color, vertex));
+        }
+        sgf.push_str(")\n");
+        sgf
+    }
+
+}
+
+impl fmt::Display for GameRecord {
+
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let color = if self.a_plays_black { "black" } else { "white" };
+        let s = format!("A as {}: {}", color, self.result);
+        s.fmt(f)
+    }
+}
+/// The results of A in a number of games against B.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Results {
+    black_wins: usize,
+    draws: usize,
+    losses: usize,
+    wins: usize,
+}
+
+impl Results {
+
+    pub fn new() -> Results {
+        Results {
+            black_wins: 0,
+            draws: 0,
+            losses: 0,
+            wins: 0,
+        }
+    }
+
+    pub fn add(&mut self, record: &GameRecord) {
+        let a_color = if record.a_plays_black() { Black } else { White };
+        match record.winner() {
+            Empty => self.draws += 1,
+            winner => {
+                if winner == Black {
+                    self.black_wins += 1;
+                }
+                if winner == a_color {
+                    self.wins += 1;
+                } else {
+                    self.losses += 1;
+                }
+            }
+        }
+    }
+
+    pub fn games(&self) -> usize {
+        self.wins + self.losses + self.draws
+    }
+
+    /// The share of the points won by A, where a draw counts as half
+    /// a win.
+    pub fn win_rate(&self) -> f64 {
+        if self.games() == 0 {
+            return 0.5;
+        }
+        (self.wins as f64 + 0.5 * self.draws as f64) / self.games() as f64
+    }
+
+    /// Half the width of the 95% confidence interval of the win rate
+    /// (using the normal approximation).
+    pub fn confidence(&self) -> f64 {
+        if self.games() == 0 {
+            return 0.5;
+        }
+        let p = self.win_rate();
+        1.96 * (p * (1.0 - p) / self.games() as f64).sqrt()
+    }
+
+    /// The Elo difference between A and B. Positive if A is stronger.
+    pub fn elo(&self) -> f64 {
+        elo(self.win_rate())
+    }
+
+    /// The Elo differences at the ends of the 95% confidence interval
+    /// of the win rate.
+    pub fn elo_interval(&self) -> (f64, f64) {
+        let p = self.win_rate();
+        let c = self.confidence();
+        (elo((p - c).max(0.0)), elo((p + c).min(1.0)))
+    }
+
+}
+
+impl fmt::Display for Results {
+
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (low, high) = self.elo_interval();
+        let s = format!(
+            "{} games: A won {}, lost {}, drew {} (black won {})\nWin rate of A: {:.1}% ± {:.1}%\nElo difference: {:+.0} ({:+.0} to {:+.0})",
+            self.games(),
+            self.wins,
+            self.losses,
+            self.draws,
+            self.black_wins,
+            100.0 * self.win_rate(),
+            100.0 * self.confidence(),
+            self.elo(),
+            low,
+            high);
+        s.fmt(f)
+    }
+}
+
+/// The Elo difference that corresponds to the expected score `p`.
+/// Infinite if `p` is 0 or 1.
+pub fn elo(p: f64) -> f64 {
+    -400.0 * (1.0 / p - 1.0).log10()
+}
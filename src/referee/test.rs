@@ -0,0 +1,172 @@
+/************************************************************************
+ *                                                                      *
+ * Copyright 2016 Urban Hafner                                          *
+ *                                                                      *
+ * This file is part of Iomrascálaí.                                    *
+ *                                                                      *
+ * Iomrascálaí is free software: you can redistribute it and/or modify  *
+ * it under the terms of the GNU General Public License as published by *
+ * the Free Software Foundation, either version 3 of the License, or    *
+ * (at your option) any later version.                                  *
+ *                                                                      *
+ * Iomrascálaí is distributed in the hope that it will be useful,       *
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of       *
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the        *
+ * GNU General Public License for more details.                         *
+ *                                                                      *
+ * You should have received a copy of the GNU General Public License    *
+ * along with Iomrascálaí.  If not, see <http://www.gnu.org/licenses/>. *
+ *                                                                      *
+ ************************************************************************/
+
+#![cfg(test)]
+
+use board::Black;
+use board::Coord;
+use board::Empty;
+use board::Pass;
+use board::Play;
+use board::Size;
+use board::White;
+use ruleset::KgsChinese;
+use ruleset::SuperKo;
+use sgf::Parser;
+use super::ExternalEngine;
+use super::GameRecord;
+use super::Player;
+use super::Referee;
+use super::Results;
+use super::elo;
+
+use std::time::Duration;
+
+fn record(a_plays_black: bool, result: &str) -> GameRecord {
+    GameRecord {
+        a_plays_black: a_plays_black,
+        black: if a_plays_black { "A" } else { "B" }.to_string(),
+        komi: 6.5,
+        moves: vec!(Play(Black, 3, 3), Play(White, 7, 7), Pass(Black), Pass(White)),
+        result: result.to_string(),
+        ruleset: KgsChinese,
+        size: Size::square(9),
+        white: if a_plays_black { "B" } else { "A" }.to_string(),
+    }
+}
+
+fn stub(mode: &str) -> Box<Player> {
+    Box::new(ExternalEngine::new(&format!("sh fixtures/gtp/stub-engine.sh {}", mode)).unwrap())
+}
+
+fn referee(a: &str, b: &str) -> Referee<'static> {
+    Referee::new(stub(a), stub(b), KgsChinese, SuperKo::Positional, Size::square(9), 0)
+}
+
+#[test]
+fn the_winner_is_read_from_the_result() {
+    assert_eq!(Black, record(true, "B+R").winner());
+    assert_eq!(White, record(true, "W+3.5").winner());
+    assert_eq!(Empty, record(true, "0").winner());
+}
+
+#[test]
+fn the_sgf_can_be_read_back() {
+    let sgf = record(false, "W+3.5").to_sgf();
+    assert!(sgf.contains("PB[B]PW[A]RE[W+3.5]"));
+    let parser = Parser::new(sgf);
+    assert_eq!(Size::square(9), parser.size());
+    let game = parser.game().unwrap();
+    assert_eq!(Black, game.board().color(&Coord::new(3, 3)));
+    assert_eq!(White, game.board().color(&Coord::new(7, 7)));
+    assert_eq!(6.5, game.komi());
+}
+
+#[test]
+fn results_are_counted_from_the_point_of_view_of_a() {
+    let mut results = Results::new();
+    results.add(&record(true, "B+R"));
+    results.add(&record(false, "W+0.5"));
+    results.add(&record(false, "B+12.5"));
+    results.add(&record(true, "0"));
+    assert_eq!(4, results.games());
+    assert_eq!(2, results.wins);
+    assert_eq!(1, results.losses);
+    assert_eq!(1, results.draws);
+    assert_eq!(2, results.black_wins);
+    assert_eq!(0.625, results.win_rate());
+}
+
+#[test]
+fn an_even_match_has_no_elo_difference() {
+    let mut results = Results::new();
+    results.add(&record(true, "B+R"));
+    results.add(&record(true, "W+R"));
+    assert_eq!(0.0, results.elo());
+    let (low, high) = results.elo_interval();
+    assert!((low + high).abs() < 1e-9);
+}
+
+#[test]
+fn elo_of_known_win_rates() {
+    assert!((elo(0.75) - 190.8).abs() < 0.1);
+    assert!((elo(0.25) + 190.8).abs() < 0.1);
+    assert!(elo(1.0).is_infinite());
+}
+
+#[test]
+fn the_confidence_shrinks_with_more_games() {
+    let mut few = Results::new();
+    let mut many = Results::new();
+    for i in 0..100 {
+        let result = if i % 4 == 0 { "W+R" } else { "B+R" };
+        if i < 8 {
+            few.add(&record(true, result));
+        }
+        many.add(&record(true, result));
+    }
+    assert_eq!(few.win_rate(), many.win_rate());
+    assert!(many.confidence() < few.confidence());
+}
+
+#[test]
+fn external_engines_answer_gtp_commands() {
+    let mut engine = ExternalEngine::new("sh fixtures/gtp/stub-engine.sh").unwrap();
+    assert_eq!(Ok("stub".to_string()), engine.send("name"));
+    assert_eq!(Ok("pass".to_string()), engine.send("genmove b"));
+    assert_eq!("stub", engine.name());
+}
+
+#[test]
+fn starting_a_missing_engine_fails() {
+    assert!(ExternalEngine::new("./does-not-exist").is_err());
+}
+
+#[test]
+fn a_game_ends_after_two_passes_and_is_scored_by_the_referee() {
+    let record = referee("pass", "pass").play(true).unwrap();
+    assert_eq!(2, record.moves.len());
+    assert_eq!("W+6.5", record.result);
+    assert_eq!("stub", record.black);
+}
+
+#[test]
+fn a_resignation_ends_the_game() {
+    let record = referee("resign", "pass").play(true).unwrap();
+    assert_eq!("W+R", record.result);
+    let record = referee("resign", "pass").play(false).unwrap();
+    assert_eq!("B+R", record.result);
+}
+
+#[test]
+fn an_illegal_move_loses_the_game() {
+    let record = referee("pass", "illegal").play(true).unwrap();
+    assert_eq!("B+F", record.result);
+    assert_eq!(1, record.moves.len());
+}
+
+#[test]
+fn a_player_that_does_not_answer_loses_on_time() {
+    let mut referee = referee("hang", "pass");
+    referee.margin = Duration::from_millis(200);
+    let record = referee.play(true).unwrap();
+    assert_eq!("W+T", record.result);
+}
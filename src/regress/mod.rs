@@ -0,0 +1,276 @@
+/************************************************************************
+ *                                                                      *
+ * Copyright 2016 Urban Hafner                                          *
+ *                                                                      *
+ * This file is part of Iomrascálaí.                                    *
+ *                                                                      *
+ * Iomrascálaí is free software: you can redistribute it and/or modify  *
+ * it under the terms of the GNU General Public License as published by *
+ * the Free Software Foundation, either version 3 of the License, or    *
+ * (at your option) any later version.                                  *
+ *                                                                      *
+ * Iomrascálaí is distributed in the hope that it will be useful,       *
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of       *
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the        *
+ * GNU General Public License for more details.                         *
+ *                                                                      *
+ * You should have received a copy of the GNU General Public License    *
+ * along with Iomrascálaí.  If not, see <http://www.gnu.org/licenses/>. *
+ *                                                                      *
+ ************************************************************************/
+
+use referee::Player;
+
+use regex::Regex;
+use std::fmt;
+use std::fs::File;
+use std::io::prelude::*;
+use std::path::Path;
+use std::path::PathBuf;
+use std::time::Instant;
+
+mod test;
+
+/// What happened to a single test.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Outcome {
+    /// The response matched the expectation.
+    Pass,
+    /// The response didn't match the expectation.
+    Fail,
+    /// The response didn't match, but the test is marked as known to
+    /// fail (with a `*`).
+    ExpectedFail,
+    /// The response matched, although the test is marked as known to
+    /// fail.
+    UnexpectedPass,
+}
+
+impl fmt::Display for Outcome {
+
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match *self {
+            Outcome::Pass => "pass",
+            Outcome::Fail => "FAIL",
+            Outcome::ExpectedFail => "expected-fail",
+            Outcome::UnexpectedPass => "UNEXPECTED-PASS",
+        };
+        s.fmt(f)
+    }
+}
+
+/// The expected result of a test as given in a `#?` line, e.g.
+/// `#? [C3|D4]`, `#? [!A1]` (anything but A1), or `#? [C3]*` (known
+/// to fail). The pattern is a regular expression that has to match
+/// the whole response and the case is ignored.
+#[derive(Clone, Debug)]
+pub struct Expectation {
+    known_to_fail: bool,
+    negated: bool,
+    regex: Regex,
+    text: String,
+}
+
+impl Expectation {
+
+    pub fn parse(line: &str) -> Result<Expectation, String> {
+        let text = line.trim_left_matches("#?").trim();
+        let known_to_fail = text.ends_with('*');
+        let bracketed = text.trim_right_matches('*').trim();
+        if !bracketed.starts_with('[') || !bracketed.ends_with(']') || bracketed.len() < 2 {
+            return Err(format!("expected '#? [pattern]' but found '{}'", line));
+        }
+        let pattern = &bracketed[1..bracketed.len() - 1];
+        let negated = pattern.starts_with('!');
+        let pattern = pattern.trim_left_matches('!');
+        let regex = try!(Regex::new(&format!("(?i)^(?:{})$", pattern))
+                         .map_err(|e| format!("invalid pattern '{}': {}", pattern, e)));
+        Ok(Expectation {
+            known_to_fail: known_to_fail,
+            negated: negated,
+            regex: regex,
+            text: text.to_string(),
+        })
+    }
+
+    pub fn matches(&self, response: &str) -> bool {
+        self.regex.is_match(response.trim()) != self.negated
+    }
+
+    fn outcome(&self, response: &Result<String, String>) -> Outcome {
+        let matches = match *response {
+            Ok(ref response) => self.matches(response),
+            Err(_) => false,
+        };
+        match (matches, self.known_to_fail) {
+            (true, false) => Outcome::Pass,
+            (false, false) => Outcome::Fail,
+            (false, true) => Outcome::ExpectedFail,
+            (true, true) => Outcome::UnexpectedPass,
+        }
+    }
+}
+
+/// The result of a single test of a suite.
+#[derive(Clone, Debug)]
+pub struct TestResult {
+    pub command: String,
+    pub expected: String,
+    pub file: String,
+    pub id: usize,
+    pub outcome: Outcome,
+    pub response: String,
+    pub seconds: f64,
+}
+
+impl TestResult {
+
+    /// One tab separated line with the file, id, outcome, time,
+    /// command, expected result, and actual response.
+    pub fn to_tsv(&self) -> String {
+        let clean = |s: &str| s.replace('\t', " ").replace('\n', " ");
+        format!("{}\t{}\t{}\t{:.3}\t{}\t{}\t{}",
+                self.file,
+                self.id,
+                self.outcome,
+                self.seconds,
+                clean(&self.command),
+                clean(&self.expected),
+                clean(&self.response))
+    }
+}
+
+impl fmt::Display for TestResult {
+
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = format!("{}:{} {} ({:.2}s) {} -> {} (expected {})",
+                        self.file,
+                        self.id,
+                        self.outcome,
+                        self.seconds,
+                        self.command,
+                        self.response,
+                        self.expected);
+        s.fmt(f)
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Entry {
+    command: String,
+    expectation: Option<Expectation>,
+    id: usize,
+}
+
+/// A regression test suite in the format of `gogui-regress`: GTP
+/// commands (optionally with a numeric id), each of which can be
+/// followed by a `#?` line with the expected response. Other lines
+/// starting with `#` are comments. The files given to `loadsgf` are
+/// relative to the suite.
+#[derive(Clone, Debug)]
+pub struct Suite {
+    dir: PathBuf,
+    entries: Vec<Entry>,
+    file: String,
+}
+
+impl Suite {
+
+    pub fn from_file(filename: &str) -> Result<Suite, String> {
+        let mut contents = String::new();
+        try!(File::open(filename)
+             .and_then(|mut file| file.read_to_string(&mut contents))
+             .map_err(|e| format!("can't read {}: {}", filename, e)));
+        let dir = Path::new(filename).parent().map(|p| p.to_path_buf()).unwrap_or(PathBuf::new());
+        Self::new(filename, &contents, dir)
+    }
+
+    pub fn new(file: &str, contents: &str, dir: PathBuf) -> Result<Suite, String> {
+        let mut entries: Vec<Entry> = vec!();
+        for (index, line) in contents.lines().enumerate() {
+            let line = line.trim();
+            if line.starts_with("#?") {
+                let expectation = try!(Expectation::parse(line).map_err(|e| format!("{}:{}: {}", file, index + 1, e)));
+                let error = format!("{}:{}: '#?' without a command", file, index + 1);
+                let entry = try!(entries.last_mut().ok_or(error.clone()));
+                if entry.expectation.is_some() {
+                    return Err(error);
+                }
+                entry.expectation = Some(expectation);
+            } else if !line.is_empty() && !line.starts_with('#') {
+                let mut words = line.split_whitespace();
+                let first = words.next().unwrap();
+                let (id, command) = match first.parse::<usize>() {
+                    Ok(id) => (id, words.collect::<Vec<&str>>().join(" ")),
+                    // Tests without an id are numbered by their line.
+                    Err(_) => (index + 1, line.to_string()),
+                };
+                entries.push(Entry {
+                    command: command,
+                    expectation: None,
+                    id: id,
+                });
+            }
+        }
+        Ok(Suite {
+            dir: dir,
+            entries: entries,
+            file: file.to_string(),
+        })
+    }
+
+    /// The number of tests, i.e. commands with an expected result.
+    pub fn len(&self) -> usize {
+        self.entries.iter().filter(|e| e.expectation.is_some()).count()
+    }
+
+    /// Sends all commands of the suite to the player and checks the
+    /// responses of the tests.
+    pub fn run(&self, player: &mut Player) -> Vec<TestResult> {
+        let mut results = vec!();
+        for entry in &self.entries {
+            let command = self.resolve(&entry.command);
+            let started = Instant::now();
+            let response = player.send(&command);
+            let elapsed = started.elapsed();
+            if let Some(ref expectation) = entry.expectation {
+                results.push(TestResult {
+                    command: entry.command.clone(),
+                    expected: expectation.text.clone(),
+                    file: self.file.clone(),
+                    id: entry.id,
+                    outcome: expectation.outcome(&response),
+                    response: match response {
+                        Ok(response) => response,
+                        Err(error) => format!("? {}", error),
+                    },
+                    seconds: elapsed.as_secs() as f64 + elapsed.subsec_nanos() as f64 / 1e9,
+                });
+            }
+        }
+        results
+    }
+
+    // Makes the file of `loadsgf` relative to the suite.
+    fn resolve(&self, command: &str) -> String {
+        let words: Vec<&str> = command.split_whitespace().collect();
+        if words.len() < 2 || words[0] != "loadsgf" || Path::new(words[1]).is_absolute() {
+            return command.to_string();
+        }
+        let path = self.dir.join(words[1]);
+        let mut resolved = vec!(words[0].to_string(), path.to_string_lossy().into_owned());
+        resolved.extend(words[2..].iter().map(|w| w.to_string()));
+        resolved.join(" ")
+    }
+}
+
+/// Counts the outcomes of a number of tests.
+pub fn summary(results: &[TestResult]) -> String {
+    let count = |outcome| results.iter().filter(|r| r.outcome == outcome).count();
+    format!("{} tests: {} passed, {} failed, {} expected failures, {} unexpected passes",
+            results.len(),
+            count(Outcome::Pass),
+            count(Outcome::Fail),
+            count(Outcome::ExpectedFail),
+            count(Outcome::UnexpectedPass))
+}
@@ -0,0 +1,130 @@
+/************************************************************************
+ *                                                                      *
+ * Copyright 2016 Urban Hafner                                          *
+ *                                                                      *
+ * This file is part of Iomrascálaí.                                    *
+ *                                                                      *
+ * Iomrascálaí is free software: you can redistribute it and/or modify  *
+ * it under the terms of the GNU General Public License as published by *
+ * the Free Software Foundation, either version 3 of the License, or    *
+ * (at your option) any later version.                                  *
+ *                                                                      *
+ * Iomrascálaí is distributed in the hope that it will be useful,       *
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of       *
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the        *
+ * GNU General Public License for more details.                         *
+ *                                                                      *
+ * You should have received a copy of the GNU General Public License    *
+ * along with Iomrascálaí.  If not, see <http://www.gnu.org/licenses/>. *
+ *                                                                      *
+ ************************************************************************/
+
+#![cfg(test)]
+
+use referee::Player;
+use super::Expectation;
+use super::Outcome;
+use super::Suite;
+use super::summary;
+
+use std::path::PathBuf;
+
+// Answers every command with the response for the first word of the
+// command and records the commands it got.
+struct Scripted {
+    commands: Vec<String>,
+    responses: Vec<(&'static str, Result<String, String>)>,
+}
+
+impl Scripted {
+
+    fn new(responses: Vec<(&'static str, Result<String, String>)>) -> Scripted {
+        Scripted {
+            commands: vec!(),
+            responses: responses,
+        }
+    }
+}
+
+impl Player for Scripted {
+
+    fn name(&mut self) -> String {
+        "scripted".to_string()
+    }
+
+    fn send(&mut self, command: &str) -> Result<String, String> {
+        self.commands.push(command.to_string());
+        let first = command.split_whitespace().next().unwrap();
+        match self.responses.iter().find(|&&(name, _)| name == first) {
+            Some(&(_, ref response)) => response.clone(),
+            None => Ok(String::new()),
+        }
+    }
+}
+
+#[test]
+fn the_pattern_has_to_match_the_whole_response() {
+    let expectation = Expectation::parse("#? [C3|D4]").unwrap();
+    assert!(expectation.matches("C3"));
+    assert!(expectation.matches("d4"));
+    assert!(!expectation.matches("C33"));
+}
+
+#[test]
+fn negated_patterns() {
+    let expectation = Expectation::parse("#? [!pass]").unwrap();
+    assert!(expectation.matches("C3"));
+    assert!(!expectation.matches("PASS"));
+}
+
+#[test]
+fn invalid_expectations_are_rejected() {
+    assert!(Expectation::parse("#? C3").is_err());
+    assert!(Expectation::parse("#? [C3(]").is_err());
+}
+
+#[test]
+fn all_four_outcomes() {
+    let contents = "1 a\n#? [x]\n2 b\n#? [x]\n3 a\n#? [x]*\n4 b\n#? [x]*\n";
+    let suite = Suite::new("t.tst", contents, PathBuf::new()).unwrap();
+    let mut player = Scripted::new(vec!(("a", Ok("x".to_string())), ("b", Ok("y".to_string()))));
+    let outcomes: Vec<Outcome> = suite.run(&mut player).iter().map(|r| r.outcome).collect();
+    assert_eq!(vec!(Outcome::Pass, Outcome::Fail, Outcome::UnexpectedPass, Outcome::ExpectedFail), outcomes);
+}
+
+#[test]
+fn an_error_response_fails_the_test() {
+    let suite = Suite::new("t.tst", "1 a\n#? [!x]", PathBuf::new()).unwrap();
+    let mut player = Scripted::new(vec!(("a", Err("unknown command".to_string()))));
+    let results = suite.run(&mut player);
+    assert_eq!(Outcome::Fail, results[0].outcome);
+    assert_eq!("? unknown command", results[0].response);
+}
+
+#[test]
+fn loadsgf_is_relative_to_the_suite() {
+    let suite = Suite::from_file("fixtures/regress/example.tst").unwrap();
+    assert_eq!(3, suite.len());
+    let mut player = Scripted::new(vec!(("reg_genmove", Ok("D4".to_string())), ("name", Ok("Iomrascalai".to_string()))));
+    let results = suite.run(&mut player);
+    assert_eq!("loadsgf fixtures/regress/../sgf/twomoves.sgf", player.commands[0]);
+    assert_eq!("reg_genmove b", player.commands[1]);
+    assert_eq!(10, results[0].id);
+    assert_eq!("3 tests: 2 passed, 0 failed, 0 expected failures, 1 unexpected passes", summary(&results));
+}
+
+#[test]
+fn an_expectation_needs_a_command() {
+    assert!(Suite::new("t.tst", "#? [x]", PathBuf::new()).is_err());
+    assert!(Suite::new("t.tst", "1 a\n#? [x]\n#? [y]", PathBuf::new()).is_err());
+}
+
+#[test]
+fn results_as_tab_separated_values() {
+    let suite = Suite::new("t.tst", "7 a\n#? [x]", PathBuf::new()).unwrap();
+    let mut player = Scripted::new(vec!(("a", Ok("x".to_string()))));
+    let line = suite.run(&mut player)[0].to_tsv();
+    let fields: Vec<&str> = line.split('\t').collect();
+    assert_eq!(vec!("t.tst", "7", "pass"), fields[..3].to_vec());
+    assert_eq!(vec!("a", "[x]", "x"), fields[4..].to_vec());
+}
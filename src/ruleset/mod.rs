@@ -24,6 +24,7 @@ pub use self::Ruleset::AnySizeTrompTaylor;
 pub use self::Ruleset::CGOS;
 pub use self::Ruleset::KgsChinese;
 pub use self::Ruleset::Minimal;
+pub use self::Ruleset::NewZealand;
 
 use std::fmt;
 use std::str::FromStr;
@@ -36,6 +37,48 @@ pub enum Ruleset {
     CGOS,
     KgsChinese,
     Minimal,
+    NewZealand,
+}
+
+/// The different ways of forbidding repeated positions. Simple ko is
+/// always handled by the board itself.
+#[derive(Clone, Debug, Eq, PartialEq, Copy)]
+pub enum SuperKo {
+    /// A move may not recreate a position that was created by a move
+    /// (not a pass) of the same player before.
+    NaturalSituational,
+    /// A move may not recreate any earlier board position.
+    Positional,
+    /// A move may not recreate an earlier board position with the same
+    /// player to move.
+    Situational,
+}
+
+impl FromStr for SuperKo {
+
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<SuperKo, Self::Err> {
+        match s {
+            "natural-situational" => Ok(SuperKo::NaturalSituational),
+            "positional"          => Ok(SuperKo::Positional),
+            "situational"         => Ok(SuperKo::Situational),
+            _                     => Err(format!("Unknown superko rule '{}'", s)),
+        }
+    }
+
+}
+
+impl fmt::Display for SuperKo {
+
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match *self {
+            SuperKo::NaturalSituational => "natural-situational",
+            SuperKo::Positional => "positional",
+            SuperKo::Situational => "situational",
+        };
+        s.fmt(f)
+    }
 }
 
 impl Ruleset {
@@ -58,9 +101,17 @@ impl Ruleset {
         match *self {
             AnySizeTrompTaylor => true,
             Minimal            => true,
+            NewZealand         => true,
             _ => false
         }
     }
+
+    pub fn super_ko(&self) -> SuperKo {
+        match *self {
+            NewZealand => SuperKo::Situational,
+            _ => SuperKo::Positional
+        }
+    }
 }
 
 impl FromStr for Ruleset {
@@ -73,6 +124,7 @@ impl FromStr for Ruleset {
             "cgos"         => Ok(CGOS),
             "chinese"      => Ok(KgsChinese),
             "minimal"      => Ok(Minimal),
+            "new-zealand"  => Ok(NewZealand),
             _              => Err(format!("Unknown ruleset '{}'", s)),
         }
     }
@@ -86,7 +138,8 @@ impl fmt::Display for Ruleset {
             AnySizeTrompTaylor => "tromp-taylor",
             CGOS => "cgos",
             KgsChinese => "chinese",
-            Minimal => "minimal"
+            Minimal => "minimal",
+            NewZealand => "new-zealand",
         };
         s.fmt(f)
     }
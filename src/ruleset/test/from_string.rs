@@ -25,7 +25,9 @@ use ruleset::AnySizeTrompTaylor;
 use ruleset::CGOS;
 use ruleset::KgsChinese;
 use ruleset::Minimal;
+use ruleset::NewZealand;
 use ruleset::Ruleset;
+use ruleset::SuperKo;
 
 use std::str::FromStr;
 
@@ -49,7 +51,24 @@ fn parses_minimal() {
     assert_eq!(Ok(Minimal), Ruleset::from_str("minimal"));
 }
 
+#[test]
+fn parses_new_zealand() {
+    assert_eq!(Ok(NewZealand), Ruleset::from_str("new-zealand"));
+}
+
 #[test]
 fn errors_with_unknown() {
     assert_eq!(Err(String::from("Unknown ruleset 'unknown'")), Ruleset::from_str("unknown"));
 }
+
+#[test]
+fn parses_superko_rules() {
+    assert_eq!(Ok(SuperKo::NaturalSituational), SuperKo::from_str("natural-situational"));
+    assert_eq!(Ok(SuperKo::Positional), SuperKo::from_str("positional"));
+    assert_eq!(Ok(SuperKo::Situational), SuperKo::from_str("situational"));
+}
+
+#[test]
+fn errors_with_unknown_superko_rule() {
+    assert_eq!(Err(String::from("Unknown superko rule 'unknown'")), SuperKo::from_str("unknown"));
+}
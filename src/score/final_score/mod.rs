@@ -40,12 +40,23 @@ impl FinalScore {
 
     pub fn new(config: Arc<Config>, game: &Game, ownership: &OwnershipStatistics) -> FinalScore {
         let mut board = game.board();
-        let dead = Coord::for_board_size(board.size()).iter()
-            .filter(|c| board.color(c) != Empty)
-            .filter(|c| ownership.owner(c) != Empty)
-            .filter(|c| ownership.owner(c) != board.color(c))
-            .cloned()
-            .collect();
+        // The pass-alive area is known for sure so it takes
+        // precedence over the ownership statistics of the playouts.
+        let dead: Vec<Coord> = {
+            let area = board.pass_alive_area();
+            let owner = |c: &Coord| {
+                match area[c.to_index(board.size())] {
+                    Empty => ownership.owner(c),
+                    color => color
+                }
+            };
+            Coord::for_board_size(board.size()).iter()
+                .filter(|c| board.color(c) != Empty)
+                .filter(|c| owner(c) != Empty)
+                .filter(|c| owner(c) != board.color(c))
+                .cloned()
+                .collect()
+        };
         if config.ruleset == KgsChinese {
             for coord in &dead {
                 board.remove_dead_stone(coord);
@@ -26,6 +26,7 @@ use board::Board;
 use board::Color;
 use board::Coord;
 use board::Empty;
+use board::Size;
 use board::White;
 use self::territory::Territory;
 
@@ -40,7 +41,10 @@ pub struct Score {
     black_stones: usize,
     komi: f32,
     owner: Vec<Color>,
-    size: u8,
+    // The komi of the game. It differs from `komi` when the playout
+    // was scored with the dynamic komi.
+    real_komi: f32,
+    size: Size,
     white_stones: usize,
 }
 
@@ -52,6 +56,7 @@ impl Score {
             black_stones: bs,
             komi: board.komi(),
             owner: owners,
+            real_komi: board.komi(),
             size: board.size(),
             white_stones: ws,
         }
@@ -76,8 +81,25 @@ impl Score {
         (self.black_stones as f32 - (self.white_stones as f32 + self.komi)).abs()
     }
 
+    /// Measures the margin against the komi of the game instead of
+    /// the (dynamic) komi the winner was decided with.
+    pub fn set_real_komi(&mut self, komi: f32) {
+        self.real_komi = komi;
+    }
+
+    /// The score difference (using the real komi) seen from the point
+    /// of view of `color`, i.e. positive if `color` won and negative
+    /// if it lost.
+    pub fn margin(&self, color: Color) -> f32 {
+        let margin = self.black_stones as f32 - (self.white_stones as f32 + self.real_komi);
+        match color {
+            White => -margin,
+            _ => margin,
+        }
+    }
+
     pub fn adjusted(&self) -> f32 {
-        let max = self.size as f32 * self.size as f32;
+        let max = self.size.points() as f32;
         match self.color() {
             White => {
                 self.score() / (max + self.komi)
@@ -89,7 +111,7 @@ impl Score {
     }
 
     fn score_tt(board: &Board) -> (usize, usize, Vec<Color>) {
-        let len = board.size() as usize * board.size() as usize;
+        let len = board.size().points();
         let mut owners = vec![Empty; len];
         Score::count_stones(board, &mut owners);
         Score::count_territory(board, &mut owners);
@@ -31,6 +31,7 @@ pub use board::Coord;
 pub use board::Empty;
 pub use board::Pass;
 pub use board::Play;
+pub use board::Size;
 pub use board::White;
 pub use fixtures::load_board;
 pub use super::Score;
@@ -186,6 +187,36 @@ describe! score {
 
     }
 
+    describe! margin {
+
+        before_each {
+            let score = Score {
+                black_stones: 10,
+                komi: 6.5,
+                owner: vec!(),
+                real_komi: 6.5,
+                size: Size::square(9),
+                white_stones: 7,
+            };
+        }
+
+        it "is negative for black when white wins" {
+            assert_that!(score.margin(Black), is(equal_to(-3.5)));
+        }
+
+        it "is positive for white when white wins" {
+            assert_that!(score.margin(White), is(equal_to(3.5)));
+        }
+
+        it "uses the real komi" {
+            let mut score = score;
+            score.komi = 0.5;
+            score.set_real_komi(7.5);
+            assert_that!(score.color(), is(equal_to(Black)));
+            assert_that!(score.margin(White), is(equal_to(4.5)));
+        }
+    }
+
     describe! adjusted {
 
         before_each {
@@ -193,7 +224,8 @@ describe! score {
                 black_stones: 0,
                 komi: 6.5,
                 owner: vec!(),
-                size: 9,
+                real_komi: 6.5,
+                size: Size::square(9),
                 white_stones: 0,
             };
         }
@@ -23,15 +23,19 @@
 
 use board::Black;
 use board::Color;
+use board::Coord;
 use board::Empty;
 use board::IllegalMove;
+use board::Move;
 use board::Pass;
 use board::Play;
+use board::Size;
 use board::White;
 use game::Game;
 use ruleset::Minimal;
 
 use regex::Regex;
+use std::cmp;
 use std::io::prelude::*;
 use std::fs::File;
 use std::path::Path;
@@ -54,14 +58,22 @@ impl<'a> Property<'a> {
 
     // SGF counts from top to bottom, while we count from bottom to
     // top (and we start at 1).
-    fn row(&self, size: u8) -> u8 {
-        size - self.char_to_int(self.val.as_bytes()[1]) + 1
+    fn row(&self, size: Size) -> u8 {
+        size.height - self.char_to_int(self.val.as_bytes()[1]) + 1
     }
 
     fn char_to_int(&self, c: u8) -> u8 {
         c - ('a' as u8) + 1
     }
 
+    fn is_point(&self, size: Size) -> bool {
+        let bytes = self.val.as_bytes();
+        bytes.len() == 2 &&
+            bytes.iter().all(|&b| b >= b'a' && b <= b'z') &&
+            self.char_to_int(bytes[0]) <= size.width &&
+            self.char_to_int(bytes[1]) <= size.height
+    }
+
     fn is_pass(&self) -> bool {
         self.val == ""
     }
@@ -113,7 +125,7 @@ impl Parser {
 	}
 
     pub fn game(&self) -> Result<Game, IllegalMove> {
-        let mut game = Game::new(self.size(), self.komi(), Minimal);
+        let mut game = Game::with_super_ko(self.size(), self.komi(), Minimal, Minimal.super_ko());
         let props = self.tokenize();
         for prop in props.iter() {
             match prop.play(game) {
@@ -126,10 +138,63 @@ impl Parser {
         Ok(game)
     }
 
-    fn size(&self) -> u8 {
+    /// The moves of the game (`B` and `W` properties) in the order
+    /// they were played. Setup stones aren't included.
+    pub fn moves(&self) -> Vec<Move> {
+        let size = self.size();
+        self.tokenize().iter()
+            .filter(|p| p.name == "B" || p.name == "W")
+            .filter(|p| p.is_pass() || p.is_point(size))
+            .map(|p| if p.is_pass() { Pass(p.color()) } else { Play(p.color(), p.col(), p.row(size)) })
+            .collect()
+    }
+
+    /// Returns the points of all properties with the given name (e.g.
+    /// the marked points of `TR` or `SQ`). Compressed point lists
+    /// like `[aa:cc]` are expanded to all points of the rectangle.
+    pub fn points(&self, name: &str) -> Vec<Coord> {
+        let size = self.size();
+        let mut coords = vec!();
+        for prop in self.tokenize().iter().filter(|p| p.name == name) {
+            let (from, to) = match prop.val.find(':') {
+                Some(i) => (&prop.val[..i], &prop.val[i+1..]),
+                None => (prop.val, prop.val)
+            };
+            let from = Property { name: name, val: from };
+            let to = Property { name: name, val: to };
+            if !from.is_point(size) || !to.is_point(size) {
+                continue;
+            }
+            let cols = cmp::min(from.col(), to.col())..cmp::max(from.col(), to.col())+1;
+            let rows = cmp::min(from.row(size), to.row(size))..cmp::max(from.row(size), to.row(size))+1;
+            for col in cols {
+                for row in rows.clone() {
+                    let coord = Coord::new(col, row);
+                    if !coords.contains(&coord) {
+                        coords.push(coord);
+                    }
+                }
+            }
+        }
+        coords
+    }
+
+    /// Returns the player to move as set with the `PL` property.
+    pub fn player(&self) -> Option<Color> {
+        let props = self.tokenize();
+        match props.iter().find(|p| p.name == "PL").map(|p| p.val) {
+            Some("B") => Some(Black),
+            Some("W") => Some(White),
+            _ => None
+        }
+    }
+
+    /// The size of the board as set with the `SZ` property (either
+    /// `SZ[19]` or `SZ[19:13]`). Defaults to 19x19.
+    pub fn size(&self) -> Size {
         let props = self.tokenize();
         let prop = props.iter().find(|p| p.name == "SZ");
-        prop.and_then(|p| p.val.parse().ok()).unwrap_or(19)
+        prop.and_then(|p| Size::from_sgf(p.val).ok()).unwrap_or(Size::square(19))
     }
 
     fn komi(&self) -> f32 {
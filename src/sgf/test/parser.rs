@@ -22,6 +22,9 @@
 #![cfg(test)]
 use board::Black;
 use board::Coord;
+use board::Pass;
+use board::Play;
+use board::Size;
 use board::White;
 use sgf::parser::Parser;
 
@@ -31,7 +34,16 @@ use std::path::Path;
 fn sets_the_board_size_from_sgf() {
     let parser = Parser::from_path(Path::new("fixtures/sgf/empty.sgf")).unwrap();
     let game  = parser.game().unwrap();
-    assert_eq!(game.size(), 19);
+    assert_eq!(game.size(), Size::square(19));
+}
+
+#[test]
+fn sets_rectangular_board_sizes_from_sgf() {
+    let parser = Parser::from_path(Path::new("fixtures/sgf/rectangular.sgf")).unwrap();
+    let game  = parser.game().unwrap();
+    assert_eq!(game.size(), Size::new(7, 5).unwrap());
+    assert_eq!(game.board().color(&Coord::new(7, 5)), Black);
+    assert_eq!(game.board().color(&Coord::new(1, 1)), White);
 }
 
 #[test]
@@ -64,3 +76,28 @@ fn finished_game() {
     let game   = parser.game().unwrap();
     assert!(game.is_over());
 }
+
+#[test]
+fn reads_marked_points() {
+    let parser = Parser::new(String::from("(;SZ[9]SQ[ah]TR[ai:ci][ii])"));
+    assert_eq!(vec!(Coord::new(1, 2)), parser.points("SQ"));
+    assert_eq!(vec!(Coord::new(1, 1), Coord::new(2, 1), Coord::new(3, 1), Coord::new(9, 1)), parser.points("TR"));
+}
+
+#[test]
+fn ignores_points_outside_of_the_board() {
+    let parser = Parser::new(String::from("(;SZ[9]TR[zz][aa:zz][a])"));
+    assert!(parser.points("TR").is_empty());
+}
+
+#[test]
+fn reads_the_player_to_move() {
+    assert_eq!(Some(White), Parser::new(String::from("(;SZ[9]PL[W])")).player());
+    assert_eq!(None, Parser::new(String::from("(;SZ[9])")).player());
+}
+
+#[test]
+fn reads_the_moves() {
+    let parser = Parser::new(String::from("(;SZ[9]AB[aa];B[ei];W[];B[zz])"));
+    assert_eq!(vec!(Play(Black, 5, 1), Pass(White)), parser.moves());
+}
@@ -0,0 +1,526 @@
+/************************************************************************
+ *                                                                      *
+ * Copyright 2016 Urban Hafner                                          *
+ *                                                                      *
+ * This file is part of Iomrascálaí.                                    *
+ *                                                                      *
+ * Iomrascálaí is free software: you can redistribute it and/or modify  *
+ * it under the terms of the GNU General Public License as published by *
+ * the Free Software Foundation, either version 3 of the License, or    *
+ * (at your option) any later version.                                  *
+ *                                                                      *
+ * Iomrascálaí is distributed in the hope that it will be useful,       *
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of       *
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the        *
+ * GNU General Public License for more details.                         *
+ *                                                                      *
+ * You should have received a copy of the GNU General Public License    *
+ * along with Iomrascálaí.  If not, see <http://www.gnu.org/licenses/>. *
+ *                                                                      *
+ ************************************************************************/
+
+use board::Board;
+use board::Color;
+use board::Coord;
+use board::Empty;
+use board::Move;
+use board::Pass;
+use board::Play;
+use config::TacticsConfig;
+
+use std::fmt;
+
+mod test;
+
+/// The result of reading out one of the tactical situations on the
+/// board. The chains are identified by one of their stones and the
+/// move is always for the player whose turn it is.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Verdict {
+    /// The move captures the opponent chain.
+    Capture { chain: Coord, m: Move },
+    /// The move connects two of our own chains that could otherwise
+    /// be cut.
+    Connect { chains: (Coord, Coord), m: Move },
+    /// The move separates two opponent chains that could otherwise
+    /// connect.
+    Cut { chains: (Coord, Coord), m: Move },
+    /// The move saves our chain which could otherwise be captured.
+    Save { chain: Coord, m: Move },
+    /// The move wins the capturing race between our chain and the
+    /// adjacent opponent chain which we would otherwise lose.
+    Semeai { own: Coord, other: Coord, m: Move },
+}
+
+impl Verdict {
+
+    pub fn m(&self) -> Move {
+        match *self {
+            Verdict::Capture { m, .. } => m,
+            Verdict::Connect { m, .. } => m,
+            Verdict::Cut { m, .. } => m,
+            Verdict::Save { m, .. } => m,
+            Verdict::Semeai { m, .. } => m,
+        }
+    }
+
+}
+
+impl fmt::Display for Verdict {
+
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let vertex = self.m().coord().to_gtp();
+        let s = match *self {
+            Verdict::Capture { chain, .. } =>
+                format!("capture {} at {}", chain.to_gtp(), vertex),
+            Verdict::Connect { chains: (a, b), .. } =>
+                format!("connect {} {} at {}", a.to_gtp(), b.to_gtp(), vertex),
+            Verdict::Cut { chains: (a, b), .. } =>
+                format!("cut {} {} at {}", a.to_gtp(), b.to_gtp(), vertex),
+            Verdict::Save { chain, .. } =>
+                format!("save {} at {}", chain.to_gtp(), vertex),
+            Verdict::Semeai { own, other, .. } =>
+                format!("semeai {} {} at {}", own.to_gtp(), other.to_gtp(), vertex),
+        };
+        s.fmt(f)
+    }
+
+}
+
+/// Reads out all tactical situations on the board that are relevant
+/// for the player to move. See `Verdict` for what is reported.
+pub fn analyze(board: &Board, reader: &mut Reader) -> Vec<Verdict> {
+    let player = board.next_player();
+    let mut verdicts = vec!();
+    for chain in board.chains().iter() {
+        let libs = chain.liberties().len();
+        let coord = chain.coords()[0];
+        if chain.color() == player {
+            // Our own chains in atari are included as well.
+            if libs <= reader.liberties && reader.attack(board, coord).is_some() {
+                if let Some(m) = reader.defend(board, coord) {
+                    verdicts.push(Verdict::Save { chain: coord, m: m });
+                }
+            }
+        } else if libs >= 2 && libs <= reader.liberties {
+            // Chains in atari are already handled by the capture
+            // priors.
+            if let Some(m) = reader.attack(board, coord) {
+                verdicts.push(Verdict::Capture { chain: coord, m: m });
+            }
+        }
+    }
+    for (own, other) in race_candidates(board, player, reader.liberties) {
+        // Only races we would lose if the opponent moved first matter.
+        if reader.semeai(board, other, own).is_some() {
+            if let Some(m) = reader.semeai(board, own, other) {
+                verdicts.push(Verdict::Semeai { own: own, other: other, m: m });
+            }
+        }
+    }
+    for (a, b) in cutting_candidates(board) {
+        if board.color(&a) == player {
+            if reader.cut(board, a, b).is_some() {
+                if let Some(m) = reader.connect(board, a, b) {
+                    verdicts.push(Verdict::Connect { chains: (a, b), m: m });
+                }
+            }
+        } else if let Some(m) = reader.cut(board, a, b) {
+            verdicts.push(Verdict::Cut { chains: (a, b), m: m });
+        }
+    }
+    verdicts
+}
+
+// Pairs of adjacent chains (our own first) that both have between 2
+// and `max_liberties` liberties.
+fn race_candidates(board: &Board, player: Color, max_liberties: usize) -> Vec<(Coord, Coord)> {
+    let in_range = |coord: &Coord| {
+        let libs = board.get_chain(*coord).unwrap().liberties().len();
+        libs >= 2 && libs <= max_liberties
+    };
+    let mut pairs = vec!();
+    for chain in board.chains().iter().filter(|c| c.color() == player) {
+        let own = chain.coords()[0];
+        if !in_range(&own) {
+            continue;
+        }
+        let mut seen = vec!();
+        for &coord in chain.coords().iter() {
+            for &n in board.neighbours(coord).iter() {
+                if board.color(&n) == player.opposite() && !seen.contains(&board.chain_id(&n)) {
+                    seen.push(board.chain_id(&n));
+                    let other = board.get_chain(n).unwrap().coords()[0];
+                    if in_range(&other) {
+                        pairs.push((own, other));
+                    }
+                }
+            }
+        }
+    }
+    pairs
+}
+
+// Pairs of chains of the same color that share exactly one liberty.
+// With two or more shared liberties the chains are connected anyway.
+fn cutting_candidates(board: &Board) -> Vec<(Coord, Coord)> {
+    let chains = board.chains();
+    let mut pairs = vec!();
+    for (i, a) in chains.iter().enumerate() {
+        for b in chains[i+1..].iter().filter(|b| b.color() == a.color()) {
+            let shared = a.liberties().iter().filter(|&l| b.liberties().contains(l)).count();
+            if shared == 1 {
+                pairs.push((a.coords()[0], b.coords()[0]));
+            }
+        }
+    }
+    pairs
+}
+
+/// Bounded depth-first search for the tactical fights around single
+/// chains: capturing and saving chains, capturing races, and
+/// connecting and cutting. Moves that put the chain in atari don't
+/// count against the depth so that simple ladders are read out. A
+/// search that hits the node limit gives up: chains are assumed to
+/// live, connecting and cutting moves to fail, and races are decided
+/// by counting liberties.
+pub struct Reader {
+    depth: usize,
+    liberties: usize,
+    node_limit: usize,
+    nodes: usize,
+}
+
+impl Reader {
+
+    pub fn new(config: &TacticsConfig) -> Reader {
+        Reader {
+            depth: config.depth,
+            liberties: config.liberties,
+            node_limit: config.nodes,
+            nodes: 0,
+        }
+    }
+
+    /// Returns a move that captures the chain at `coord` if the
+    /// opponent of the chain moves first.
+    pub fn attack(&mut self, board: &Board, coord: Coord) -> Option<Move> {
+        let color = board.color(&coord);
+        if color == Empty {
+            return None;
+        }
+        let mut b = self.prepare(board, color.opposite());
+        let depth = self.depth;
+        self.capture(&mut b, coord, color, depth)
+    }
+
+    /// Returns a move that saves the chain at `coord` if its owner
+    /// moves first. Only meaningful for chains where `attack()`
+    /// succeeds.
+    pub fn defend(&mut self, board: &Board, coord: Coord) -> Option<Move> {
+        let color = board.color(&coord);
+        if color == Empty {
+            return None;
+        }
+        let mut b = self.prepare(board, color);
+        let depth = self.depth;
+        Self::only_plays(self.rescue(&mut b, coord, color, depth))
+    }
+
+    /// Returns a move for the owner of `own` (who moves first) that
+    /// captures `other` before `own` is captured.
+    pub fn semeai(&mut self, board: &Board, own: Coord, other: Coord) -> Option<Move> {
+        let color = board.color(&own);
+        if color == Empty || board.color(&other) != color.opposite() {
+            return None;
+        }
+        let mut b = self.prepare(board, color);
+        let depth = self.depth;
+        Self::only_plays(self.race(&mut b, own, other, depth))
+    }
+
+    /// Returns a move for the owner of the two chains (who moves
+    /// first) that connects them in a way that they can't be cut
+    /// anymore.
+    pub fn connect(&mut self, board: &Board, a: Coord, b: Coord) -> Option<Move> {
+        let color = board.color(&a);
+        if color == Empty || board.color(&b) != color {
+            return None;
+        }
+        let mut board = self.prepare(board, color);
+        let depth = self.depth;
+        Self::only_plays(self.join(&mut board, a, b, color, depth))
+    }
+
+    /// Returns a move for the opponent of the two chains (who moves
+    /// first) that keeps them from connecting.
+    pub fn cut(&mut self, board: &Board, a: Coord, b: Coord) -> Option<Move> {
+        let color = board.color(&a);
+        if color == Empty || board.color(&b) != color {
+            return None;
+        }
+        let mut board = self.prepare(board, color.opposite());
+        let depth = self.depth;
+        Self::only_plays(self.separate(&mut board, a, b, color, depth))
+    }
+
+    // Every search works on its own copy of the board on which moves
+    // are played and taken back. This also makes sure that it's the
+    // turn of the player that starts the search.
+    fn prepare(&mut self, board: &Board, to_move: Color) -> Board {
+        self.nodes = 0;
+        let mut b = board.clone();
+        b.reset_game_over();
+        if b.next_player() != to_move {
+            let pass = Pass(b.next_player());
+            b.play_legal_move(pass);
+        }
+        b
+    }
+
+    // Searches use a pass to signal that the goal is reached without
+    // playing a move (e.g. the chains are already connected).
+    fn only_plays(m: Option<Move>) -> Option<Move> {
+        match m {
+            Some(Play(..)) => m,
+            _ => None
+        }
+    }
+
+    fn out_of_nodes(&self) -> bool {
+        self.nodes >= self.node_limit
+    }
+
+    fn try_move(&mut self, board: &mut Board, m: Move) -> bool {
+        if board.make_move(m).is_ok() {
+            self.nodes += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    // The attacker of the chain is to move.
+    fn capture(&mut self, board: &mut Board, coord: Coord, color: Color, depth: usize) -> Option<Move> {
+        let libs = Self::liberties_of(board, coord);
+        let attacker = color.opposite();
+        if libs.len() == 1 {
+            let m = Play(attacker, libs[0].col, libs[0].row);
+            return if board.is_legal(m).is_ok() { Some(m) } else { None };
+        }
+        if depth == 0 || libs.len() > self.liberties || self.out_of_nodes() {
+            return None;
+        }
+        for m in Self::attacking_moves(board, attacker, &libs) {
+            if !self.try_move(board, m) {
+                continue;
+            }
+            let captured = board.color(&coord) != color || {
+                let next_depth = if Self::liberties_of(board, coord).len() == 1 { depth } else { depth - 1 };
+                self.rescue(board, coord, color, next_depth).is_none()
+            };
+            board.unmake_move();
+            if captured {
+                return Some(m);
+            }
+        }
+        None
+    }
+
+    // The owner of the chain is to move.
+    fn rescue(&mut self, board: &mut Board, coord: Coord, color: Color, depth: usize) -> Option<Move> {
+        let libs = Self::liberties_of(board, coord);
+        if libs.len() > self.liberties || self.out_of_nodes() {
+            return Some(Pass(color));
+        }
+        for m in Self::defending_moves(board, coord, color, &libs) {
+            if !self.try_move(board, m) {
+                continue;
+            }
+            let saved = board.color(&coord) == color && self.capture(board, coord, color, depth).is_none();
+            board.unmake_move();
+            if saved {
+                return Some(m);
+            }
+        }
+        None
+    }
+
+    // The owner of `own` is to move.
+    fn race(&mut self, board: &mut Board, own: Coord, other: Coord, depth: usize) -> Option<Move> {
+        let color = board.color(&own);
+        let other_color = color.opposite();
+        let own_libs = Self::liberties_of(board, own);
+        let other_libs = Self::liberties_of(board, other);
+        if other_libs.len() == 1 {
+            let m = Play(color, other_libs[0].col, other_libs[0].row);
+            if board.is_legal(m).is_ok() {
+                return Some(m);
+            }
+        }
+        if depth == 0 || self.out_of_nodes() {
+            // The player to move wins races with equal liberties.
+            return if own_libs.len() >= other_libs.len() { Some(Pass(color)) } else { None };
+        }
+        let mut moves = Self::attacking_moves(board, color, &other_libs);
+        moves.extend(Self::defending_moves(board, own, color, &own_libs));
+        for m in moves {
+            if !self.try_move(board, m) {
+                continue;
+            }
+            let won = if board.color(&other) != other_color {
+                true
+            } else if board.color(&own) != color {
+                false
+            } else {
+                self.race(board, other, own, depth - 1).is_none()
+            };
+            board.unmake_move();
+            if won {
+                return Some(m);
+            }
+        }
+        None
+    }
+
+    // The owner of the chains is to move.
+    fn join(&mut self, board: &mut Board, a: Coord, b: Coord, color: Color, depth: usize) -> Option<Move> {
+        if board.color(&a) != color || board.color(&b) != color {
+            return None;
+        }
+        if board.chain_id(&a) == board.chain_id(&b) {
+            return Some(Pass(color));
+        }
+        if depth == 0 || self.out_of_nodes() {
+            return None;
+        }
+        for m in Self::connecting_moves(board, a, b, color) {
+            if !self.try_move(board, m) {
+                continue;
+            }
+            let connected = if board.color(&a) == color && board.chain_id(&a) == board.chain_id(&b) {
+                let full_depth = self.depth;
+                self.capture(board, a, color, full_depth).is_none()
+            } else {
+                self.separate(board, a, b, color, depth - 1).is_none()
+            };
+            board.unmake_move();
+            if connected {
+                return Some(m);
+            }
+        }
+        None
+    }
+
+    // The opponent of the chains is to move.
+    fn separate(&mut self, board: &mut Board, a: Coord, b: Coord, color: Color, depth: usize) -> Option<Move> {
+        let cutter = color.opposite();
+        if board.color(&a) != color || board.color(&b) != color {
+            return Some(Pass(cutter));
+        }
+        if board.chain_id(&a) == board.chain_id(&b) {
+            return None;
+        }
+        let shared = Self::shared_liberties(board, a, b);
+        if shared.is_empty() {
+            return Some(Pass(cutter));
+        }
+        if depth == 0 || self.out_of_nodes() {
+            return None;
+        }
+        for lib in shared {
+            let m = Play(cutter, lib.col, lib.row);
+            if !self.try_move(board, m) {
+                continue;
+            }
+            let cut = self.join(board, a, b, color, depth - 1).is_none();
+            board.unmake_move();
+            if cut {
+                return Some(m);
+            }
+        }
+        None
+    }
+
+    fn liberties_of(board: &Board, coord: Coord) -> Vec<Coord> {
+        match board.get_chain(coord) {
+            Some(chain) => chain.liberties().iter().collect(),
+            None => vec!()
+        }
+    }
+
+    fn shared_liberties(board: &Board, a: Coord, b: Coord) -> Vec<Coord> {
+        let b_chain = board.get_chain(b).unwrap();
+        Self::liberties_of(board, a).into_iter()
+            .filter(|&l| b_chain.liberties().contains(l))
+            .collect()
+    }
+
+    // The liberties of the chain, tried in the order of how many
+    // liberties the attacker gets when playing there. With two
+    // liberties the points next to them are added as well, to find
+    // nets.
+    fn attacking_moves(board: &Board, attacker: Color, libs: &Vec<Coord>) -> Vec<Move> {
+        let mut coords = libs.clone();
+        if libs.len() == 2 {
+            for lib in libs.iter() {
+                for &n in board.neighbours(*lib).iter() {
+                    if board.color(&n) == Empty && !coords.contains(&n) {
+                        coords.push(n);
+                    }
+                }
+            }
+        }
+        Self::sorted_by_liberties(board, &mut coords);
+        coords.iter().map(|c| Play(attacker, c.col, c.row)).collect()
+    }
+
+    // Capturing adjacent opponent chains in atari first, then
+    // extending.
+    fn defending_moves(board: &Board, coord: Coord, color: Color, libs: &Vec<Coord>) -> Vec<Move> {
+        let mut coords = Self::captures_next_to(board, &[coord], color);
+        let mut extensions = libs.clone();
+        Self::sorted_by_liberties(board, &mut extensions);
+        for c in extensions {
+            if !coords.contains(&c) {
+                coords.push(c);
+            }
+        }
+        coords.iter().map(|c| Play(color, c.col, c.row)).collect()
+    }
+
+    fn connecting_moves(board: &Board, a: Coord, b: Coord, color: Color) -> Vec<Move> {
+        let mut coords = Self::shared_liberties(board, a, b);
+        for c in Self::captures_next_to(board, &[a, b], color) {
+            if !coords.contains(&c) {
+                coords.push(c);
+            }
+        }
+        coords.iter().map(|c| Play(color, c.col, c.row)).collect()
+    }
+
+    // The liberties of the opponent chains in atari that touch one of
+    // the given chains.
+    fn captures_next_to(board: &Board, chains: &[Coord], color: Color) -> Vec<Coord> {
+        let mut coords = vec!();
+        for &chain in chains.iter() {
+            let stones = board.get_chain(chain).unwrap().coords();
+            for &stone in stones.iter() {
+                for &n in board.neighbours(stone).iter() {
+                    if board.color(&n) == color.opposite() {
+                        let libs = Self::liberties_of(board, n);
+                        if libs.len() == 1 && !coords.contains(&libs[0]) {
+                            coords.push(libs[0]);
+                        }
+                    }
+                }
+            }
+        }
+        coords
+    }
+
+    fn sorted_by_liberties(board: &Board, coords: &mut Vec<Coord>) {
+        coords.sort_by(|a, b| board.liberty_count(*b).cmp(&board.liberty_count(*a)));
+    }
+
+}
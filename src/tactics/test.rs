@@ -0,0 +1,102 @@
+/************************************************************************
+ *                                                                      *
+ * Copyright 2016 Urban Hafner                                          *
+ *                                                                      *
+ * This file is part of Iomrascálaí.                                    *
+ *                                                                      *
+ * Iomrascálaí is free software: you can redistribute it and/or modify  *
+ * it under the terms of the GNU General Public License as published by *
+ * the Free Software Foundation, either version 3 of the License, or    *
+ * (at your option) any later version.                                  *
+ *                                                                      *
+ * Iomrascálaí is distributed in the hope that it will be useful,       *
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of       *
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the        *
+ * GNU General Public License for more details.                         *
+ *                                                                      *
+ * You should have received a copy of the GNU General Public License    *
+ * along with Iomrascálaí.  If not, see <http://www.gnu.org/licenses/>. *
+ *                                                                      *
+ ************************************************************************/
+
+#![cfg(test)]
+
+use board::Black;
+use board::Board;
+use board::Coord;
+use board::Move;
+use board::Play;
+use board::White;
+use config::Config;
+use ruleset::KgsChinese;
+use sgf::Parser;
+use tactics::Reader;
+
+use std::path::Path;
+
+fn board_with(moves: Vec<Move>) -> Board {
+    let mut board = Board::new(9, 6.5, KgsChinese);
+    for m in moves {
+        board.play_legal_move(m);
+    }
+    board
+}
+
+fn reader() -> Reader {
+    let config = Config::test_config();
+    Reader::new(&config.tactics)
+}
+
+#[test]
+fn attack_reads_out_a_ladder() {
+    let parser = Parser::from_path(Path::new("fixtures/sgf/ladders.sgf")).unwrap();
+    let board = parser.game().unwrap().board();
+    assert!(reader().attack(&board, Coord::new(4, 4)).is_some());
+}
+
+#[test]
+fn attack_captures_a_chain_in_atari() {
+    let board = board_with(vec!(Play(Black, 1, 1), Play(White, 2, 1)));
+    assert_eq!(Some(Play(White, 1, 2)), reader().attack(&board, Coord::new(1, 1)));
+}
+
+#[test]
+fn defend_cant_save_a_chain_on_the_edge() {
+    let board = board_with(vec!(Play(Black, 1, 1), Play(White, 2, 1)));
+    assert_eq!(None, reader().defend(&board, Coord::new(1, 1)));
+}
+
+#[test]
+fn defend_extends_in_the_open() {
+    let board = board_with(vec!(
+        Play(Black, 5, 5), Play(White, 5, 6),
+        Play(Black, 5, 3), Play(White, 4, 5),
+        Play(Black, 9, 9), Play(White, 6, 5)));
+    assert_eq!(Some(Play(Black, 5, 4)), reader().defend(&board, Coord::new(5, 5)));
+}
+
+#[test]
+fn semeai_is_won_by_the_player_to_move() {
+    let board = board_with(vec!(
+        Play(Black, 2, 1), Play(White, 1, 1),
+        Play(Black, 9, 9), Play(White, 3, 1)));
+    let mut reader = reader();
+    assert_eq!(Some(Play(Black, 1, 2)), reader.semeai(&board, Coord::new(2, 1), Coord::new(1, 1)));
+    assert_eq!(Some(Play(White, 2, 2)), reader.semeai(&board, Coord::new(1, 1), Coord::new(2, 1)));
+}
+
+#[test]
+fn connect_and_cut_play_on_the_cutting_point() {
+    let board = board_with(vec!(
+        Play(Black, 3, 3), Play(White, 4, 3),
+        Play(Black, 4, 4), Play(White, 9, 9)));
+    let mut reader = reader();
+    assert_eq!(Some(Play(Black, 3, 4)), reader.connect(&board, Coord::new(3, 3), Coord::new(4, 4)));
+    assert_eq!(Some(Play(White, 3, 4)), reader.cut(&board, Coord::new(3, 3), Coord::new(4, 4)));
+}
+
+#[test]
+fn connect_returns_none_for_the_same_chain() {
+    let board = board_with(vec!(Play(Black, 3, 3), Play(White, 9, 9), Play(Black, 3, 4)));
+    assert_eq!(None, reader().connect(&board, Coord::new(3, 3), Coord::new(3, 4)));
+}
@@ -57,6 +57,10 @@ impl Timer {
 
     }
 
+    pub fn set_config(&mut self, config: Arc<Config>) {
+        self.config = config;
+    }
+
     pub fn setup(&mut self, main_in_s: i64, byo_in_s: i64, stones: i32) {
         self.set_main_time(main_in_s * 1000);
         self.set_byo_time(byo_in_s * 1000);
@@ -79,11 +83,9 @@ impl Timer {
         self.reset_time_stamp();
         let budget = self.budget(game);
         self.current_budget = budget;
-        let msg = format!(
-            "Thinking for {}ms ({}ms time left)",
-            budget.num_milliseconds(),
-            self.main_time_left());
-        self.config.log(msg);
+        info!(self.config, "Thinking for {}ms ({}ms time left)",
+              budget.num_milliseconds(),
+              self.main_time_left());
     }
 
     pub fn ran_out_of_time(&self, win_ratio: f32) -> bool {
@@ -91,13 +93,21 @@ impl Timer {
         let budget5 = self.current_budget / fastplay_budget;
         let elapsed = self.elapsed();
         if elapsed > budget5 && win_ratio > self.config.time_control.fastplay_threshold {
-            self.config.log(format!("Search stopped early. Fastplay rule triggered."));
+            info!(self.config, "Search stopped early. Fastplay rule triggered.");
             true
         } else {
             elapsed > self.current_budget
         }
     }
 
+    pub fn budget_ms(&self) -> i64 {
+        self.current_budget.num_milliseconds()
+    }
+
+    pub fn elapsed_ms(&self) -> i64 {
+        self.elapsed().num_milliseconds()
+    }
+
     pub fn stop(&mut self) {
         self.adjust_time();
     }
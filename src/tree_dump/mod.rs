@@ -0,0 +1,216 @@
+/************************************************************************
+ *                                                                      *
+ * Copyright 2016 Urban Hafner                                          *
+ *                                                                      *
+ * This file is part of Iomrascálaí.                                    *
+ *                                                                      *
+ * Iomrascálaí is free software: you can redistribute it and/or modify  *
+ * it under the terms of the GNU General Public License as published by *
+ * the Free Software Foundation, either version 3 of the License, or    *
+ * (at your option) any later version.                                  *
+ *                                                                      *
+ * Iomrascálaí is distributed in the hope that it will be useful,       *
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of       *
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the        *
+ * GNU General Public License for more details.                         *
+ *                                                                      *
+ * You should have received a copy of the GNU General Public License    *
+ * along with Iomrascálaí.  If not, see <http://www.gnu.org/licenses/>. *
+ *                                                                      *
+ ************************************************************************/
+
+use board::Black;
+use board::Move;
+use board::NoMove;
+use engine::Node;
+
+use std::str::FromStr;
+
+mod test;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum DumpFormat {
+    /// A Graphviz digraph with one box per node.
+    Dot,
+    /// One JSON object per node with its children nested inside.
+    Json,
+}
+
+impl DumpFormat {
+
+    /// The format for the extension of the file name (DOT for `.dot`
+    /// and `.gv` files, JSON for `.json` files), or `None` if the
+    /// extension doesn't determine it.
+    pub fn from_filename(filename: &str) -> Option<DumpFormat> {
+        if filename.ends_with(".dot") || filename.ends_with(".gv") {
+            Some(DumpFormat::Dot)
+        } else if filename.ends_with(".json") {
+            Some(DumpFormat::Json)
+        } else {
+            None
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match *self {
+            DumpFormat::Dot => "dot",
+            DumpFormat::Json => "json",
+        }
+    }
+}
+
+impl FromStr for DumpFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<DumpFormat, String> {
+        match s {
+            "dot" => Ok(DumpFormat::Dot),
+            "json" => Ok(DumpFormat::Json),
+            _ => Err(format!("Unknown tree dump format '{}'", s)),
+        }
+    }
+}
+
+/// Writes the top of a search tree so that it can be inspected why a
+/// move was chosen. Only the nodes at most `max_depth` moves below
+/// the root and with at least `min_playouts` playouts are included.
+/// The children of a node are ordered by their number of playouts.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TreeDump {
+    format: DumpFormat,
+    max_depth: usize,
+    min_playouts: usize,
+}
+
+impl TreeDump {
+
+    pub fn new(format: DumpFormat, max_depth: usize, min_playouts: usize) -> TreeDump {
+        TreeDump {
+            format: format,
+            max_depth: max_depth,
+            min_playouts: min_playouts,
+        }
+    }
+
+    pub fn format(&self) -> DumpFormat {
+        self.format
+    }
+
+    pub fn max_depth(&self) -> usize {
+        self.max_depth
+    }
+
+    pub fn min_playouts(&self) -> usize {
+        self.min_playouts
+    }
+
+    pub fn dump(&self, root: &Node) -> String {
+        let mut out = String::new();
+        match self.format {
+            DumpFormat::Dot => {
+                out.push_str("digraph tree {\n");
+                out.push_str("  node [shape=box, fontname=\"monospace\"];\n");
+                self.dot(root, None, 0, &mut 0, &mut out);
+                out.push_str("}\n");
+            },
+            DumpFormat::Json => {
+                self.json(root, None, 0, &mut out);
+                out.push('\n');
+            }
+        }
+        out
+    }
+
+    fn children<'a>(&self, node: &'a Node, depth: usize) -> Vec<&'a Node> {
+        if depth >= self.max_depth {
+            return vec!();
+        }
+        let mut children: Vec<&Node> = node.children().iter()
+            .filter(|n| n.playouts() >= self.min_playouts)
+            .collect();
+        children.sort_by(|a, b| b.playouts().cmp(&a.playouts()));
+        children
+    }
+
+    fn json(&self, node: &Node, parent: Option<&Node>, depth: usize, out: &mut String) {
+        let (color, vertex) = match parent {
+            Some(_) => (
+                color_name(node.m()).map_or("null".to_string(), |c| format!("\"{}\"", c)),
+                node.m().to_gtp().ok().map_or("null".to_string(), |v| format!("\"{}\"", v))),
+            None => ("null".to_string(), "null".to_string()),
+        };
+        out.push_str(&format!(
+            "{{\"move\":{},\"color\":{},\"playouts\":{},\"plays\":{},\"wins\":{},\"win_ratio\":{},\"amaf_plays\":{},\"amaf_wins\":{},\"prior_plays\":{},\"prior_wins\":{},\"uct_value\":{},\"expected_score\":{},\"children\":[",
+            vertex,
+            color,
+            node.playouts(),
+            json_number(node.plays()),
+            json_number(node.wins()),
+            json_number(node.win_ratio()),
+            json_number(node.amaf_plays()),
+            json_number(node.amaf_wins()),
+            node.prior_plays(),
+            node.prior_wins(),
+            parent.map_or("null".to_string(), |p| json_number(node.uct_value(p))),
+            json_number(node.expected_score())));
+        for (i, child) in self.children(node, depth).iter().enumerate() {
+            if i > 0 {
+                out.push(',');
+            }
+            self.json(child, Some(node), depth + 1, out);
+        }
+        out.push_str("]}");
+    }
+
+    // Writes the node and its children and returns the id of the
+    // node (`next_id` is the id of the next node to write).
+    fn dot(&self, node: &Node, parent: Option<&Node>, depth: usize, next_id: &mut usize, out: &mut String) -> usize {
+        let id = *next_id;
+        *next_id += 1;
+        let title = match parent {
+            Some(_) => match (color_name(node.m()), node.m().to_gtp()) {
+                (Some(color), Ok(vertex)) => format!("{} {}", color, vertex),
+                _ => "?".to_string(),
+            },
+            None => "root".to_string(),
+        };
+        let uct = parent.map_or("-".to_string(), |p| format!("{:.4}", node.uct_value(p)));
+        out.push_str(&format!(
+            "  n{} [label=\"{}\\nplayouts {} plays {} wins {} ({:.1}%)\\namaf {}/{} prior {}/{}\\nuct {} score {:+.1}\"];\n",
+            id,
+            title,
+            node.playouts(),
+            node.plays(),
+            node.wins(),
+            node.win_ratio() * 100.0,
+            node.amaf_wins(),
+            node.amaf_plays(),
+            node.prior_wins(),
+            node.prior_plays(),
+            uct,
+            node.expected_score()));
+        for child in self.children(node, depth) {
+            let child_id = self.dot(child, Some(node), depth + 1, next_id, out);
+            out.push_str(&format!("  n{} -> n{};\n", id, child_id));
+        }
+        id
+    }
+}
+
+fn color_name(m: Move) -> Option<&'static str> {
+    match m {
+        NoMove => None,
+        _ if *m.color() == Black => Some("B"),
+        _ => Some("W"),
+    }
+}
+
+// NaN and infinity (e.g. the UCT value of an unvisited node) aren't
+// valid JSON.
+fn json_number(n: f32) -> String {
+    if n.is_finite() {
+        format!("{}", n)
+    } else {
+        "null".to_string()
+    }
+}
@@ -0,0 +1,99 @@
+/************************************************************************
+ *                                                                      *
+ * Copyright 2016 Urban Hafner                                          *
+ *                                                                      *
+ * This file is part of Iomrascálaí.                                    *
+ *                                                                      *
+ * Iomrascálaí is free software: you can redistribute it and/or modify  *
+ * it under the terms of the GNU General Public License as published by *
+ * the Free Software Foundation, either version 3 of the License, or    *
+ * (at your option) any later version.                                  *
+ *                                                                      *
+ * Iomrascálaí is distributed in the hope that it will be useful,       *
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of       *
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the        *
+ * GNU General Public License for more details.                         *
+ *                                                                      *
+ * You should have received a copy of the GNU General Public License    *
+ * along with Iomrascálaí.  If not, see <http://www.gnu.org/licenses/>. *
+ *                                                                      *
+ ************************************************************************/
+
+#![cfg(test)]
+
+use board::Black;
+use board::Board;
+use config::Config;
+use engine::Node;
+use game::Game;
+use playout::PlayoutResult;
+use ruleset::KgsChinese;
+use super::DumpFormat;
+use super::TreeDump;
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+// A root on a 2x2 board whose first child was searched three times
+// and whose second child was searched once.
+fn root() -> Node {
+    let game = Game::new(2, 0.5, KgsChinese);
+    let mut root = Node::root(&game, Black, Arc::new(Config::test_config()));
+    let board = Board::new(2, 0.5, KgsChinese);
+    for &child in [0, 0, 0, 1].iter() {
+        let playout_result = PlayoutResult::new(board.score(), HashMap::new());
+        root.record_play_on_path(&[child]);
+        root.record_on_path(&[child], &playout_result);
+    }
+    root
+}
+
+#[test]
+fn format_from_str() {
+    assert_eq!(Ok(DumpFormat::Dot), "dot".parse());
+    assert_eq!(Ok(DumpFormat::Json), "json".parse());
+    assert!("xml".parse::<DumpFormat>().is_err());
+}
+
+#[test]
+fn format_from_filename() {
+    assert_eq!(Some(DumpFormat::Dot), DumpFormat::from_filename("tree.dot"));
+    assert_eq!(Some(DumpFormat::Dot), DumpFormat::from_filename("tree.gv"));
+    assert_eq!(Some(DumpFormat::Json), DumpFormat::from_filename("tree.json"));
+    assert_eq!(None, DumpFormat::from_filename("tree"));
+}
+
+#[test]
+fn json_includes_the_children_with_enough_playouts() {
+    let json = TreeDump::new(DumpFormat::Json, 1, 1).dump(&root());
+    assert!(json.starts_with("{\"move\":null,\"color\":null,\"playouts\":4,"));
+    assert!(json.ends_with("]}\n"));
+    assert_eq!(3, json.matches("\"move\":").count());
+    assert_eq!(1, json.matches("\"uct_value\":null").count());
+    assert!(json.find("\"playouts\":3,").unwrap() < json.find("\"playouts\":1,").unwrap());
+}
+
+#[test]
+fn json_leaves_out_nodes_with_too_few_playouts() {
+    let json = TreeDump::new(DumpFormat::Json, 1, 2).dump(&root());
+    assert_eq!(2, json.matches("\"move\":").count());
+    assert!(json.contains("\"color\":\"B\""));
+}
+
+#[test]
+fn json_stops_at_the_max_depth() {
+    let json = TreeDump::new(DumpFormat::Json, 0, 0).dump(&root());
+    assert_eq!(1, json.matches("\"move\":").count());
+    assert!(json.contains("\"children\":[]}"));
+}
+
+#[test]
+fn dot_has_a_box_per_node_and_an_edge_per_child() {
+    let dot = TreeDump::new(DumpFormat::Dot, 1, 1).dump(&root());
+    assert!(dot.starts_with("digraph tree {\n"));
+    assert!(dot.ends_with("}\n"));
+    assert!(dot.contains("n0 [label=\"root\\nplayouts 4 "));
+    assert_eq!(3, dot.matches("[label=").count());
+    assert!(dot.contains("  n0 -> n1;\n"));
+    assert!(dot.contains("  n0 -> n2;\n"));
+}
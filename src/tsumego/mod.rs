@@ -0,0 +1,279 @@
+/************************************************************************
+ *                                                                      *
+ * Copyright 2016 Urban Hafner                                          *
+ *                                                                      *
+ * This file is part of Iomrascálaí.                                    *
+ *                                                                      *
+ * Iomrascálaí is free software: you can redistribute it and/or modify  *
+ * it under the terms of the GNU General Public License as published by *
+ * the Free Software Foundation, either version 3 of the License, or    *
+ * (at your option) any later version.                                  *
+ *                                                                      *
+ * Iomrascálaí is distributed in the hope that it will be useful,       *
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of       *
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the        *
+ * GNU General Public License for more details.                         *
+ *                                                                      *
+ * You should have received a copy of the GNU General Public License    *
+ * along with Iomrascálaí.  If not, see <http://www.gnu.org/licenses/>. *
+ *                                                                      *
+ ************************************************************************/
+
+use board::Black;
+use board::Board;
+use board::Color;
+use board::Coord;
+use board::Empty;
+use board::Move;
+use board::Pass;
+use board::Play;
+use config::TsumegoConfig;
+use sgf::Parser;
+
+use std::cmp;
+use std::fmt;
+
+mod test;
+
+const INFINITY: usize = ::std::usize::MAX / 2;
+
+/// A life-and-death problem: the chain of the target stone is
+/// attacked by the opponent of its owner. Both players may only play
+/// on the empty points of the region (or pass).
+pub struct Problem {
+    board: Board,
+    player: Color,
+    region: Vec<Coord>,
+    target: Coord,
+}
+
+impl Problem {
+
+    /// The player to move is the next player of the board. If the
+    /// region is empty all points of the board are used.
+    pub fn new(board: Board, target: Coord, region: Vec<Coord>) -> Result<Problem, String> {
+        if !target.is_inside(board.size()) || board.color(&target) == Empty {
+            return Err("target is not a stone".to_string());
+        }
+        let region = if region.is_empty() {
+            Coord::for_board_size(board.size())
+        } else {
+            region
+        };
+        Ok(Problem {
+            player: board.next_player(),
+            board: board,
+            region: region,
+            target: target,
+        })
+    }
+
+    /// Reads the problem from an SGF file. The target stone is marked
+    /// with `SQ`, the region with `TR`, and the player to move is
+    /// set with `PL` (defaults to black).
+    pub fn from_sgf(parser: &Parser) -> Result<Problem, String> {
+        let game = try!(parser.game().map_err(|e| format!("illegal move in problem ({:?})", e)));
+        let target = match parser.points("SQ").first() {
+            Some(&coord) => coord,
+            None => return Err("no target stone (SQ) in problem".to_string())
+        };
+        let mut problem = try!(Problem::new(game.board(), target, parser.points("TR")));
+        problem.player = parser.player().unwrap_or(Black);
+        Ok(problem)
+    }
+
+    pub fn player(&self) -> Color {
+        self.player
+    }
+
+    pub fn defender(&self) -> Color {
+        self.board.color(&self.target)
+    }
+
+    // The defender wins if the target chain is unconditionally alive
+    // or if it's still on the board when both players passed.
+    fn winner(&self, board: &Board) -> Option<Color> {
+        let defender = self.defender();
+        if board.color(&self.target) != defender {
+            Some(defender.opposite())
+        } else if board.is_game_over() {
+            Some(defender)
+        } else if board.unconditionally_alive_chains(defender).contains(&board.chain_id(&self.target)) {
+            Some(defender)
+        } else {
+            None
+        }
+    }
+
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Outcome {
+    /// The player to move reaches their goal by playing this move.
+    Win(Move),
+    /// The player to move can't reach their goal.
+    Loss,
+    /// The search hit the node limit.
+    Unknown,
+}
+
+pub struct Solution {
+    pub nodes: usize,
+    pub outcome: Outcome,
+    player: Color,
+    wants_to_kill: bool,
+}
+
+impl fmt::Display for Solution {
+
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let player = if self.player == Black { "B" } else { "W" };
+        let goal = if self.wants_to_kill { "kill" } else { "live" };
+        let s = match self.outcome {
+            Outcome::Win(m) => {
+                let vertex = m.to_gtp().unwrap_or(String::from("pass"));
+                format!("{} {}s at {} ({} nodes)", player, goal, vertex, self.nodes)
+            },
+            Outcome::Loss => format!("{} can't {} ({} nodes)", player, goal, self.nodes),
+            Outcome::Unknown => format!("unknown ({} nodes)", self.nodes),
+        };
+        s.fmt(f)
+    }
+
+}
+
+struct Node {
+    children: Vec<usize>,
+    disproof: usize,
+    m: Move,
+    proof: usize,
+}
+
+/// Proof-number search for life-and-death problems. The tree is
+/// kept in memory, while the board is brought to the most-proving
+/// node by playing the moves from the root and taking them back
+/// afterwards.
+pub struct Solver {
+    max_nodes: usize,
+}
+
+impl Solver {
+
+    pub fn new(config: &TsumegoConfig) -> Solver {
+        Solver { max_nodes: config.nodes }
+    }
+
+    pub fn solve(&self, problem: &Problem) -> Solution {
+        let player = problem.player;
+        let mut board = problem.board.clone();
+        if board.next_player() != player {
+            let pass = Pass(board.next_player());
+            board.play_legal_move(pass);
+        }
+        board.reset_game_over();
+        let (proof, disproof) = Self::evaluate(problem, &board);
+        let mut nodes = vec!(Node { children: vec!(), disproof: disproof, m: Pass(player.opposite()), proof: proof });
+        while nodes[0].proof != 0 && nodes[0].disproof != 0 && nodes.len() < self.max_nodes {
+            let mut path = vec!(0);
+            let mut current = 0;
+            while !nodes[current].children.is_empty() {
+                current = Self::most_proving_child(&nodes, current, path.len() % 2 == 1);
+                board.make_legal_move(nodes[current].m);
+                path.push(current);
+            }
+            let children = Self::expand(problem, &mut board, &mut nodes);
+            if children.is_empty() {
+                // Can only happen if not even a pass is legal.
+                nodes[current].proof = INFINITY;
+                nodes[current].disproof = 0;
+            }
+            nodes[current].children = children;
+            for (depth, &id) in path.iter().enumerate().rev() {
+                Self::update(&mut nodes, id, depth % 2 == 0);
+                if depth > 0 {
+                    board.unmake_move();
+                }
+            }
+        }
+        let outcome = if nodes[0].proof == 0 {
+            match nodes[0].children.iter().find(|&&id| nodes[id].proof == 0) {
+                Some(&id) => Outcome::Win(nodes[id].m),
+                // Already solved without playing a move.
+                None => Outcome::Win(Pass(player))
+            }
+        } else if nodes[0].disproof == 0 {
+            Outcome::Loss
+        } else {
+            Outcome::Unknown
+        };
+        Solution {
+            nodes: nodes.len(),
+            outcome: outcome,
+            player: player,
+            wants_to_kill: player != problem.defender(),
+        }
+    }
+
+    // The root player wants to minimize the proof numbers, the
+    // opponent the disproof numbers.
+    fn most_proving_child(nodes: &Vec<Node>, id: usize, or_node: bool) -> usize {
+        let children = &nodes[id].children;
+        if or_node {
+            *children.iter().min_by_key(|&&c| nodes[c].proof).unwrap()
+        } else {
+            *children.iter().min_by_key(|&&c| nodes[c].disproof).unwrap()
+        }
+    }
+
+    fn expand(problem: &Problem, board: &mut Board, nodes: &mut Vec<Node>) -> Vec<usize> {
+        let color = board.next_player();
+        let mut moves: Vec<Move> = problem.region.iter()
+            .filter(|c| board.color(c) == Empty)
+            .map(|c| Play(color, c.col, c.row))
+            .collect();
+        moves.push(Pass(color));
+        let mut children = vec!();
+        for m in moves {
+            if board.make_move(m).is_err() {
+                continue;
+            }
+            // Suicide isn't useful even where the rules allow it.
+            if m.is_pass() || board.color(&m.coord()) == color {
+                let (proof, disproof) = Self::evaluate(problem, board);
+                nodes.push(Node { children: vec!(), disproof: disproof, m: m, proof: proof });
+                children.push(nodes.len() - 1);
+            }
+            board.unmake_move();
+        }
+        children
+    }
+
+    fn evaluate(problem: &Problem, board: &Board) -> (usize, usize) {
+        match problem.winner(board) {
+            Some(winner) if winner == problem.player => (0, INFINITY),
+            Some(_) => (INFINITY, 0),
+            None => (1, 1)
+        }
+    }
+
+    fn update(nodes: &mut Vec<Node>, id: usize, or_node: bool) {
+        if nodes[id].children.is_empty() {
+            return;
+        }
+        let (proof, disproof) = {
+            let children = &nodes[id].children;
+            let min_proof = children.iter().map(|&c| nodes[c].proof).min().unwrap();
+            let min_disproof = children.iter().map(|&c| nodes[c].disproof).min().unwrap();
+            let sum_proof = children.iter().fold(0, |acc, &c| cmp::min(INFINITY, acc + nodes[c].proof));
+            let sum_disproof = children.iter().fold(0, |acc, &c| cmp::min(INFINITY, acc + nodes[c].disproof));
+            if or_node {
+                (min_proof, sum_disproof)
+            } else {
+                (sum_proof, min_disproof)
+            }
+        };
+        nodes[id].proof = proof;
+        nodes[id].disproof = disproof;
+    }
+
+}
@@ -0,0 +1,60 @@
+/************************************************************************
+ *                                                                      *
+ * Copyright 2016 Urban Hafner                                          *
+ *                                                                      *
+ * This file is part of Iomrascálaí.                                    *
+ *                                                                      *
+ * Iomrascálaí is free software: you can redistribute it and/or modify  *
+ * it under the terms of the GNU General Public License as published by *
+ * the Free Software Foundation, either version 3 of the License, or    *
+ * (at your option) any later version.                                  *
+ *                                                                      *
+ * Iomrascálaí is distributed in the hope that it will be useful,       *
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of       *
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the        *
+ * GNU General Public License for more details.                         *
+ *                                                                      *
+ * You should have received a copy of the GNU General Public License    *
+ * along with Iomrascálaí.  If not, see <http://www.gnu.org/licenses/>. *
+ *                                                                      *
+ ************************************************************************/
+
+#![cfg(test)]
+
+use board::Black;
+use board::Board;
+use board::Coord;
+use board::Play;
+use board::White;
+use config::Config;
+use ruleset::KgsChinese;
+use sgf::Parser;
+use tsumego::Outcome;
+use tsumego::Problem;
+use tsumego::Solver;
+
+use std::path::Path;
+
+fn solve(file: &str) -> Outcome {
+    let parser = Parser::from_path(Path::new(file)).unwrap();
+    let problem = Problem::from_sgf(&parser).unwrap();
+    Solver::new(&Config::test_config().tsumego).solve(&problem).outcome
+}
+
+#[test]
+fn kills_on_the_vital_point() {
+    let outcome = solve("fixtures/sgf/tsumego/kill-straight-three.sgf");
+    assert_eq!(Outcome::Win(Play(Black, 2, 1)), outcome);
+}
+
+#[test]
+fn lives_on_the_vital_point() {
+    let outcome = solve("fixtures/sgf/tsumego/live-straight-three.sgf");
+    assert_eq!(Outcome::Win(Play(White, 2, 1)), outcome);
+}
+
+#[test]
+fn target_must_be_a_stone() {
+    let board = Board::new(9, 6.5, KgsChinese);
+    assert!(Problem::new(board, Coord::new(1, 1), vec!()).is_err());
+}
@@ -0,0 +1,323 @@
+/************************************************************************
+ *                                                                      *
+ * Copyright 2016 Urban Hafner                                          *
+ *                                                                      *
+ * This file is part of Iomrascálaí.                                    *
+ *                                                                      *
+ * Iomrascálaí is free software: you can redistribute it and/or modify  *
+ * it under the terms of the GNU General Public License as published by *
+ * the Free Software Foundation, either version 3 of the License, or    *
+ * (at your option) any later version.                                  *
+ *                                                                      *
+ * Iomrascálaí is distributed in the hope that it will be useful,       *
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of       *
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the        *
+ * GNU General Public License for more details.                         *
+ *                                                                      *
+ * You should have received a copy of the GNU General Public License    *
+ * along with Iomrascálaí.  If not, see <http://www.gnu.org/licenses/>. *
+ *                                                                      *
+ ************************************************************************/
+
+use config::Config;
+
+use rand::Rng;
+use std::fs::File;
+use std::io::prelude::*;
+use toml;
+use toml::value::Table;
+use toml::Value;
+
+mod test;
+
+// The usual exponents of the SPSA gain sequences.
+const ALPHA: f64 = 0.602;
+const GAMMA: f64 = 0.101;
+
+/// A parameter to tune and the range of its values.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Param {
+    integer: bool,
+    max: f64,
+    min: f64,
+    name: String,
+}
+
+impl Param {
+
+    // The value without rounding integer parameters. The checkpoints
+    // keep these so that small steps aren't lost when resuming.
+    fn raw_value(&self, x: f64) -> f64 {
+        self.min + x * (self.max - self.min)
+    }
+
+    fn value(&self, x: f64) -> f64 {
+        let value = self.raw_value(x);
+        if self.integer { value.round() } else { value }
+    }
+
+    fn normalize(&self, value: f64) -> f64 {
+        clamp((value - self.min) / (self.max - self.min))
+    }
+
+    fn to_value(&self, x: f64) -> Value {
+        if self.integer {
+            Value::Integer(self.value(x) as i64)
+        } else {
+            Value::Float(self.value(x))
+        }
+    }
+}
+
+/// What to tune and how. A spec file looks like this:
+///
+/// ```toml
+/// iterations = 200
+/// games = 4
+///
+/// [params]
+/// "tree.rave_equiv" = [0.0, 100.0]
+/// "tree.expand_after" = [0, 20]
+/// ```
+///
+/// In each iteration both perturbed configurations play `games`
+/// games against the reference. `a`, `c`, and `stability` (the `A`
+/// of the SPSA papers) are optional and relative to the range of
+/// the parameters.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Spec {
+    a: f64,
+    c: f64,
+    games: usize,
+    iterations: usize,
+    params: Vec<(String, f64, f64)>,
+    stability: f64,
+}
+
+impl Spec {
+
+    pub fn from_file(filename: &str) -> Result<Spec, String> {
+        let mut contents = String::new();
+        try!(File::open(filename)
+             .and_then(|mut file| file.read_to_string(&mut contents))
+             .map_err(|e| format!("can't read {}: {}", filename, e)));
+        Self::from_toml(&contents)
+    }
+
+    pub fn from_toml(contents: &str) -> Result<Spec, String> {
+        let table: Table = try!(toml::from_str(contents).map_err(|e| format!("invalid TOML: {}", e)));
+        let mut spec = Spec {
+            a: 0.02,
+            c: 0.05,
+            games: 2,
+            iterations: 100,
+            params: vec!(),
+            stability: 10.0,
+        };
+        for (key, value) in table.iter() {
+            match key.as_ref() {
+                "a" => spec.a = try!(Self::positive(key, value)),
+                "c" => spec.c = try!(Self::positive(key, value)),
+                "games" => spec.games = try!(Self::positive(key, value)) as usize,
+                "iterations" => spec.iterations = try!(Self::positive(key, value)) as usize,
+                "stability" => spec.stability = try!(Self::positive(key, value)),
+                "params" => spec.params = try!(Self::params(value)),
+                _ => return Err(format!("{}: unknown key", key)),
+            }
+        }
+        if spec.params.is_empty() {
+            return Err("params: no parameters to tune".to_string());
+        }
+        Ok(spec)
+    }
+
+    pub fn iterations(&self) -> usize {
+        self.iterations
+    }
+
+    pub fn games(&self) -> usize {
+        self.games
+    }
+
+    fn number(value: &Value) -> Option<f64> {
+        match *value {
+            Value::Integer(i) => Some(i as f64),
+            Value::Float(f) => Some(f),
+            _ => None,
+        }
+    }
+
+    fn positive(key: &str, value: &Value) -> Result<f64, String> {
+        match Self::number(value) {
+            Some(n) if n > 0.0 => Ok(n),
+            _ => Err(format!("{}: expected a positive number but found {}", key, value)),
+        }
+    }
+
+    fn params(value: &Value) -> Result<Vec<(String, f64, f64)>, String> {
+        let table = try!(value.as_table().ok_or("params: expected a table".to_string()));
+        let mut params = vec!();
+        for (name, range) in table.iter() {
+            let bounds: Vec<f64> = range.as_array()
+                .map(|values| values.iter().filter_map(Self::number).collect())
+                .unwrap_or(vec!());
+            if bounds.len() != 2 || bounds[0] >= bounds[1] {
+                return Err(format!("params.{}: expected a range [min, max] but found {}", name, range));
+            }
+            params.push((name.clone(), bounds[0], bounds[1]));
+        }
+        Ok(params)
+    }
+}
+
+/// Tunes the parameters of a spec with SPSA (simultaneous
+/// perturbation stochastic approximation). All parameters are
+/// perturbed at the same time and the gradient is estimated from the
+/// results of the two perturbed configurations, so that an iteration
+/// costs the same number of games no matter how many parameters are
+/// tuned. The values are kept scaled to [0, 1] internally.
+pub struct Tuner {
+    iteration: usize,
+    params: Vec<Param>,
+    spec: Spec,
+    values: Vec<f64>,
+}
+
+impl Tuner {
+
+    /// Starts with the values of `config`. All parameters of the spec
+    /// must be numeric parameters of the config.
+    pub fn new(spec: Spec, config: &Config) -> Result<Tuner, String> {
+        let current = config.params();
+        let mut params = vec!();
+        let mut values = vec!();
+        for &(ref name, min, max) in &spec.params {
+            let value = match current.iter().find(|&&(ref n, _)| n == name) {
+                Some(&(_, ref value)) => value,
+                None => return Err(format!("params.{}: unknown parameter", name)),
+            };
+            let number: f64 = try!(value.parse().map_err(|_| format!("params.{}: not a number", name)));
+            let param = Param {
+                integer: value.parse::<i64>().is_ok(),
+                max: max,
+                min: min,
+                name: name.clone(),
+            };
+            values.push(param.normalize(number));
+            params.push(param);
+        }
+        Ok(Tuner {
+            iteration: 0,
+            params: params,
+            spec: spec,
+            values: values,
+        })
+    }
+
+    pub fn iteration(&self) -> usize {
+        self.iteration
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.iteration >= self.spec.iterations
+    }
+
+    /// The current values of the parameters as they are passed to
+    /// `Config::with_param()`.
+    pub fn params(&self) -> Vec<(String, String)> {
+        self.assignments(&self.values)
+    }
+
+    /// One SPSA iteration. `evaluate` gets the values of a perturbed
+    /// configuration and returns its win rate against the reference.
+    pub fn step<F, R: Rng>(&mut self, rng: &mut R, mut evaluate: F) -> Result<(), String>
+        where F: FnMut(&[(String, String)]) -> Result<f64, String> {
+        let k = self.iteration as f64;
+        let ck = self.spec.c / (k + 1.0).powf(GAMMA);
+        let ak = self.spec.a / (k + 1.0 + self.spec.stability).powf(ALPHA);
+        let delta: Vec<f64> = self.values.iter()
+            .map(|_| if rng.gen() { 1.0 } else { -1.0 })
+            .collect();
+        let plus: Vec<f64> = self.values.iter().zip(&delta).map(|(x, d)| clamp(x + ck * d)).collect();
+        let minus: Vec<f64> = self.values.iter().zip(&delta).map(|(x, d)| clamp(x - ck * d)).collect();
+        let f_plus = try!(evaluate(&self.assignments(&plus)));
+        let f_minus = try!(evaluate(&self.assignments(&minus)));
+        for (x, d) in self.values.iter_mut().zip(&delta) {
+            *x = clamp(*x + ak * (f_plus - f_minus) / (2.0 * ck * d));
+        }
+        self.iteration += 1;
+        Ok(())
+    }
+
+    /// Continues from a checkpoint written by `save_checkpoint()`.
+    pub fn load_checkpoint(&mut self, filename: &str) -> Result<(), String> {
+        let mut contents = String::new();
+        try!(File::open(filename)
+             .and_then(|mut file| file.read_to_string(&mut contents))
+             .map_err(|e| format!("can't read {}: {}", filename, e)));
+        let table: Table = try!(toml::from_str(&contents).map_err(|e| format!("{}: invalid TOML: {}", filename, e)));
+        let iteration = table.get("iteration").and_then(|i| i.as_integer());
+        let values = table.get("values").and_then(|v| v.as_table());
+        match (iteration, values) {
+            (Some(iteration), Some(values)) => {
+                for (param, x) in self.params.iter().zip(self.values.iter_mut()) {
+                    if let Some(value) = values.get(&param.name).and_then(Spec::number) {
+                        *x = param.normalize(value);
+                    }
+                }
+                self.iteration = iteration as usize;
+                Ok(())
+            },
+            _ => Err(format!("{}: not a checkpoint", filename)),
+        }
+    }
+
+    pub fn save_checkpoint(&self, filename: &str) -> Result<(), String> {
+        let mut values = Table::new();
+        for (param, x) in self.params.iter().zip(&self.values) {
+            values.insert(param.name.clone(), Value::Float(param.raw_value(*x)));
+        }
+        let mut table = Table::new();
+        table.insert("iteration".to_string(), Value::Integer(self.iteration as i64));
+        table.insert("values".to_string(), Value::Table(values));
+        Self::write(filename, &table)
+    }
+
+    /// Writes the current values as a config file that only contains
+    /// the tuned parameters, e.g. to be used with `--config`.
+    pub fn save_overlay(&self, filename: &str) -> Result<(), String> {
+        Self::write(filename, &self.overlay())
+    }
+
+    fn overlay(&self) -> Table {
+        let mut table = Table::new();
+        for (param, x) in self.params.iter().zip(&self.values) {
+            let mut parts = param.name.splitn(2, '.');
+            let section = parts.next().unwrap().to_string();
+            let key = parts.next().unwrap().to_string();
+            let entry = table.entry(section).or_insert(Value::Table(Table::new()));
+            if let Value::Table(ref mut section) = *entry {
+                section.insert(key, param.to_value(*x));
+            }
+        }
+        table
+    }
+
+    fn write(filename: &str, table: &Table) -> Result<(), String> {
+        let contents = toml::to_string(&Value::Table(table.clone())).unwrap();
+        File::create(filename)
+            .and_then(|mut file| file.write_all(contents.as_bytes()))
+            .map_err(|e| format!("can't write {}: {}", filename, e))
+    }
+
+    fn assignments(&self, values: &[f64]) -> Vec<(String, String)> {
+        self.params.iter().zip(values)
+            .map(|(param, x)| (param.name.clone(), param.to_value(*x).to_string()))
+            .collect()
+    }
+
+}
+
+fn clamp(x: f64) -> f64 {
+    x.max(0.0).min(1.0)
+}
@@ -0,0 +1,116 @@
+/************************************************************************
+ *                                                                      *
+ * Copyright 2016 Urban Hafner                                          *
+ *                                                                      *
+ * This file is part of Iomrascálaí.                                    *
+ *                                                                      *
+ * Iomrascálaí is free software: you can redistribute it and/or modify  *
+ * it under the terms of the GNU General Public License as published by *
+ * the Free Software Foundation, either version 3 of the License, or    *
+ * (at your option) any later version.                                  *
+ *                                                                      *
+ * Iomrascálaí is distributed in the hope that it will be useful,       *
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of       *
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the        *
+ * GNU General Public License for more details.                         *
+ *                                                                      *
+ * You should have received a copy of the GNU General Public License    *
+ * along with Iomrascálaí.  If not, see <http://www.gnu.org/licenses/>. *
+ *                                                                      *
+ ************************************************************************/
+
+#![cfg(test)]
+
+use config::Config;
+use ruleset::KgsChinese;
+use super::Spec;
+use super::Tuner;
+
+use rand::SeedableRng;
+use rand::XorShiftRng;
+use std::env::temp_dir;
+
+fn config() -> Config {
+    Config::default(false, false, KgsChinese, Some(1))
+}
+
+fn spec() -> Spec {
+    Spec::from_toml("iterations = 300\n[params]\n\"tree.rave_equiv\" = [0.0, 100.0]\n\"tree.expand_after\" = [0, 20]").unwrap()
+}
+
+fn value(tuner: &Tuner, name: &str) -> f64 {
+    tuner.params().iter().find(|&&(ref n, _)| n == name).unwrap().1.parse().unwrap()
+}
+
+#[test]
+fn the_spec_has_defaults_for_everything_but_the_params() {
+    let spec = spec();
+    assert_eq!(300, spec.iterations());
+    assert_eq!(2, spec.games());
+    assert_eq!(2, spec.params.len());
+}
+
+#[test]
+fn invalid_specs_are_rejected() {
+    assert_eq!(Err("params: no parameters to tune".to_string()), Spec::from_toml("games = 4"));
+    assert_eq!(Err("gmaes: unknown key".to_string()), Spec::from_toml("gmaes = 4"));
+    assert!(Spec::from_toml("[params]\n\"tree.rave_equiv\" = [100.0, 0.0]").is_err());
+    assert!(Spec::from_toml("[params]\n\"tree.rave_equiv\" = 10.0").is_err());
+}
+
+#[test]
+fn tuning_starts_from_the_values_of_the_config() {
+    let tuner = Tuner::new(spec(), &config()).unwrap();
+    assert_eq!(20.0, value(&tuner, "tree.rave_equiv"));
+    assert_eq!("1", tuner.params().iter().find(|&&(ref n, _)| n == "tree.expand_after").unwrap().1);
+}
+
+#[test]
+fn unknown_parameters_are_rejected() {
+    let spec = Spec::from_toml("[params]\n\"tree.rave_equv\" = [0.0, 1.0]").unwrap();
+    assert_eq!(Err("params.tree.rave_equv: unknown parameter".to_string()), Tuner::new(spec, &config()).map(|_| ()));
+}
+
+#[test]
+fn spsa_finds_the_maximum_of_a_smooth_function() {
+    let spec = Spec::from_toml("a = 0.5\niterations = 300\n[params]\n\"tree.rave_equiv\" = [0.0, 100.0]\n\"tree.expand_after\" = [0, 20]").unwrap();
+    let mut tuner = Tuner::new(spec, &config()).unwrap();
+    let mut rng = XorShiftRng::from_seed([1, 2, 3, 4]);
+    while !tuner.is_done() {
+        tuner.step(&mut rng, |params| {
+            let x: f64 = params[1].1.parse().unwrap();
+            Ok(1.0 - ((x - 70.0) / 100.0).powi(2))
+        }).unwrap();
+    }
+    assert!((value(&tuner, "tree.rave_equiv") - 70.0).abs() < 10.0);
+}
+
+#[test]
+fn the_checkpoint_can_be_loaded_again() {
+    let filename = temp_dir().join("iomrascalai-tuner-checkpoint.toml");
+    let filename = filename.to_str().unwrap();
+    let mut tuner = Tuner::new(spec(), &config()).unwrap();
+    let mut rng = XorShiftRng::from_seed([1, 2, 3, 4]);
+    for _ in 0..3 {
+        tuner.step(&mut rng, |params| Ok(params[1].1.parse::<f64>().unwrap() / 100.0)).unwrap();
+    }
+    tuner.save_checkpoint(filename).unwrap();
+    let mut resumed = Tuner::new(spec(), &config()).unwrap();
+    resumed.load_checkpoint(filename).unwrap();
+    assert_eq!(3, resumed.iteration());
+    assert_eq!(value(&tuner, "tree.expand_after"), value(&resumed, "tree.expand_after"));
+    assert!((value(&tuner, "tree.rave_equiv") - value(&resumed, "tree.rave_equiv")).abs() < 1e-9);
+    // The integer parameters aren't rounded in the checkpoint.
+    for (x, y) in tuner.values.iter().zip(&resumed.values) {
+        assert!((x - y).abs() < 1e-9);
+    }
+}
+
+#[test]
+fn the_overlay_is_a_valid_config_file() {
+    let tuner = Tuner::new(spec(), &config()).unwrap();
+    let toml = ::toml::to_string(&::toml::Value::Table(tuner.overlay())).unwrap();
+    let tuned = Config::from_toml(toml, false, false, KgsChinese, Some(1)).unwrap();
+    assert_eq!(20.0, tuned.tree.rave_equiv);
+    assert_eq!(1, tuned.tree.expand_after);
+}
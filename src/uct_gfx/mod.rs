@@ -22,8 +22,10 @@ impl<'a> Display for UctGfx<'a> {
         let mut s = String::new();
         s.push_str("LABEL ");
         for child in self.root.children() {
-            s.push_str(&format!("{} ", child.m().to_gtp()));
-            s.push_str(&format!("{} ", child.playouts()));
+            if let Ok(vertex) = child.m().to_gtp() {
+                s.push_str(&format!("{} ", vertex));
+                s.push_str(&format!("{} ", child.playouts()));
+            }
         }
 
         s.fmt(f)